@@ -0,0 +1,217 @@
+//! Derive macro for the `FromInstance` trait of the `blend` crate. Enable the `derive` feature of `blend` instead of
+//! depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{ext::IdentExt, parse_macro_input, Data, DeriveInput, Error, Field, Fields, LitStr};
+
+/// Implements `blend::runtime::FromInstance` for a struct with named fields. Each field is read with
+/// `blend::runtime::FromField` from the field of the instance with the same name, unless an attribute says otherwise:
+///
+/// * `#[blend(field = "loc")]` reads it from another field.
+/// * `#[blend(path = "id.name")]` reads it from a field of a struct inside the instance, following the field names
+///   separated by dots.
+///
+/// Use `Option` for fields which can be null pointers and `Vec` for `ListBase`s, arrays of structs and pointers to
+/// lists. When a field can't be read, the error says which one.
+///
+/// Any instance with the fields is accepted, whatever its struct. `#[blend(type = "Mesh")]` on the struct only accepts
+/// instances of the Blender struct `Mesh`, returning an `AccessError::WrongType` for the others.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::{Blend, FromInstance};
+/// #[derive(FromInstance)]
+/// struct Object {
+///     #[blend(path = "id.name")]
+///     name: String,
+///     #[blend(field = "loc")]
+///     location: [f32; 3],
+///     #[blend(field = "data")]
+///     mesh: Option<Mesh>,
+/// }
+///
+/// #[derive(FromInstance)]
+/// #[blend(type = "Mesh")]
+/// struct Mesh {
+///     #[blend(path = "id.name")]
+///     name: String,
+///     #[blend(field = "mat")]
+///     materials: Vec<Material>,
+/// }
+///
+/// #[derive(FromInstance)]
+/// struct Material {
+///     #[blend(path = "id.name")]
+///     name: String,
+///     r: f32,
+///     g: f32,
+///     b: f32,
+/// }
+///
+/// # fn main() {
+///     # let blend = Blend::from_path("../examples/blend_files/2_80.blend").expect("error loading blend file");
+/// for obj in blend.instances_with_code(*b"OB") {
+///     match Object::from_instance(&obj) {
+///         Ok(obj) => println!("\"{}\" at {:?}", obj.name, obj.location),
+///         // Cameras and lights aren't meshes: "could not read 'mesh': field 'OB\"Camera\".data<Camera>' is declared..."
+///         Err(err) => println!("{}", err),
+///     }
+/// }
+/// # let cube = blend.instances_with_code(*b"OB").find(|obj| obj.get("id").get_string("name") == "OBCube").unwrap();
+/// # let cube = Object::from_instance(&cube).unwrap();
+/// # let mesh = cube.mesh.unwrap();
+/// # assert_eq!(mesh.name, "MECube");
+/// # assert_eq!(mesh.materials.len(), 1);
+/// # assert_eq!(mesh.materials[0].name, "MAMaterial");
+/// # let camera = blend.instances_with_code(*b"OB").next().unwrap();
+/// # let error = Object::from_instance(&camera).err().unwrap();
+/// # assert_eq!(error.field, "mesh");
+/// # assert!(matches!(error.error, blend::runtime::AccessError::WrongType { .. }));
+/// # }
+/// ```
+#[proc_macro_derive(FromInstance, attributes(blend))]
+pub fn derive_from_instance(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    from_instance(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn from_instance(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "FromInstance can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "FromInstance can only be derived for structs",
+            ))
+        }
+    };
+
+    let reads = fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().expect("named fields have a name");
+            let ty = &field.ty;
+            let name = ident.unraw().to_string();
+            let path = path(field)?.unwrap_or_else(|| name.clone());
+
+            Ok(quote! {
+                #ident: <#ty as ::blend::runtime::FromField>::from_path(instance, #path)
+                    .map_err(|err| err.in_field(#name))?,
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let type_check = type_name(input)?.map(|type_name| {
+        quote! {
+            if instance.type_name != #type_name {
+                return ::std::result::Result::Err(::blend::runtime::AccessError::WrongType {
+                    field: instance.path(),
+                    expected: ::std::string::String::from(#type_name),
+                    found: ::std::string::String::from(instance.type_name),
+                }
+                .into());
+            }
+        }
+    });
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::blend::runtime::FromInstance for #ident #ty_generics #where_clause {
+            fn from_instance(
+                instance: &::blend::runtime::Instance,
+            ) -> ::std::result::Result<Self, ::blend::runtime::FromInstanceError> {
+                #type_check
+                ::std::result::Result::Ok(Self {
+                    #(#reads)*
+                })
+            }
+        }
+    })
+}
+
+/// Returns the name of the Blender struct the instances must have, if it is set by a `#[blend(type = "...")]` attribute
+/// on the struct.
+fn type_name(input: &DeriveInput) -> syn::Result<Option<String>> {
+    let mut type_name = None;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("blend"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("type") {
+                return Err(meta.error("expected `type`"));
+            }
+
+            let value: LitStr = meta.value()?.parse()?;
+            if type_name.is_some() {
+                return Err(Error::new(value.span(), "`type` can only be set once"));
+            }
+            if value.value().is_empty() {
+                return Err(Error::new(value.span(), "empty struct name"));
+            }
+
+            type_name = Some(value.value());
+            Ok(())
+        })?;
+    }
+
+    Ok(type_name)
+}
+
+/// Returns the path to the field of the instance `field` is read from, if it is set by a `#[blend(...)]` attribute.
+fn path(field: &Field) -> syn::Result<Option<String>> {
+    let mut path = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("blend"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let is_field = meta.path.is_ident("field");
+            if !is_field && !meta.path.is_ident("path") {
+                return Err(meta.error("expected `field` or `path`"));
+            }
+
+            let value: LitStr = meta.value()?.parse()?;
+            if path.is_some() {
+                return Err(Error::new(
+                    value.span(),
+                    "`field` and `path` can only be set once",
+                ));
+            }
+            if value.value().split('.').any(str::is_empty) {
+                return Err(Error::new(value.span(), "empty field name"));
+            }
+            if is_field && value.value().contains('.') {
+                return Err(Error::new(
+                    value.span(),
+                    "`field` takes a single field name, use `path` to read fields of other structs",
+                ));
+            }
+
+            path = Some(value.value());
+            Ok(())
+        })?;
+    }
+
+    Ok(path)
+}
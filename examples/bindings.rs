@@ -0,0 +1,33 @@
+use blend::{codegen::BindingGenerator, Blend};
+use std::env;
+
+/// Prints Rust bindings for the structs of a blend file's DNA:
+///
+/// cargo run --example bindings -- file.blend Object Mesh > blender.rs
+///
+/// The struct names after the path select which structs get a wrapper, every struct gets one when there are none.
+/// Without arguments it prints the bindings for the objects and meshes of the 3.5 example file.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (path, structs) = match &args[..] {
+        [] => (
+            String::from("examples/blend_files/3_5.blend"),
+            vec![String::from("Object"), String::from("Mesh")],
+        ),
+        [path, structs @ ..] => (path.clone(), structs.to_vec()),
+    };
+
+    let blend = Blend::from_path(&path).expect("error loading blend file");
+
+    let generator = BindingGenerator {
+        structs,
+        ..BindingGenerator::default()
+    };
+
+    let mut bindings = String::new();
+    generator
+        .generate(&blend.blend.dna, &mut bindings)
+        .expect("error writing bindings");
+    print!("{}", bindings);
+}
@@ -0,0 +1,34 @@
+use blend::Blend;
+use std::env;
+
+/// Prints the differences between the DNA of two blend files, usually saved by different versions of Blender:
+///
+/// cargo run --example dna_diff -- old.blend new.blend
+///
+/// Without arguments it compares the 2.80 and 3.5 example files.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (old_path, new_path) = match &args[..] {
+        [] => (
+            String::from("examples/blend_files/2_80.blend"),
+            String::from("examples/blend_files/3_5.blend"),
+        ),
+        [old_path, new_path] => (old_path.clone(), new_path.clone()),
+        _ => {
+            eprintln!("usage: dna_diff <old.blend> <new.blend>");
+            std::process::exit(1);
+        }
+    };
+
+    let old = Blend::from_path(&old_path).expect("error loading old blend file");
+    let new = Blend::from_path(&new_path).expect("error loading new blend file");
+
+    let diff = old.blend.dna.diff(&new.blend.dna);
+
+    if diff.is_empty() {
+        println!("{} and {} have the same DNA", old_path, new_path);
+    } else {
+        print!("{}", diff);
+    }
+}
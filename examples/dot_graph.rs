@@ -0,0 +1,46 @@
+use blend::{runtime::DotExporter, Blend};
+use std::{convert::TryInto, env};
+
+/// Prints the datablocks of a blend file and the pointers between them as a Graphviz graph:
+///
+/// cargo run --example dot_graph -- file.blend OB ME MA > graph.dot
+///
+/// The block codes after the path select which datablocks are written. Without arguments it writes the objects, meshes
+/// and materials of the 2.80 example file.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (path, codes) = match &args[..] {
+        [] => (
+            String::from("examples/blend_files/2_80.blend"),
+            vec![String::from("OB"), String::from("ME"), String::from("MA")],
+        ),
+        [path, codes @ ..] => (path.clone(), codes.to_vec()),
+    };
+
+    let codes = codes
+        .iter()
+        .map(|code| match code.as_bytes().try_into() {
+            Ok(code) => code,
+            Err(_) => {
+                eprintln!(
+                    "usage: dot_graph <file.blend> [CODE...], where codes are two characters long"
+                );
+                std::process::exit(1);
+            }
+        })
+        .collect();
+
+    let blend = Blend::from_path(&path).expect("error loading blend file");
+
+    let exporter = DotExporter {
+        codes,
+        ..DotExporter::default()
+    };
+
+    let mut dot = String::new();
+    exporter
+        .export(&blend, &mut dot)
+        .expect("error writing graph");
+    print!("{}", dot);
+}
@@ -1,12 +1,12 @@
 use blend::Blend;
-use libflate::gzip::Decoder;
 use std::{
     env,
     fs::File,
-    io::{self, BufWriter, Read, Write},
+    io::{self, BufWriter, Write},
     path::{self, PathBuf},
 };
 
+// Compressed blend files can be printed too if the `gzip` or `zstd` features are enabled.
 fn print_blend(file_name: impl AsRef<str>) -> Result<(), io::Error> {
     let file_name = file_name.as_ref();
     let base_path = path::PathBuf::from(
@@ -17,19 +17,7 @@ fn print_blend(file_name: impl AsRef<str>) -> Result<(), io::Error> {
     let output_path = base_path.join(format!("examples/print_blend/output_{}.txt", file_name));
 
     println!("{}", blend_path.display());
-    let mut file = File::open(blend_path)?;
-    let mut data = Vec::new();
-    file.read_to_end(&mut data)?;
-
-    if data[0..7] != *b"BLENDER" {
-        let mut decoder = Decoder::new(&data[..])?;
-        let mut gzip_data = Vec::new();
-        decoder.read_to_end(&mut gzip_data)?;
-
-        data = gzip_data;
-    }
-
-    let blend = Blend::new(&data[..]).expect("error loading blend file");
+    let blend = Blend::from_path(blend_path).expect("error loading blend file");
     let mut output_path_without_file = PathBuf::from(&output_path);
     output_path_without_file.pop();
     std::fs::create_dir_all(&output_path_without_file)?;
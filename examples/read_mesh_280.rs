@@ -91,7 +91,7 @@ fn instance_to_mesh(mesh: Instance) -> Option<Mesh> {
         }
     }
 
-    let faces: Vec<_> = (&verts_array_buff[..])
+    let faces: Vec<_> = verts_array_buff
         .chunks(3)
         .enumerate()
         .map(|(i, pos)| {
@@ -0,0 +1,443 @@
+use crate::{
+    parsers::{
+        dna::Dna,
+        dna_diff::FieldType,
+        field::{parse_field, FieldInfo},
+        primitive::PrimitiveKind,
+        BlendParseError,
+    },
+    runtime::Blend,
+};
+use std::{collections::HashSet, fmt, fs, path::Path};
+
+/// Generates Rust bindings for the structs of a `Dna`: one wrapper around `Instance` per struct, with a method for
+/// each field. Reading `object.loc()` instead of `instance.get_f32_vec("loc")` turns a typo in a field name into a
+/// compile error, and the wrappers cost nothing over the `Instance` they hold.
+///
+/// For the struct `Object` the generated code looks like this:
+///
+/// ```text
+/// pub struct Object<'a>(pub Instance<'a>);
+///
+/// impl<'a> Object<'a> {
+///     /// `ID id`
+///     pub fn id(&self) -> ID<'a> { ... }
+///     /// `float loc[3]`
+///     pub fn loc(&self) -> [f32; 3] { ... }
+///     /// `void *data`
+///     pub fn data(&self) -> Option<Instance<'a>> { ... }
+///     /// `Material **mat`
+///     pub fn mat(&self) -> impl Iterator<Item = Material<'a>> { ... }
+///     // ...
+/// }
+/// ```
+///
+/// Fields are read with the panicking accessors of `Instance`, except for pointers, which return `None` when they are
+/// null or don't point to a block. `ListBase`s, arrays of structs and pointers to arrays of pointers are read as
+/// iterators. Function pointers, arrays of pointers and `_pad` fields are left out. A wrapper can be created from an
+/// `Instance` of its struct with `TryFrom`.
+///
+/// The bindings only match files saved by the version of Blender the `Dna` comes from, as fields are added, removed
+/// and renamed between versions.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::{Blend, codegen::BindingGenerator};
+/// # fn main() {
+///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+/// let generator = BindingGenerator {
+///     structs: vec![String::from("Object"), String::from("ID")],
+///     ..BindingGenerator::default()
+/// };
+///
+/// let mut bindings = String::new();
+/// generator.generate(&blend.blend.dna, &mut bindings).unwrap();
+/// # assert!(bindings.contains("pub struct Object<'a>(pub ::blend::runtime::Instance<'a>);"));
+/// # assert!(bindings.contains("pub fn id(&self) -> ID<'a> {"));
+/// # assert!(bindings.contains("pub fn loc(&self) -> [f32; 3] {"));
+/// # assert!(bindings.contains("pub fn data(&self) -> ::std::option::Option<::blend::runtime::Instance<'a>> {"));
+/// # assert!(bindings.contains("pub fn parent(&self) -> ::std::option::Option<Object<'a>> {"));
+/// # assert!(!bindings.contains("pub struct Mesh"));
+/// # }
+/// ```
+///
+/// In a build script, `generate_file` writes the bindings where they can be included with
+/// `include!(concat!(env!("OUT_DIR"), "/blender.rs"))`:
+///
+/// ```no_run
+/// # use blend::codegen::BindingGenerator;
+/// # fn main() {
+/// let out_dir = std::env::var("OUT_DIR").unwrap();
+///
+/// BindingGenerator::default()
+///     .generate_file("assets/level.blend", format!("{}/blender.rs", out_dir))
+///     .expect("error generating bindings");
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindingGenerator {
+    /// The names of the structs to generate wrappers for, like `"Object"`. Every struct is generated when empty.
+    /// Fields holding structs without a wrapper are read as `Instance`s.
+    pub structs: Vec<String>,
+    /// How the generated code refers to this crate. `::blend` by default.
+    pub crate_path: String,
+}
+
+impl Default for BindingGenerator {
+    fn default() -> Self {
+        BindingGenerator {
+            structs: Vec::new(),
+            crate_path: String::from("::blend"),
+        }
+    }
+}
+
+impl BindingGenerator {
+    /// Writes the bindings for the structs of `dna` to `out`.
+    pub fn generate(&self, dna: &Dna, out: &mut impl fmt::Write) -> fmt::Result {
+        let wrapped = dna
+            .structs
+            .iter()
+            .map(|dna_struct| &dna.types[dna_struct.type_index].name[..])
+            .filter(|name| self.structs.is_empty() || self.structs.iter().any(|s| s == name))
+            .collect::<HashSet<_>>();
+        let instance = format!("{}::runtime::Instance<'a>", self.crate_path);
+        let error = format!("{}::runtime::AccessError", self.crate_path);
+
+        writeln!(out, "// Generated by blend::codegen. Do not edit.")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/// Returns `None` if the pointer read by `value` is null or doesn't point to a block."
+        )?;
+        writeln!(out, "#[allow(dead_code)]")?;
+        writeln!(
+            out,
+            "fn nullable<T>(value: ::std::result::Result<T, {}>) -> ::std::option::Option<T> {{",
+            error
+        )?;
+        writeln!(out, "    match value {{")?;
+        writeln!(out, "        Ok(value) => Some(value),")?;
+        writeln!(
+            out,
+            "        Err({0}::NullPointer {{ .. }}) | Err({0}::DanglingPointer {{ .. }}) => None,",
+            error
+        )?;
+        writeln!(out, "        Err(err) => panic!(\"{{}}\", err),")?;
+        writeln!(out, "    }}")?;
+        writeln!(out, "}}")?;
+        writeln!(out)?;
+        writeln!(
+            out,
+            "/// Returns the elements of the list read by `list`, which has none if it is a null pointer."
+        )?;
+        writeln!(out, "#[allow(dead_code)]")?;
+        writeln!(out, "fn elements<'a>(")?;
+        writeln!(
+            out,
+            "    list: ::std::result::Result<impl ::std::iter::Iterator<Item = ::std::result::Result<{0}, {1}>>, {1}>,",
+            instance, error
+        )?;
+        writeln!(
+            out,
+            ") -> impl ::std::iter::Iterator<Item = {}> {{",
+            instance
+        )?;
+        writeln!(out, "    nullable(list)")?;
+        writeln!(out, "        .into_iter()")?;
+        writeln!(out, "        .flatten()")?;
+        writeln!(
+            out,
+            "        .map(|element| element.unwrap_or_else(|err| panic!(\"{{}}\", err)))"
+        )?;
+        writeln!(out, "}}")?;
+
+        for dna_struct in &dna.structs {
+            let struct_name = &dna.types[dna_struct.type_index].name;
+
+            if !wrapped.contains(&struct_name[..]) {
+                continue;
+            }
+
+            writeln!(out)?;
+            writeln!(
+                out,
+                "/// Wraps an `Instance` of the struct `{}`.",
+                struct_name
+            )?;
+            writeln!(
+                out,
+                "#[allow(non_camel_case_types, clippy::upper_case_acronyms)]"
+            )?;
+            writeln!(out, "#[derive(Debug, Clone)]")?;
+            writeln!(out, "#[repr(transparent)]")?;
+            writeln!(out, "pub struct {}<'a>(pub {});", struct_name, instance)?;
+            writeln!(out)?;
+            writeln!(
+                out,
+                "impl<'a> ::std::convert::TryFrom<{}> for {}<'a> {{",
+                instance, struct_name
+            )?;
+            writeln!(out, "    type Error = {};", instance)?;
+            writeln!(out)?;
+            writeln!(
+                out,
+                "    /// Returns the instance back if it is not a `{}`.",
+                struct_name
+            )?;
+            writeln!(
+                out,
+                "    fn try_from(instance: {}) -> ::std::result::Result<Self, Self::Error> {{",
+                instance
+            )?;
+            writeln!(
+                out,
+                "        if instance.type_name == \"{}\" {{",
+                struct_name
+            )?;
+            writeln!(out, "            Ok(Self(instance))")?;
+            writeln!(out, "        }} else {{")?;
+            writeln!(out, "            Err(instance)")?;
+            writeln!(out, "        }}")?;
+            writeln!(out, "    }}")?;
+            writeln!(out, "}}")?;
+            writeln!(out)?;
+            writeln!(
+                out,
+                "#[allow(non_snake_case, clippy::wrong_self_convention)]"
+            )?;
+            writeln!(out, "impl<'a> {}<'a> {{", struct_name)?;
+
+            let mut first = true;
+            for field in &dna_struct.fields {
+                let (name, info) = match parse_field(&dna.names[field.name_index]) {
+                    Ok((_, (name, info))) => (name, info),
+                    Err(_) => continue,
+                };
+                if name.starts_with("_pad") {
+                    continue;
+                }
+
+                let field_type = FieldType {
+                    type_name: dna.types[field.type_index].name.clone(),
+                    info,
+                };
+                let (return_type, body) = match self.accessor(
+                    dna,
+                    &wrapped,
+                    name,
+                    &field_type,
+                    dna.types[field.type_index].bytes_len,
+                ) {
+                    Some(accessor) => accessor,
+                    None => continue,
+                };
+
+                if !first {
+                    writeln!(out)?;
+                }
+                first = false;
+
+                writeln!(out, "    /// `{}`", field_type.declaration(name))?;
+                writeln!(
+                    out,
+                    "    pub fn {}(&self) -> {} {{",
+                    method_name(name),
+                    return_type
+                )?;
+                for line in body {
+                    writeln!(out, "        {}", line)?;
+                }
+                writeln!(out, "    }}")?;
+            }
+
+            writeln!(out, "}}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the `Dna` of the file at `blend_path` and writes its bindings to `out_path`. Meant to be called from a
+    /// build script, so it also tells cargo to run the build script again when the blend file changes.
+    pub fn generate_file(
+        &self,
+        blend_path: impl AsRef<Path>,
+        out_path: impl AsRef<Path>,
+    ) -> Result<(), BlendParseError> {
+        let blend_path = blend_path.as_ref();
+        let blend = Blend::from_path(blend_path)?;
+
+        let mut bindings = String::new();
+        self.generate(&blend.blend.dna, &mut bindings)
+            .expect("writing to a String can't fail");
+
+        fs::write(out_path, bindings).map_err(BlendParseError::IoError)?;
+        println!("cargo:rerun-if-changed={}", blend_path.display());
+
+        Ok(())
+    }
+
+    /// Returns the return type and the body of the method reading the field `name`, or `None` if the field is left
+    /// out.
+    fn accessor(
+        &self,
+        dna: &Dna,
+        wrapped: &HashSet<&str>,
+        name: &str,
+        field_type: &FieldType,
+        bytes_len: usize,
+    ) -> Option<(String, Vec<String>)> {
+        let type_name = &field_type.type_name[..];
+        let instance = format!("{}::runtime::Instance<'a>", self.crate_path);
+        let is_struct = dna
+            .structs
+            .iter()
+            .any(|dna_struct| dna.types[dna_struct.type_index].name == type_name);
+
+        // The wrapper of the struct, or `Instance` if there is none, and what turns an `Instance` into it
+        let (wrapper, wrap) = if wrapped.contains(type_name) {
+            (format!("{}<'a>", type_name), format!(".map({})", type_name))
+        } else {
+            (instance.clone(), String::new())
+        };
+
+        let accessor = match &field_type.info {
+            FieldInfo::Value if type_name == "ListBase" => (
+                format!("impl ::std::iter::Iterator<Item = {}>", instance),
+                vec![format!("elements(self.0.try_get_iter(\"{}\"))", name)],
+            ),
+            FieldInfo::Value if is_struct => {
+                let get = format!("self.0.get(\"{}\")", name);
+                if wrapped.contains(type_name) {
+                    (wrapper, vec![format!("{}({})", type_name, get)])
+                } else {
+                    (wrapper, vec![get])
+                }
+            }
+            FieldInfo::Value => {
+                let primitive = primitive(type_name, bytes_len)?;
+                (
+                    String::from(primitive),
+                    vec![format!("self.0.get_value::<{}>(\"{}\")", primitive, name)],
+                )
+            }
+            FieldInfo::ValueArray { .. } if is_struct => (
+                format!("impl ::std::iter::Iterator<Item = {}>", wrapper),
+                vec![format!(
+                    "elements(self.0.try_get_iter(\"{}\")){}",
+                    name, wrap
+                )],
+            ),
+            FieldInfo::ValueArray { dimensions, .. }
+                if dimensions.len() == 1 && type_name == "char" =>
+            {
+                (
+                    String::from("::std::string::String"),
+                    vec![format!("self.0.get_string(\"{}\")", name)],
+                )
+            }
+            FieldInfo::ValueArray { dimensions, .. } => {
+                let primitive = primitive(type_name, bytes_len)?;
+                (
+                    array_type(primitive, dimensions),
+                    vec![
+                        format!(
+                            "let values = self.0.get_vec::<{}>(\"{}\");",
+                            primitive, name
+                        ),
+                        array_from_values(dimensions),
+                    ],
+                )
+            }
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } if is_struct => (
+                format!("::std::option::Option<{}>", wrapper),
+                vec![format!("nullable(self.0.try_get(\"{}\")){}", name, wrap)],
+            ),
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } => match primitive(type_name, bytes_len) {
+                Some(primitive) => (
+                    format!("::std::option::Option<::std::vec::Vec<{}>>", primitive),
+                    vec![format!(
+                        "nullable(self.0.try_get_vec::<{}>(\"{}\"))",
+                        primitive, name
+                    )],
+                ),
+                // `void` and types the DNA doesn't describe can point to anything, so any error means there is no
+                // struct to return
+                None => (
+                    format!("::std::option::Option<{}>", instance),
+                    vec![format!("self.0.try_get(\"{}\").ok()", name)],
+                ),
+            },
+            FieldInfo::Pointer {
+                indirection_count: 2,
+            } if is_struct => (
+                format!("impl ::std::iter::Iterator<Item = {}>", wrapper),
+                vec![format!(
+                    "elements(self.0.try_get_iter(\"{}\")){}",
+                    name, wrap
+                )],
+            ),
+            _ => return None,
+        };
+
+        Some(accessor)
+    }
+}
+
+/// Returns the Rust type read from fields of the primitive DNA type `type_name`, which is `bytes_len` bytes long.
+fn primitive(type_name: &str, bytes_len: usize) -> Option<&'static str> {
+    PrimitiveKind::from_type_name(type_name, bytes_len).map(PrimitiveKind::rust_name)
+}
+
+/// Returns the nested array type of a field, like `[[f32; 4]; 4]` for `float obmat[4][4]`.
+fn array_type(primitive: &str, dimensions: &[usize]) -> String {
+    dimensions
+        .iter()
+        .rev()
+        .fold(String::from(primitive), |inner, len| {
+            format!("[{}; {}]", inner, len)
+        })
+}
+
+/// Returns the expression building the nested array of a field from the flat `values` of all its elements.
+fn array_from_values(dimensions: &[usize]) -> String {
+    let index = (0..dimensions.len())
+        .map(|i| {
+            let stride: usize = dimensions[i + 1..].iter().product();
+            match stride {
+                1 => format!("i{}", i),
+                _ => format!("i{} * {}", i, stride),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" + ");
+
+    (0..dimensions.len())
+        .rev()
+        .fold(format!("values[{}]", index), |inner, i| {
+            format!("::std::array::from_fn(|i{}| {})", i, inner)
+        })
+}
+
+/// Returns the name of the method reading the field `name`, which can't be a keyword.
+fn method_name(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in",
+        "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+        "return", "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe",
+        "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+
+    match name {
+        // These can't be raw identifiers
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        _ if KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => String::from(name),
+    }
+}
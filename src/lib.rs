@@ -77,13 +77,39 @@
 //! helpful. You can use it to save an entire .blend file as text to disk. You can also print single struct instances
 //! if you know somewhat what you need.
 //! 
-//! It's also important to note that when printing an `Instance` if one of their properties is a list, elements other
-//! than the first are skipped. If you need to see the entire list simply access it and print its members individually.
+//! Blender renames, moves and removes struct fields between versions. The `dna_diff` example prints which structs and
+//! fields changed between the files saved by two versions: `cargo run --example dna_diff -- old.blend new.blend`.
 //! 
-//! The `Display` implementation for `Instance` is a bit unpolished so larger .blend
-//! files might cause a stack overflow but that can be fixed by running the code in release mode. If you find something that
+//! Data linked from other .blend files is saved as a placeholder with only the name of the datablock. Load the file
+//! with `BlendLibrarySet` instead of `Blend` to have `Instance::get` follow these links into the library files, which are
+//! opened as they are needed.
+//! 
+//! To read a few values without writing the loops yourself, `Blend::query` takes a path like
+//! `OB[id.name=OBCube].data.mvert[*].co` and returns every value it leads to.
+//! 
+//! With the `serde` feature an `Instance` can be serialized, to JSON for example. `Instance::serializable` controls
+//! which pointers are written as nested objects and which as references to the address of their block.
+//! 
+//! Printing an `Instance` writes every field and every element of its lists. Structs found through pointers are written
+//! the first time and as `@address` after that, and other datablocks are only referenced. Use `runtime::Dumper` to limit
+//! how many elements of each list are written or to follow the pointers to other datablocks. If you find something that
 //! breaks formatting please open an issue.
 //! 
+//! To see how the datablocks of a file point to each other, `runtime::DotExporter` writes them as a graph that
+//! Graphviz can draw, as in the `dot_graph` example.
+//! 
+//! `codegen::BindingGenerator` turns the DNA of a file into Rust code with a wrapper around `Instance` for each
+//! struct, so `object.loc()` can be written instead of `object.get_f32_vec("loc")` and misspelled fields don't compile.
+//! It can be called from a build script, or see the `bindings` example.
+//! 
+//! To read instances into your own structs, implement `FromInstance` or, with the `derive` feature, derive it with
+//! `#[derive(FromInstance)]`. Fields can be renamed with `#[blend(field = "loc")]` or read from inside other structs
+//! with `#[blend(path = "id.name")]`, and null pointers are read as `None` by `Option` fields.
+//! 
+//! For the most common datablocks the `types` module has ready-made wrappers: `types::Object`, `types::Mesh`,
+//! `types::Camera`, `types::Light` and `types::Material`. They read the fields that changed between Blender versions
+//! from wherever the file keeps them, so the same code works for every version in `examples/blend_files`.
+//! 
 //! ### Running examples
 //! 
 //! A .blend file may contain personal information from the machine it was created, that's why no .blend files are provided
@@ -95,7 +121,9 @@
 //! As the .blend file is self-describing it should possible to parse files from every Blender version (tests were done
 //! on files from version 2.72 to 2.80). Some things are assumed to always be true though: the type `int` for example is
 //! always considered equivalent to Rust's `i32` but there is nothing in the file specification that guarantees this. There
-//! is very little reason to believe Blender would change its primitive types though.
+//! is very little reason to believe Blender would change its primitive types though. Primitive types are recognized by
+//! their name (see `parsers::primitive::PRIMITIVE_TYPE_NAMES`) rather than by their position in the DNA, which changes
+//! when Blender adds new basic types.
 //! 
 //! ### Warnings
 //! 
@@ -110,21 +138,31 @@
 //! 
 //! This crate is also somewhat panic happy. While it should be always possible to check if the field you are accessing
 //! exists, is valid, contains a particular type of data, etc, you are meant to know what you are accessing ahead of
-//! time so almost none of the functions will return a `Result::Err` or `Option::None` on bad inputs.
+//! time so almost none of the functions will return a `Result::Err` or `Option::None` on bad inputs. If you can't trust
+//! the files you read, every accessor of `Instance` has a `try_` version (`try_get`, `try_get_f32`, `try_get_iter`, etc)
+//! which returns a `runtime::AccessError` instead of panicking.
 //! 
 //! Finally, this was developed to facilitate game development but should be useful for any use case.
 //! 
 //! ### Limitations
 //! 
-//! This crate does not support compressed .blend files and it also does not support writing .blend files. To solve the
-//! first you can uncompress the file before passing the data to `blend::Blend::from_data` see the `print_blend` example
-//! to see how. The second one is a bit harder due to the way the code is organized, but PRs are welcome!
+//! Compressed .blend files are only supported if the matching cargo feature is enabled: `gzip` for files saved by
+//! Blender 2.x and `zstd` for files saved by Blender 3.0 and later. Without them, loading a compressed file returns
+//! `BlendParseError::CompressedFileNotSupported`.
 //! 
-//! `GLOB`, `REND` and `TEST` blocks are not fully supported. Parts of the code already supports these blocks but they are
-//! not fully implemented as I haven't found a use-case for them. Open an issue if you would like support for these!
+//! Files can be written back to disk with `RawBlend::write_to`. Files are always written uncompressed. Files saved on big-endian
+//! or 32-bit machines can be converted to little-endian with 64-bit pointers with `RawBlend::to_little_endian_64`.
+//! 
+//! The `GLOB` block can be accessed with `Blend::file_global`, `REND` blocks with `Blend::render_info` and the thumbnail in
+//! the `TEST` block with `Blend::thumbnail`. Enable the `png` feature to encode the thumbnail as a PNG image.
 
 
+pub mod codegen;
 pub mod parsers;
 pub mod runtime;
+pub mod types;
+
+pub use runtime::{Blend, BlendLibrarySet, FromInstance, Instance};
 
-pub use runtime::{Blend, Instance};
+#[cfg(feature = "derive")]
+pub use blend_derive::FromInstance;
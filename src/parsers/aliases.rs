@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+/// Fields Blender renamed in its code but still saves with their old name, from Blender's `DNA_rename_defs.h`. Each
+/// entry is the struct, the name saved in files and the name used by Blender's code (and its Python API).
+const BLENDER_RENAMES: &[(&str, &str, &str)] = &[
+    ("BPoint", "alfa", "tilt"),
+    ("BezTriple", "alfa", "tilt"),
+    ("Bone", "curveInX", "curve_in_x"),
+    ("Bone", "curveOutX", "curve_out_x"),
+    ("Bone", "scaleIn", "scale_in_x"),
+    ("Bone", "scaleOut", "scale_out_x"),
+    ("bPoseChannel", "curveInX", "curve_in_x"),
+    ("bPoseChannel", "curveOutX", "curve_out_x"),
+    ("bPoseChannel", "scaleIn", "scale_in_x"),
+    ("bPoseChannel", "scaleOut", "scale_out_x"),
+    ("Camera", "YF_dofdist", "dof_distance"),
+    ("Camera", "clipend", "clip_end"),
+    ("Camera", "clipsta", "clip_start"),
+    ("Collection", "dupli_ofs", "instance_offset"),
+    ("Curve", "ext1", "extrude"),
+    ("Curve", "ext2", "bevel_radius"),
+    ("Curve", "width", "offset"),
+    ("Curve", "loc", "texspace_location"),
+    ("Curve", "size", "texspace_size"),
+    ("Curve", "texflag", "texspace_flag"),
+    ("Mesh", "loc", "texspace_location"),
+    ("Mesh", "size", "texspace_size"),
+    ("Mesh", "texflag", "texspace_flag"),
+    ("MetaBall", "loc", "texspace_location"),
+    ("MetaBall", "size", "texspace_size"),
+    ("MetaBall", "texflag", "texspace_flag"),
+    ("Object", "col", "color"),
+    ("Object", "dup_group", "instance_collection"),
+    ("Object", "dupfacesca", "instance_faces_scale"),
+    ("Object", "restrictflag", "visibility_flag"),
+    ("Object", "size", "scale"),
+    ("ParticleSettings", "dup_group", "instance_collection"),
+    ("ParticleSettings", "dup_ob", "instance_object"),
+    ("ParticleSettings", "dupliweights", "instance_weights"),
+    ("View3D", "far", "clip_end"),
+    ("View3D", "near", "clip_start"),
+    // Structs with a file path used to call it `name`, like the name of an `ID`
+    ("Image", "name", "filepath"),
+    ("Library", "name", "filepath"),
+    ("MovieClip", "name", "filepath"),
+    ("Text", "name", "filepath"),
+    ("VFont", "name", "filepath"),
+    ("bSound", "name", "filepath"),
+];
+
+/// Alternative names for struct fields. When a field is not found by its name, `Instance` tries its aliases, so code
+/// can use the same name for a field across Blender versions that saved it under different names.
+///
+/// The default table holds the renames Blender itself knows about, like `Object.size` being `scale` in Blender's code.
+/// Aliases always work both ways: `scale` finds `size` and `size` finds `scale`.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::Blend;
+/// # fn main() {
+///     # let mut blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+/// blend.blend.aliases.insert("Camera", "lens", "focal_length");
+///
+/// let obj = blend.instances_with_code(*b"OB").next().unwrap();
+/// assert_eq!(obj.get_f32_vec("scale"), obj.get_f32_vec("size"));
+///
+/// let camera = blend.instances_with_code(*b"CA").next().unwrap();
+/// assert_eq!(camera.get_f32("focal_length"), camera.get_f32("lens"));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FieldAliases {
+    /// Maps a struct name to the aliases of each of its fields.
+    aliases: HashMap<String, HashMap<String, Vec<String>>>,
+}
+
+impl FieldAliases {
+    /// Creates a table without any alias.
+    pub fn empty() -> Self {
+        FieldAliases {
+            aliases: HashMap::new(),
+        }
+    }
+
+    /// Makes `alias` another name for the field `name` of the struct `struct_name`, and the other way around.
+    pub fn insert(&mut self, struct_name: &str, name: &str, alias: &str) {
+        let fields = self.aliases.entry(String::from(struct_name)).or_default();
+
+        for (from, to) in [(name, alias), (alias, name)] {
+            let aliases = fields.entry(String::from(from)).or_default();
+            if !aliases.iter().any(|a| a == to) {
+                aliases.push(String::from(to));
+            }
+        }
+    }
+
+    /// Returns the other names of the field `name` of the struct `struct_name`.
+    pub fn get(&self, struct_name: &str, name: &str) -> &[String] {
+        self.aliases
+            .get(struct_name)
+            .and_then(|fields| fields.get(name))
+            .map_or(&[], |aliases| &aliases[..])
+    }
+}
+
+impl Default for FieldAliases {
+    /// Creates a table with the fields renamed by Blender.
+    fn default() -> Self {
+        let mut aliases = FieldAliases::empty();
+
+        for (struct_name, name, alias) in BLENDER_RENAMES {
+            aliases.insert(struct_name, name, alias);
+        }
+
+        aliases
+    }
+}
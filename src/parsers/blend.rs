@@ -1,27 +1,39 @@
 use crate::parsers::{
+    aliases::FieldAliases,
+    buffer::{BlendBuffer, BlockBytes},
+    compression::decompress,
     dna::{Dna, DnaParseContext},
+    layout::{LayoutCache, LayoutError, StructLayout},
+    primitive::{write_i64, write_u32, write_u64},
     BlendParseError, Endianness, PointerSize, Result,
 };
 use nom::{
     branch::alt,
     bytes::complete::{tag, take},
+    error::{ErrorKind, ParseError},
     multi::many_till,
-    number::complete::{be_u32, be_u64, le_u32, le_u64},
+    number::complete::{be_i64, be_u32, be_u64, le_i64, le_u32, le_u64},
     sequence::tuple,
     Err,
 };
 use std::{
-    convert::TryInto,
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
     fmt::{self, Debug, Formatter},
-    io::Read,
+    io::{self, Read, Write},
     num::NonZeroU64,
+    ops::{Deref, DerefMut},
     path::Path,
     result::Result as StdResult,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 pub struct BlockData {
-    /// The entire binary data of the `Block` in the blend file.
-    pub data: Vec<u8>,
+    /// The entire binary data of the `Block` in the blend file. Usually a slice of the buffer the file was read into.
+    pub data: BlockBytes,
     /// The data field can contain more than one struct, count tells us how many there is.
     pub count: usize,
 }
@@ -33,11 +45,23 @@ impl Debug for BlockData {
 }
 
 /// Represents all possible block types found in the blend file.
-/// `Rend`, `Test` and `Global` are ignored by this crate but are still represented here.
+/// `Global` holds the `FileGlobal` struct. `Rend` and `Test` are not described by the DNA and are read by hand.
 #[derive(Debug)]
 pub enum Block {
-    Rend,
-    Test,
+    /// Render settings of a scene: the start frame, the end frame and the scene name. Blender writes one of these for
+    /// every scene so the frame range can be read without parsing the whole file. They are not described by the DNA.
+    Rend {
+        memory_address: NonZeroU64,
+        dna_index: usize,
+        data: BlockData,
+    },
+    /// The thumbnail shown by Blender's file browser: the width and height as two ints followed by the RGBA pixels,
+    /// starting with the bottom row.
+    Test {
+        memory_address: NonZeroU64,
+        dna_index: usize,
+        data: BlockData,
+    },
     Global {
         memory_address: NonZeroU64,
         dna_index: usize,
@@ -62,59 +86,137 @@ pub enum Block {
     Dna(Dna),
 }
 
+impl Block {
+    /// Returns the data of the block. Only the `DNA1` block has none, its data is parsed into a `Dna`.
+    pub fn data(&self) -> Option<&BlockData> {
+        match self {
+            Block::Rend { data, .. }
+            | Block::Test { data, .. }
+            | Block::Global { data, .. }
+            | Block::Principal { data, .. }
+            | Block::Subsidiary { data, .. } => Some(data),
+            Block::Dna(_) => None,
+        }
+    }
+
+    /// Same as `data`, but mutable.
+    pub fn data_mut(&mut self) -> Option<&mut BlockData> {
+        match self {
+            Block::Rend { data, .. }
+            | Block::Test { data, .. }
+            | Block::Global { data, .. }
+            | Block::Principal { data, .. }
+            | Block::Subsidiary { data, .. } => Some(data),
+            Block::Dna(_) => None,
+        }
+    }
+
+    /// Returns the memory address saved in the header of the block. `None` for the `DNA1` block, whose address is
+    /// not kept.
+    pub fn memory_address(&self) -> Option<NonZeroU64> {
+        match self {
+            Block::Rend { memory_address, .. }
+            | Block::Test { memory_address, .. }
+            | Block::Global { memory_address, .. }
+            | Block::Principal { memory_address, .. }
+            | Block::Subsidiary { memory_address, .. } => Some(*memory_address),
+            Block::Dna(_) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Header {
     /// The size of the pointer on the machine used to save the blend file.
     pub pointer_size: PointerSize,
     /// The endianness on the machine used to save the blend file.
     pub endianness: Endianness,
-    /// The version of Blender used to save the blend file.
+    /// The version of Blender used to save the blend file, as ASCII digits (`b"305"` for Blender 3.5).
     pub version: [u8; 3],
+    /// The version of the file format. `0` is the legacy format with a 12 byte file header and 32-bit block lengths.
+    /// `1` is the format introduced in Blender 5.0, with a 17 byte file header and 64-bit block lengths.
+    pub format_version: u8,
+}
+
+impl Header {
+    /// Returns true if blocks in this file use the large block header, with 64-bit lengths and counts.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::{Blend, parsers::blend::RawBlend};
+    /// # fn main() {
+    /// let mut raw = RawBlend::from_path("examples/blend_files/3_5.blend").unwrap();
+    /// assert!(!raw.header.has_large_block_headers());
+    ///
+    /// // Writes the file with the header and block headers of Blender 5.0.
+    /// raw.header.format_version = 1;
+    /// let mut data = Vec::new();
+    /// raw.write_to(&mut data).unwrap();
+    /// assert!(data.starts_with(b"BLENDER17-01v0305"));
+    ///
+    /// let blend = Blend::new(&data[..]).unwrap();
+    /// assert!(blend.blend.header.has_large_block_headers());
+    /// # }
+    /// ```
+    pub fn has_large_block_headers(&self) -> bool {
+        self.format_version >= 1
+    }
 }
 
-fn pointer_size_bits32(input: &[u8]) -> Result<PointerSize> {
+fn pointer_size_bits32(input: &[u8]) -> Result<'_, PointerSize> {
     let (input, _) = tag("_")(input)?;
     Ok((input, PointerSize::Bits32))
 }
 
-fn pointer_size_bits64(input: &[u8]) -> Result<PointerSize> {
+fn pointer_size_bits64(input: &[u8]) -> Result<'_, PointerSize> {
     let (input, _) = tag("-")(input)?;
     Ok((input, PointerSize::Bits64))
 }
 
-pub fn pointer_size(input: &[u8]) -> Result<PointerSize> {
+pub fn pointer_size(input: &[u8]) -> Result<'_, PointerSize> {
     alt((pointer_size_bits32, pointer_size_bits64))(input)
 }
 
-fn endianness_litte(input: &[u8]) -> Result<Endianness> {
+fn endianness_litte(input: &[u8]) -> Result<'_, Endianness> {
     let (input, _) = tag("v")(input)?;
     Ok((input, Endianness::Little))
 }
 
-fn endianness_big(input: &[u8]) -> Result<Endianness> {
+fn endianness_big(input: &[u8]) -> Result<'_, Endianness> {
     let (input, _) = tag("V")(input)?;
     Ok((input, Endianness::Big))
 }
 
-pub fn endianness(input: &[u8]) -> Result<Endianness> {
+pub fn endianness(input: &[u8]) -> Result<'_, Endianness> {
     alt((endianness_litte, endianness_big))(input)
 }
 
-pub fn version(input: &[u8]) -> Result<[u8; 3]> {
+pub fn version(input: &[u8]) -> Result<'_, [u8; 3]> {
     let (input, v) = take(3_usize)(input)?;
     Ok((input, [v[0], v[1], v[2]]))
 }
 
-pub fn header(input: &[u8]) -> Result<Header> {
-    let (input, _) = match tag::<_, _, BlendParseError>("BLENDER")(input) {
-        Ok(v) => v,
-        Err(_) => {
-            return Err(nom::Err::Failure(
-                BlendParseError::CompressedFileNotSupported,
-            ))
-        }
-    };
+/// Parses `len` ASCII digits as a decimal number.
+fn decimal(input: &[u8], len: usize) -> Result<'_, usize> {
+    let (input, digits) = take(len)(input)?;
 
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return Err(Err::Error(BlendParseError::from_error_kind(
+            input,
+            ErrorKind::Digit,
+        )));
+    }
+
+    let value = digits
+        .iter()
+        .fold(0, |value, digit| value * 10 + usize::from(digit - b'0'));
+
+    Ok((input, value))
+}
+
+/// The legacy header, like `b"BLENDER-v305"`. Used by every Blender version before 5.0.
+fn legacy_header(input: &[u8]) -> Result<'_, Header> {
     let (input, (pointer_size, endianness, version)) =
         tuple((pointer_size, endianness, version))(input)?;
 
@@ -124,11 +226,54 @@ pub fn header(input: &[u8]) -> Result<Header> {
             pointer_size,
             endianness,
             version,
+            format_version: 0,
         },
     ))
 }
 
-pub fn block_header_code(input: &[u8]) -> Result<[u8; 4]> {
+/// The header introduced in Blender 5.0, like `b"BLENDER17-01v0500"`. The first two digits are the size of the whole
+/// header, the next two are the file format version and the last four are the Blender version. Files using this
+/// header are always saved with 64-bit pointers.
+fn large_header(input: &[u8]) -> Result<'_, Header> {
+    const LARGE_HEADER_LEN: usize = 17;
+
+    let (input, header_len) = decimal(input, 2)?;
+    let (input, _) = tag("-")(input)?;
+    let (input, format_version) = decimal(input, 2)?;
+    let (input, endianness) = endianness(input)?;
+    let (input, v) = take(4_usize)(input)?;
+
+    // Longer headers may be used by future versions. Their extra bytes are unknown and couldn't be written back by
+    // `RawBlend::write_to`, so they are rejected instead of skipped
+    if format_version != 1 || header_len != LARGE_HEADER_LEN || v[0] != b'0' {
+        return Err(Err::Failure(BlendParseError::UnsupportedFormatVersion));
+    }
+
+    Ok((
+        input,
+        Header {
+            pointer_size: PointerSize::Bits64,
+            endianness,
+            version: [v[1], v[2], v[3]],
+            format_version: 1,
+        },
+    ))
+}
+
+pub fn header(input: &[u8]) -> Result<'_, Header> {
+    let (input, _) = match tag::<_, _, BlendParseError>("BLENDER")(input) {
+        Ok(v) => v,
+        Err(_) => {
+            return Err(nom::Err::Failure(
+                BlendParseError::CompressedFileNotSupported,
+            ))
+        }
+    };
+
+    alt((large_header, legacy_header))(input)
+}
+
+pub fn block_header_code(input: &[u8]) -> Result<'_, [u8; 4]> {
     let (input, v) = take(4_usize)(input)?;
     Ok((input, [v[0], v[1], v[2], v[3]]))
 }
@@ -136,19 +281,358 @@ pub fn block_header_code(input: &[u8]) -> Result<[u8; 4]> {
 #[derive(Debug)]
 pub struct RawBlend {
     pub header: Header,
-    pub blocks: Vec<Block>,
+    /// The blocks of the file, without the `DNA1` and `ENDB` blocks. See `RawBlend::blocks` and `RawBlend::blocks_mut`.
+    pub(crate) blocks: Vec<Block>,
     pub dna: Dna,
+    /// Other names `Instance` accepts for struct fields. Starts with the fields renamed by Blender.
+    pub aliases: FieldAliases,
+    /// Maps the memory address of every principal and subsidiary block to its index in `blocks`.
+    block_index: HashMap<NonZeroU64, usize>,
+    /// The layouts of the structs in `dna`, computed as they are needed.
+    layouts: LayoutCache,
+    /// The memory address saved in the header of the `DNA1` block. Only kept so files can be written back unchanged.
+    pub(crate) dna_memory_address: Option<NonZeroU64>,
+    /// Tells this `RawBlend` apart from every other one created by the process, so an `InstanceLocation` can't be
+    /// used to edit another file.
+    pub(crate) id: u64,
 }
 
+/// The `id` of the next `RawBlend`.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
 impl RawBlend {
-    /// Returns a new `Blend` instance from `data`.
+    /// Creates a `RawBlend` from its parts and indexes its blocks by memory address.
+    pub fn new(header: Header, blocks: Vec<Block>, dna: Dna) -> Self {
+        let layouts = LayoutCache::new(&dna);
+        let mut blend = RawBlend {
+            header,
+            blocks,
+            dna,
+            aliases: FieldAliases::default(),
+            block_index: HashMap::new(),
+            layouts,
+            dna_memory_address: None,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        };
+        blend.rebuild_index();
+        blend
+    }
+
+    /// The blocks of the file, without the `DNA1` and `ENDB` blocks.
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Gives mutable access to the blocks of the file. Pointers are resolved through an index of the blocks by memory
+    /// address, which is rebuilt when the returned `BlocksMut` is dropped, so adding, removing or reordering blocks or
+    /// changing their addresses never leaves `block_at` pointing to the wrong blocks.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::parsers::{blend::{Block, BlockData, RawBlend}, buffer::BlockBytes};
+    /// # fn main() {
+    /// let mut raw = RawBlend::from_path("examples/blend_files/2_80.blend").unwrap();
+    /// let address = raw
+    ///     .blocks()
+    ///     .iter()
+    ///     .find_map(|block| match block {
+    ///         Block::Principal { memory_address, .. } => Some(*memory_address),
+    ///         _ => None,
+    ///     })
+    ///     .unwrap();
+    ///
+    /// // When blocks share a memory address the first one is found
+    /// raw.blocks_mut().push(Block::Subsidiary {
+    ///     memory_address: address,
+    ///     dna_index: 0,
+    ///     data: BlockData { data: BlockBytes::Owned(Vec::new()), count: 0 },
+    /// });
+    /// assert!(matches!(raw.block_at(address), Some(Block::Principal { .. })));
+    ///
+    /// raw.blocks_mut().retain(|block| !matches!(block, Block::Principal { .. }));
+    /// assert!(matches!(raw.block_at(address), Some(Block::Subsidiary { .. })));
+    /// # }
+    /// ```
+    pub fn blocks_mut(&mut self) -> BlocksMut<'_> {
+        BlocksMut { blend: self }
+    }
+
+    /// Recomputes the address-to-block index used by `block_at`. When blocks share a memory address the first one is
+    /// indexed.
+    fn rebuild_index(&mut self) {
+        let mut block_index = HashMap::with_capacity(self.blocks.len());
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            if let Block::Principal { memory_address, .. }
+            | Block::Subsidiary { memory_address, .. } = block
+            {
+                block_index.entry(*memory_address).or_insert(i);
+            }
+        }
+
+        self.block_index = block_index;
+    }
+
+    /// Writes the blend file to `writer`: the header, every block in `blocks`, the `DNA1` block encoded from `dna` and
+    /// the final `ENDB` block. The pointer size, endianness and block header layout of `header` are used, so an
+    /// unmodified file is written back byte for byte.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::parsers::blend::RawBlend;
+    /// # fn main() {
+    /// for file_name in &["2_80", "2_90", "3_0", "3_5"] {
+    ///     let path = format!("examples/blend_files/{}.blend", file_name);
+    ///     let original = std::fs::read(&path).unwrap();
+    ///
+    ///     let mut written = Vec::new();
+    ///     RawBlend::from_path(&path).unwrap().write_to(&mut written).unwrap();
+    ///
+    ///     assert!(original == written, "{} was not written back unchanged", path);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// Values that don't fit in the block headers of the file, like 64-bit addresses in a file with 32-bit pointers,
+    /// return an `InvalidData` error:
+    ///
+    /// ```rust
+    /// # use blend::parsers::{blend::RawBlend, PointerSize};
+    /// # use std::io::ErrorKind;
+    /// # fn main() {
+    /// let mut raw = RawBlend::from_path("examples/blend_files/2_80.blend").unwrap();
+    /// raw.header.pointer_size = PointerSize::Bits32;
+    ///
+    /// let err = raw.write_to(&mut Vec::new()).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::InvalidData);
+    /// # }
+    /// ```
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.write_header(&mut writer)?;
+
+        for block in &self.blocks {
+            let (code, memory_address, dna_index, data) = match block {
+                Block::Rend {
+                    memory_address,
+                    dna_index,
+                    data,
+                } => (*b"REND", *memory_address, *dna_index, data),
+                Block::Test {
+                    memory_address,
+                    dna_index,
+                    data,
+                } => (*b"TEST", *memory_address, *dna_index, data),
+                Block::Global {
+                    memory_address,
+                    dna_index,
+                    data,
+                } => (*b"GLOB", *memory_address, *dna_index, data),
+                Block::Principal {
+                    code,
+                    memory_address,
+                    dna_index,
+                    data,
+                } => ([code[0], code[1], 0, 0], *memory_address, *dna_index, data),
+                Block::Subsidiary {
+                    memory_address,
+                    dna_index,
+                    data,
+                } => (*b"DATA", *memory_address, *dna_index, data),
+                // Assumption: The DNA block is always the last one, so it is written after the loop
+                Block::Dna(_) => continue,
+            };
+
+            self.write_block_header(
+                &mut writer,
+                code,
+                data.data.len(),
+                memory_address.get(),
+                dna_index,
+                data.count,
+            )?;
+            writer.write_all(&data.data)?;
+        }
+
+        let dna = self.dna.to_bytes(self.header.endianness)?;
+        // Any non-null address works for a DNA block that wasn't read from a file
+        let dna_memory_address = self.dna_memory_address.map_or(1, NonZeroU64::get);
+        self.write_block_header(&mut writer, *b"DNA1", dna.len(), dna_memory_address, 0, 1)?;
+        writer.write_all(&dna)?;
+
+        self.write_block_header(&mut writer, *b"ENDB", 0, 0, 0, 0)
+    }
+
+    fn write_header<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let endianness = match self.header.endianness {
+            Endianness::Little => b'v',
+            Endianness::Big => b'V',
+        };
+
+        writer.write_all(b"BLENDER")?;
+        if self.header.has_large_block_headers() {
+            write!(writer, "17-{:02}", self.header.format_version)?;
+            writer.write_all(&[endianness, b'0'])?;
+        } else {
+            let pointer_size = match self.header.pointer_size {
+                PointerSize::Bits32 => b'_',
+                PointerSize::Bits64 => b'-',
+            };
+            writer.write_all(&[pointer_size, endianness])?;
+        }
+        writer.write_all(&self.header.version)
+    }
+
+    /// Writes a block header in the layout used by this file, the inverse of `BlendParseContext::block_header` and
+    /// `BlendParseContext::large_block_header`.
+    ///
+    /// Returns an `InvalidData` error if the values don't fit the sizes used by the block header.
+    fn write_block_header<W: Write>(
+        &self,
+        writer: &mut W,
+        code: [u8; 4],
+        size: usize,
+        memory_address: u64,
+        dna_index: usize,
+        count: usize,
+    ) -> io::Result<()> {
+        let endianness = self.header.endianness;
+        let dna_index = write_u32(header_value(code, "struct index", dna_index)?, endianness);
+
+        writer.write_all(&code)?;
+        if self.header.has_large_block_headers() {
+            writer.write_all(&dna_index)?;
+            writer.write_all(&write_u64(memory_address, endianness))?;
+            writer.write_all(&write_i64(header_value(code, "size", size)?, endianness))?;
+            writer.write_all(&write_i64(header_value(code, "count", count)?, endianness))
+        } else {
+            writer.write_all(&write_u32(header_value(code, "size", size)?, endianness))?;
+            match self.header.pointer_size {
+                PointerSize::Bits32 => writer.write_all(&write_u32(
+                    header_value(code, "memory address", memory_address)?,
+                    endianness,
+                ))?,
+                PointerSize::Bits64 => writer.write_all(&write_u64(memory_address, endianness))?,
+            }
+            writer.write_all(&dna_index)?;
+            writer.write_all(&write_u32(header_value(code, "count", count)?, endianness))
+        }
+    }
+
+    /// Returns the layout of the struct at `struct_index` in `dna.structs`. It is computed on the first call and shared
+    /// by every later call.
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if there is no struct at `struct_index` or if the `Dna` is corrupt. See `try_struct_layout`.
+    pub fn struct_layout(&self, struct_index: usize) -> Arc<StructLayout> {
+        self.try_struct_layout(struct_index)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Same as `struct_layout`, but returns an error instead of panicking. Struct indices read from the headers of
+    /// blocks can be anything in a corrupt file, they should be passed to this method.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::parsers::{blend::RawBlend, layout::LayoutError};
+    /// # fn main() {
+    /// let raw = RawBlend::from_path("examples/blend_files/2_80.blend").unwrap();
+    /// assert!(raw.try_struct_layout(0).is_ok());
+    /// assert_eq!(
+    ///     raw.try_struct_layout(60000).unwrap_err(),
+    ///     LayoutError::NoSuchStruct { struct_index: 60000 }
+    /// );
+    /// # }
+    /// ```
+    pub fn try_struct_layout(
+        &self,
+        struct_index: usize,
+    ) -> StdResult<Arc<StructLayout>, LayoutError> {
+        self.layouts.get(&self.dna, &self.header, struct_index)
+    }
+
+    /// Returns the index in `dna.structs` of the struct of the type at `type_index` in `dna.types`, or `None` if the
+    /// type is not a struct. Unlike searching `dna.structs`, this is a lookup in a map built when the file is loaded.
+    pub fn struct_index(&self, type_index: usize) -> Option<usize> {
+        self.layouts.struct_index(type_index)
+    }
+
+    /// Returns the principal or subsidiary block saved at `address`, if there is one. Pointers in the blend file are
+    /// resolved with this method, it doesn't search the blocks.
+    pub fn block_at(&self, address: NonZeroU64) -> Option<&Block> {
+        self.block_index
+            .get(&address)
+            .and_then(|&index| self.blocks.get(index))
+    }
+
+    /// Returns the index in `blocks` of `block`, which must be a reference into `blocks`.
+    pub(crate) fn block_position(&self, block: &Block) -> Option<usize> {
+        let indexed = match block {
+            Block::Principal { memory_address, .. } | Block::Subsidiary { memory_address, .. } => {
+                self.block_index.get(memory_address).copied()
+            }
+            _ => None,
+        };
+
+        // Only the first of the blocks sharing an address is in the index, so fall back to a search by reference
+        indexed
+            .filter(|&index| std::ptr::eq(&self.blocks[index], block))
+            .or_else(|| self.blocks.iter().position(|b| std::ptr::eq(b, block)))
+    }
+
+    /// Returns a new `Blend` instance from `data`. Compressed files are decompressed first if the cargo feature for
+    /// their format (`gzip` or `zstd`) is enabled.
     pub fn from_data<T: Read>(mut data: T) -> StdResult<Self, BlendParseError> {
         let mut buffer = Vec::new();
         data.read_to_end(&mut buffer)
             .map_err(BlendParseError::IoError)?;
 
+        RawBlend::from_buffer(buffer)
+    }
+
+    /// Returns a new `Blend` instance from the bytes of a blend file. The blocks reference their data inside `buffer`
+    /// instead of copying it.
+    pub fn from_buffer(buffer: Vec<u8>) -> StdResult<Self, BlendParseError> {
+        let buffer = decompress(buffer)?;
+        RawBlend::from_shared_buffer(Arc::new(BlendBuffer::Owned(buffer)))
+    }
+
+    /// Returns a new `Blend` instance from a path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> StdResult<Self, BlendParseError> {
+        use std::fs::File;
+
+        let file = File::open(path).map_err(BlendParseError::IoError)?;
+        RawBlend::from_data(file)
+    }
+
+    /// Returns a new `Blend` instance from a memory mapped file. Blocks read their data straight from the mapping, so
+    /// only the parts of the file that are accessed are loaded into memory. Compressed files can't be mapped and are
+    /// decompressed into memory instead.
+    ///
+    /// The file must not be modified while the returned value is alive, see `memmap2::Mmap` for details.
+    #[cfg(feature = "memmap")]
+    pub fn from_mmap<P: AsRef<Path>>(path: P) -> StdResult<Self, BlendParseError> {
+        use crate::parsers::compression::Compression;
+        use std::fs::File;
+
+        let file = File::open(path).map_err(BlendParseError::IoError)?;
+        // Safety: the mapping is only ever read, modifying the file while it is mapped is documented above as a
+        // requirement on the caller.
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(BlendParseError::IoError)?;
+
+        if Compression::detect(&map) == Compression::None {
+            RawBlend::from_shared_buffer(Arc::new(BlendBuffer::Mapped(map)))
+        } else {
+            RawBlend::from_buffer(map.to_vec())
+        }
+    }
+
+    fn from_shared_buffer(buffer: Arc<BlendBuffer>) -> StdResult<Self, BlendParseError> {
         let mut parser = BlendParseContext::default();
-        let res = parser.blend(&buffer);
+        let res = parser.blend_shared(&buffer);
 
         match res {
             Ok((_, blend)) => Ok(blend),
@@ -156,16 +640,78 @@ impl RawBlend {
             Err(Err::Incomplete(..)) => Err(BlendParseError::NotEnoughData),
         }
     }
+}
 
-    /// Returns a new `Blend` instance from a path.
-    pub fn from_path<P: AsRef<Path>>(path: P) -> StdResult<Self, BlendParseError> {
-        use std::fs::File;
+/// Returns the `BlockBytes` for `block_data`. If the data is a slice of `buffer` the block shares it, otherwise the
+/// data is copied.
+fn block_bytes(block_data: &[u8], buffer: Option<&Arc<BlendBuffer>>) -> BlockBytes {
+    match buffer {
+        Some(buffer) => {
+            let start = block_data.as_ptr() as usize - buffer.as_ptr() as usize;
+            BlockBytes::Shared {
+                buffer: Arc::clone(buffer),
+                range: start..start + block_data.len(),
+            }
+        }
+        None => BlockBytes::Owned(block_data.to_vec()),
+    }
+}
 
-        let file = File::open(path).map_err(BlendParseError::IoError)?;
-        RawBlend::from_data(file)
+/// Mutable access to the blocks of a `RawBlend`, returned by `RawBlend::blocks_mut`. Derefs to the `Vec` of blocks and
+/// rebuilds the address index of the `RawBlend` when dropped.
+pub struct BlocksMut<'a> {
+    blend: &'a mut RawBlend,
+}
+
+impl Deref for BlocksMut<'_> {
+    type Target = Vec<Block>;
+
+    fn deref(&self) -> &Vec<Block> {
+        &self.blend.blocks
     }
 }
 
+impl DerefMut for BlocksMut<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<Block> {
+        &mut self.blend.blocks
+    }
+}
+
+impl Drop for BlocksMut<'_> {
+    fn drop(&mut self) {
+        self.blend.rebuild_index();
+    }
+}
+
+/// Converts `value` to the integer type of a block header field, or returns an `InvalidData` error naming the block
+/// code and the field if it doesn't fit.
+fn header_value<T, U>(code: [u8; 4], field: &str, value: T) -> io::Result<U>
+where
+    T: Copy + fmt::Display,
+    U: TryFrom<T>,
+{
+    U::try_from(value).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the {} of a {} block ({}) doesn't fit in its block header",
+                field,
+                String::from_utf8_lossy(&code).trim_end_matches('\0'),
+                value
+            ),
+        )
+    })
+}
+
+/// The fields shared by both block header layouts.
+struct BlockHeader {
+    code: [u8; 4],
+    size: usize,
+    memory_address: NonZeroU64,
+    dna_index: usize,
+    count: usize,
+}
+
 #[derive(Default)]
 pub enum BlendParseContext {
     #[default]
@@ -205,21 +751,20 @@ impl BlendParseContext {
         }
     }
 
+    /// Parses the block header used by the legacy file format, where the length, the dna index and the count are
+    /// 32-bit values and the memory address depends on the pointer size.
+    ///
     /// Panics if a u32 can't be converted to usize in your system.
-    fn block<'a, 'b>(&'a self, input: &'b [u8]) -> Result<'b, Block>
+    fn block_header<'a, 'b>(&'a self, input: &'b [u8]) -> Result<'b, BlockHeader>
     where
         'b: 'a,
     {
         match self {
             BlendParseContext::ParsedHeader(header) => {
                 let (input, code) = block_header_code(input)?;
-                let (input, size): (_, usize) = match header.endianness {
-                    Endianness::Little => {
-                        le_u32(input).map(|(i, n)| (i, n.try_into().expect("u32 to usize")))?
-                    }
-                    Endianness::Big => {
-                        be_u32(input).map(|(i, n)| (i, n.try_into().expect("u32 to usize")))?
-                    }
+                let (input, size) = match header.endianness {
+                    Endianness::Little => le_u32(input)?,
+                    Endianness::Big => be_u32(input)?,
                 };
                 let (input, memory_address) = self.memory_address(input)?;
                 let (input, dna_index) = match header.endianness {
@@ -231,26 +776,135 @@ impl BlendParseContext {
                     Endianness::Big => be_u32(input)?,
                 };
 
+                Ok((
+                    input,
+                    BlockHeader {
+                        code,
+                        size: size.try_into().expect("u32 to usize"),
+                        memory_address,
+                        dna_index: dna_index.try_into().expect("u32 to usize"),
+                        count: count.try_into().expect("u32 to usize"),
+                    },
+                ))
+            }
+            BlendParseContext::Empty => unreachable!("Header should be parsed here"),
+        }
+    }
+
+    /// Parses the large block header used since Blender 5.0. The dna index comes right after the code and the length
+    /// and count are 64-bit values.
+    ///
+    /// Fails with `ErrorKind::Verify` if the length or the count is negative or doesn't fit in a usize.
+    fn large_block_header<'a, 'b>(&'a self, input: &'b [u8]) -> Result<'b, BlockHeader>
+    where
+        'b: 'a,
+    {
+        match self {
+            BlendParseContext::ParsedHeader(header) => {
+                let (input, code) = block_header_code(input)?;
+                let (input, dna_index) = match header.endianness {
+                    Endianness::Little => le_u32(input)?,
+                    Endianness::Big => be_u32(input)?,
+                };
+                let (input, memory_address) = self.memory_address(input)?;
+                let (input, size) = match header.endianness {
+                    Endianness::Little => le_i64(input)?,
+                    Endianness::Big => be_i64(input)?,
+                };
+                let (input, count) = match header.endianness {
+                    Endianness::Little => le_i64(input)?,
+                    Endianness::Big => be_i64(input)?,
+                };
+
+                let to_usize = |value: i64| {
+                    usize::try_from(value).map_err(|_| {
+                        Err::Failure(BlendParseError::from_error_kind(input, ErrorKind::Verify))
+                    })
+                };
+
+                Ok((
+                    input,
+                    BlockHeader {
+                        code,
+                        size: to_usize(size)?,
+                        memory_address,
+                        dna_index: dna_index.try_into().expect("u32 to usize"),
+                        count: to_usize(count)?,
+                    },
+                ))
+            }
+            BlendParseContext::Empty => unreachable!("Header should be parsed here"),
+        }
+    }
+
+    /// Parses a block header with the layout used by the file.
+    fn any_block_header<'a, 'b>(&'a self, input: &'b [u8]) -> Result<'b, BlockHeader>
+    where
+        'b: 'a,
+    {
+        match self {
+            BlendParseContext::ParsedHeader(header) if header.has_large_block_headers() => {
+                self.large_block_header(input)
+            }
+            _ => self.block_header(input),
+        }
+    }
+
+    fn block<'a, 'b>(
+        &'a self,
+        input: &'b [u8],
+        buffer: Option<&Arc<BlendBuffer>>,
+    ) -> Result<'b, Block>
+    where
+        'b: 'a,
+    {
+        match self {
+            BlendParseContext::ParsedHeader(header) => {
+                let (
+                    input,
+                    BlockHeader {
+                        code,
+                        size,
+                        memory_address,
+                        dna_index,
+                        count,
+                    },
+                ) = self.any_block_header(input)?;
+
                 let (input, block_data) = take(size)(input)?;
 
                 //Assumption: These block codes will always exist
                 let block = match &code {
-                    b"REND" => Block::Rend,
-                    b"TEST" => Block::Test,
+                    b"REND" => Block::Rend {
+                        memory_address,
+                        dna_index,
+                        data: BlockData {
+                            data: block_bytes(block_data, buffer),
+                            count,
+                        },
+                    },
+                    b"TEST" => Block::Test {
+                        memory_address,
+                        dna_index,
+                        data: BlockData {
+                            data: block_bytes(block_data, buffer),
+                            count,
+                        },
+                    },
                     b"GLOB" => Block::Global {
                         memory_address,
-                        dna_index: dna_index.try_into().expect("u32 to usize"),
+                        dna_index,
                         data: BlockData {
-                            data: block_data.to_vec(),
-                            count: count.try_into().expect("u32 to usize"),
+                            data: block_bytes(block_data, buffer),
+                            count,
                         },
                     },
                     b"DATA" => Block::Subsidiary {
                         memory_address,
-                        dna_index: dna_index.try_into().expect("u32 to usize"),
+                        dna_index,
                         data: BlockData {
-                            data: block_data.to_vec(),
-                            count: count.try_into().expect("u32 to usize"),
+                            data: block_bytes(block_data, buffer),
+                            count,
                         },
                     },
                     b"DNA1" => {
@@ -268,9 +922,9 @@ impl BlendParseContext {
                             Block::Principal {
                                 code: [code1, code2],
                                 memory_address,
-                                dna_index: dna_index.try_into().expect("u32 to usize"),
+                                dna_index,
                                 data: BlockData {
-                                    data: block_data.to_vec(),
+                                    data: block_bytes(block_data, buffer),
                                     count: 1,
                                 },
                             }
@@ -285,7 +939,27 @@ impl BlendParseContext {
         }
     }
 
+    /// Parses an entire blend file. Every block gets its own copy of its data.
     pub fn blend<'a, 'b>(&'a mut self, input: &'b [u8]) -> Result<'b, RawBlend>
+    where
+        'b: 'a,
+    {
+        self.blend_with_buffer(input, None)
+    }
+
+    /// Parses an entire blend file. Every block references its data inside `buffer` instead of copying it.
+    pub fn blend_shared<'a, 'b>(&'a mut self, buffer: &'b Arc<BlendBuffer>) -> Result<'b, RawBlend>
+    where
+        'b: 'a,
+    {
+        self.blend_with_buffer(&buffer[..], Some(buffer))
+    }
+
+    fn blend_with_buffer<'a, 'b>(
+        &'a mut self,
+        input: &'b [u8],
+        buffer: Option<&'b Arc<BlendBuffer>>,
+    ) -> Result<'b, RawBlend>
     where
         'b: 'a,
     {
@@ -294,7 +968,18 @@ impl BlendParseContext {
         //This has to happen before the rest of the parser runs
         *self = BlendParseContext::ParsedHeader(header.clone());
 
-        let (input, (mut blocks, _)) = many_till(move |d| self.block(d), tag("ENDB"))(input)?;
+        let mut dna_memory_address = None;
+        let (input, (mut blocks, _)) = many_till(
+            |d| {
+                let (input, block) = self.block(d, buffer)?;
+                if let Block::Dna(..) = block {
+                    let (_, block_header) = self.any_block_header(d)?;
+                    dna_memory_address = Some(block_header.memory_address);
+                }
+                Ok((input, block))
+            },
+            tag("ENDB"),
+        )(input)?;
 
         let dna = if let Some(Block::Dna(dna)) = blocks.pop() {
             // Assumption: The DNA block is always the last one
@@ -303,13 +988,156 @@ impl BlendParseContext {
             return Err(Err::Failure(BlendParseError::NoDnaBlockFound));
         };
 
-        Ok((
-            input,
-            RawBlend {
-                blocks,
-                dna,
-                header,
-            },
-        ))
+        let mut blend = RawBlend::new(header, blocks, dna);
+        blend.dna_memory_address = dna_memory_address;
+
+        Ok((input, blend))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Block, RawBlend};
+    use crate::{
+        parsers::{BlendParseError, Endianness},
+        Blend,
+    };
+
+    /// Returns the names of the objects of `blend`.
+    fn object_names(blend: &Blend) -> Vec<String> {
+        blend
+            .instances_with_code(*b"OB")
+            .map(|obj| obj.get("id").get_string("name"))
+            .collect()
+    }
+
+    /// Writes 3_5.blend with the header and block headers of Blender 5.0.
+    fn large_block_headers_file() -> (RawBlend, Vec<u8>) {
+        let mut raw = RawBlend::from_path("examples/blend_files/3_5.blend").unwrap();
+        raw.header.format_version = 1;
+
+        let mut data = Vec::new();
+        raw.write_to(&mut data).unwrap();
+        (raw, data)
+    }
+
+    #[test]
+    fn read_written_large_block_headers() {
+        let (raw, data) = large_block_headers_file();
+        let blend = Blend::new(&data[..]).unwrap();
+
+        assert!(blend.blend.header.has_large_block_headers());
+        assert_eq!(blend.blend.header.version, *b"305");
+        assert_eq!(blend.blend.blocks().len(), raw.blocks().len());
+        assert_eq!(object_names(&blend), object_names(&Blend { blend: raw }));
+    }
+
+    #[test]
+    fn negative_large_block_lengths() {
+        let (_, data) = large_block_headers_file();
+
+        // The length of the first block comes after its code, struct index and memory address
+        let mut corrupt = data.clone();
+        corrupt[17 + 16..17 + 24].copy_from_slice(&(-1_i64).to_le_bytes());
+        assert!(matches!(
+            RawBlend::from_data(&corrupt[..]),
+            Err(BlendParseError::NomError { .. })
+        ));
+
+        let mut corrupt = data;
+        corrupt[17 + 24..17 + 32].copy_from_slice(&i64::MIN.to_le_bytes());
+        assert!(matches!(
+            RawBlend::from_data(&corrupt[..]),
+            Err(BlendParseError::NomError { .. })
+        ));
+    }
+
+    /// Appends a block to `file`. The block headers follow `LargeBHead8` in Blender's `BLO_core_bhead.hh`:
+    /// `int code; int SDNAnr; uint64_t old; int64_t len; int64_t nr;`.
+    fn large_bhead(
+        file: &mut Vec<u8>,
+        code: &[u8; 4],
+        sdna_nr: i32,
+        old: u64,
+        data: &[u8],
+        nr: i64,
+    ) {
+        file.extend_from_slice(code);
+        file.extend_from_slice(&sdna_nr.to_le_bytes());
+        file.extend_from_slice(&old.to_le_bytes());
+        file.extend_from_slice(&(data.len() as i64).to_le_bytes());
+        file.extend_from_slice(&nr.to_le_bytes());
+        file.extend_from_slice(data);
+    }
+
+    /// A file written by hand in the layout of Blender 5.0, independently of `write_to`, holding the camera object of
+    /// 3_5.blend.
+    fn hand_written_file(source: &Blend) -> (Vec<u8>, usize) {
+        let camera = source
+            .instances_with_code(*b"OB")
+            .find(|obj| obj.get("id").get_string("name") == "OBCamera")
+            .unwrap();
+        let (dna_index, camera_data) = match source.blend.block_at(camera.memory_address()) {
+            Some(Block::Principal {
+                dna_index, data, ..
+            }) => (*dna_index, &data.data[..]),
+            _ => unreachable!(),
+        };
+        let dna = source.blend.dna.to_bytes(Endianness::Little).unwrap();
+
+        let mut file = b"BLENDER17-01v0500".to_vec();
+        large_bhead(
+            &mut file,
+            b"OB\0\0",
+            dna_index as i32,
+            0x7f00_0000_1000,
+            camera_data,
+            1,
+        );
+        large_bhead(&mut file, b"DNA1", 0, 0x7f00_0000_2000, &dna, 1);
+        large_bhead(&mut file, b"ENDB", 0, 0, &[], 0);
+        (file, dna_index)
+    }
+
+    #[test]
+    fn read_hand_written_5_0_file() {
+        let source = Blend::from_path("examples/blend_files/3_5.blend").unwrap();
+        let (file, camera_dna_index) = hand_written_file(&source);
+        let blend = Blend::new(&file[..]).unwrap();
+
+        assert_eq!(blend.blend.header.version, *b"500");
+        assert_eq!(blend.blend.header.format_version, 1);
+        assert_eq!(blend.blend.blocks().len(), 1);
+        assert!(matches!(
+            &blend.blend.blocks()[0],
+            Block::Principal { code: [b'O', b'B'], dna_index, data, .. }
+                if *dna_index == camera_dna_index && data.count == 1
+        ));
+        assert_eq!(
+            blend.blend.blocks()[0].memory_address().unwrap().get(),
+            0x7f00_0000_1000
+        );
+
+        let obj = blend.instances_with_code(*b"OB").next().unwrap();
+        let camera = source
+            .instances_with_code(*b"OB")
+            .find(|obj| obj.get("id").get_string("name") == "OBCamera")
+            .unwrap();
+        assert_eq!(obj.get("id").get_string("name"), "OBCamera");
+        assert_eq!(obj.get_f32_vec("loc"), camera.get_f32_vec("loc"));
+    }
+
+    #[test]
+    fn longer_5_0_header() {
+        let source = Blend::from_path("examples/blend_files/3_5.blend").unwrap();
+        let (file, _) = hand_written_file(&source);
+
+        // Headers longer than the 17 bytes of Blender 5.0 can't be written back unchanged, so they are rejected
+        let mut longer = b"BLENDER18-01v0500\0".to_vec();
+        longer.extend_from_slice(&file[17..]);
+        assert!(matches!(
+            RawBlend::from_data(&longer[..]),
+            Err(BlendParseError::UnsupportedFormatVersion)
+        ));
     }
 }
@@ -0,0 +1,87 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::{Deref, Range},
+    sync::Arc,
+};
+
+/// The bytes of an entire (uncompressed) blend file. Blocks keep a reference to this buffer instead of copying their
+/// data out of it, so the file is only held in memory once.
+pub enum BlendBuffer {
+    /// The file was read or decompressed into memory.
+    Owned(Vec<u8>),
+    /// The file is memory mapped and the operating system pages it in as it is accessed.
+    #[cfg(feature = "memmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Deref for BlendBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            BlendBuffer::Owned(data) => &data[..],
+            #[cfg(feature = "memmap")]
+            BlendBuffer::Mapped(map) => &map[..],
+        }
+    }
+}
+
+impl Debug for BlendBuffer {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            BlendBuffer::Owned(data) => write!(f, "Owned(len: {})", data.len()),
+            #[cfg(feature = "memmap")]
+            BlendBuffer::Mapped(map) => write!(f, "Mapped(len: {})", map.len()),
+        }
+    }
+}
+
+/// The binary data of a single block. It is either a range inside the `BlendBuffer` the file was parsed from, shared
+/// with every other block, or a buffer owned by the block itself. Either way it can be used as a `&[u8]`.
+#[derive(Clone)]
+pub enum BlockBytes {
+    Owned(Vec<u8>),
+    Shared {
+        buffer: Arc<BlendBuffer>,
+        range: Range<usize>,
+    },
+}
+
+impl BlockBytes {
+    /// Returns the bytes mutably. Bytes shared with the file buffer are copied into a buffer owned by the block first,
+    /// so editing a block never touches the file buffer or the other blocks.
+    pub fn to_mut(&mut self) -> &mut [u8] {
+        if let BlockBytes::Shared { buffer, range } = self {
+            *self = BlockBytes::Owned(buffer[range.clone()].to_vec());
+        }
+
+        match self {
+            BlockBytes::Owned(data) => &mut data[..],
+            BlockBytes::Shared { .. } => unreachable!("shared bytes were copied above"),
+        }
+    }
+}
+
+impl Deref for BlockBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            BlockBytes::Owned(data) => &data[..],
+            BlockBytes::Shared { buffer, range } => &buffer[range.clone()],
+        }
+    }
+}
+
+impl Debug for BlockBytes {
+    /// Formats the bytes the same way a `Vec<u8>` would.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl From<Vec<u8>> for BlockBytes {
+    fn from(data: Vec<u8>) -> Self {
+        BlockBytes::Owned(data)
+    }
+}
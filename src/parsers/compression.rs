@@ -0,0 +1,149 @@
+use crate::parsers::BlendParseError;
+
+/// Every gzip stream starts with these two bytes.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// Every zstd frame starts with these four bytes. Blender writes its zstd files as a sequence of frames followed by a
+/// seek table, but the first frame is always a regular one.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// The compression formats Blender has used to save .blend files.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Compression {
+    /// An uncompressed file, starting with `b"BLENDER"`.
+    None,
+    /// Used by Blender versions before 3.0.
+    Gzip,
+    /// Used by Blender 3.0 and later.
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression format from the first bytes of the file. Anything that is not recognized as gzip or
+    /// zstd is assumed to be uncompressed, the header parser will complain if it isn't.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Returns the uncompressed contents of `data`, or `data` itself if it is not compressed.
+///
+/// Returns `BlendParseError::CompressedFileNotSupported` if the file is compressed but the cargo feature for its
+/// format (`gzip` or `zstd`) is not enabled, and `BlendParseError::CorruptCompressedData` if the compressed stream
+/// could not be decoded.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::parsers::compression::decompress;
+/// # fn main() {
+/// let data = std::fs::read("examples/blend_files/2_80.blend").expect("error reading blend file");
+///
+/// // Uncompressed files are returned as they are.
+/// assert_eq!(decompress(data.clone()).unwrap(), data);
+/// # }
+/// ```
+pub fn decompress(data: Vec<u8>) -> Result<Vec<u8>, BlendParseError> {
+    match Compression::detect(&data) {
+        Compression::None => Ok(data),
+        Compression::Gzip => decompress_gzip(&data),
+        Compression::Zstd => decompress_zstd(&data),
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn decompress_gzip(data: &[u8]) -> Result<Vec<u8>, BlendParseError> {
+    use flate2::read::MultiGzDecoder;
+    use std::io::Read;
+
+    let mut buffer = Vec::new();
+    MultiGzDecoder::new(data)
+        .read_to_end(&mut buffer)
+        .map_err(BlendParseError::CorruptCompressedData)?;
+
+    Ok(buffer)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress_gzip(_data: &[u8]) -> Result<Vec<u8>, BlendParseError> {
+    Err(BlendParseError::CompressedFileNotSupported)
+}
+
+#[cfg(feature = "zstd")]
+fn decompress_zstd(data: &[u8]) -> Result<Vec<u8>, BlendParseError> {
+    // The zstd decoder reads every frame in sequence and skips the skippable frame holding the seek table, so
+    // Blender's seekable format decodes like any other multi-frame stream.
+    zstd::stream::decode_all(data).map_err(BlendParseError::CorruptCompressedData)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd(_data: &[u8]) -> Result<Vec<u8>, BlendParseError> {
+    Err(BlendParseError::CompressedFileNotSupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decompress;
+    use crate::parsers::BlendParseError;
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn data() -> Vec<u8> {
+        std::fs::read("examples/blend_files/2_80.blend").unwrap()
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn check_compressed(format: &str, data: &[u8], compressed: Vec<u8>) {
+        assert_eq!(decompress(compressed.clone()).unwrap(), data, "{}", format);
+        let blend = crate::Blend::new(&compressed[..]).unwrap();
+        assert_eq!(blend.instances_with_code(*b"OB").count(), 5, "{}", format);
+
+        let truncated = compressed[..compressed.len() / 2].to_vec();
+        assert!(
+            matches!(
+                decompress(truncated),
+                Err(BlendParseError::CorruptCompressedData(_))
+            ),
+            "{}",
+            format
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip() {
+        use std::io::Write;
+
+        let data = data();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(&data).unwrap();
+        check_compressed("gzip", &data, encoder.finish().unwrap());
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd() {
+        let data = data();
+        let compressed = zstd::stream::encode_all(&data[..], 1).unwrap();
+        check_compressed("zstd", &data, compressed);
+    }
+
+    #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+    #[test]
+    fn disabled_formats() {
+        #[cfg(not(feature = "gzip"))]
+        assert!(matches!(
+            decompress(vec![0x1f, 0x8b, 0]),
+            Err(BlendParseError::CompressedFileNotSupported)
+        ));
+        #[cfg(not(feature = "zstd"))]
+        assert!(matches!(
+            decompress(vec![0x28, 0xb5, 0x2f, 0xfd]),
+            Err(BlendParseError::CompressedFileNotSupported)
+        ));
+    }
+}
@@ -0,0 +1,750 @@
+use crate::parsers::{
+    blend::{Block, BlockData, Header, RawBlend},
+    buffer::BlockBytes,
+    dna::{Dna, DnaType},
+    field::{parse_field, FieldInfo},
+    layout::LayoutError,
+    primitive::{parse_u32, parse_u64, write_u32, write_u64},
+    Endianness, PointerSize,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    fmt,
+    num::NonZeroU64,
+};
+
+/// Returned when a file can't be converted by `RawBlend::convert_to`. Only happens with corrupt files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The layout of a struct of the `Dna`, or of the struct in the header of a block, can't be computed.
+    Layout(LayoutError),
+    /// The file holds more addresses than can be given a 32-bit address when its pointers are narrowed.
+    AddressOverflow,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Layout(err) => write!(f, "{}", err),
+            ConvertError::AddressOverflow => {
+                write!(f, "the addresses of the file don't fit in 32-bit pointers")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConvertError::Layout(err) => Some(err),
+            ConvertError::AddressOverflow => None,
+        }
+    }
+}
+
+impl From<LayoutError> for ConvertError {
+    fn from(err: LayoutError) -> Self {
+        ConvertError::Layout(err)
+    }
+}
+
+/// What the data of a block holds, as far as the converter can tell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BlockContent {
+    /// No typed pointer leads to the block, like the data behind a `void*` field. Its bytes are copied unchanged.
+    Unknown,
+    /// Instances of the struct at this index of `Dna::structs`.
+    Struct(usize),
+    /// Values of the primitive type at this index of `Dna::types`.
+    Primitive(usize),
+    /// Pointers to values of the type at this index of `Dna::types`, like the data of a `Material **mat` field.
+    Pointers(usize),
+}
+
+impl RawBlend {
+    /// Converts the file to little-endian with 64-bit pointers, the layout of every platform Blender supports today.
+    /// Every struct is walked with the `Dna`: primitives are byte swapped and pointers are widened, so structs holding
+    /// pointers grow and the sizes in the `Dna` and the block lengths change accordingly. Files that are already
+    /// little-endian and 64-bit are returned unchanged.
+    ///
+    /// Blocks saved with the raw data struct index are typed by the fields pointing to them. Blocks only reached
+    /// through untyped pointers (`void*`) can't be interpreted and are copied as they are.
+    ///
+    /// Returns an error if the file is corrupt, see `convert_to`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::parsers::blend::RawBlend;
+    /// # fn main() {
+    /// let blend = RawBlend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let converted = blend.to_little_endian_64().expect("corrupt blend file");
+    ///
+    /// let mut original_data = Vec::new();
+    /// let mut converted_data = Vec::new();
+    /// blend.write_to(&mut original_data).unwrap();
+    /// converted.write_to(&mut converted_data).unwrap();
+    /// assert!(original_data == converted_data);
+    /// # }
+    /// ```
+    pub fn to_little_endian_64(&self) -> Result<RawBlend, ConvertError> {
+        self.convert_to(PointerSize::Bits64, Endianness::Little)
+    }
+
+    /// Converts the file to `pointer_size` and `endianness`, like `to_little_endian_64`. When 64-bit pointers are
+    /// narrowed to 32 bits, every address is replaced by a new one which fits, so the pointers keep leading to the same
+    /// blocks but the addresses in the file change. Files converted to 32-bit pointers always use the legacy file
+    /// format, as the format introduced in Blender 5.0 only supports 64-bit pointers.
+    ///
+    /// Returns an error if the layout of a struct of the `Dna`, or of the struct in the header of a block, can't be
+    /// computed, or if the file holds too many addresses to give each a 32-bit one.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::{Blend, parsers::{blend::RawBlend, Endianness, PointerSize}};
+    /// # fn main() {
+    /// let blend = RawBlend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    ///
+    /// // Saves the file as a big-endian 32-bit machine would have.
+    /// let mut data = Vec::new();
+    /// blend.convert_to(PointerSize::Bits32, Endianness::Big).unwrap().write_to(&mut data).unwrap();
+    /// assert!(data.starts_with(b"BLENDER_V280"));
+    ///
+    /// let converted = Blend { blend: RawBlend::from_data(&data[..]).unwrap().to_little_endian_64().unwrap() };
+    /// let cube = converted
+    ///     .instances_with_code(*b"OB")
+    ///     .find(|obj| obj.get("id").get_string("name") == "OBCube")
+    ///     .unwrap();
+    /// let mesh = cube.get("data");
+    /// assert_eq!(mesh.get("id").get_string("name"), "MECube");
+    /// assert_eq!(mesh.get_i32("totvert"), 8);
+    /// # }
+    /// ```
+    pub fn convert_to(
+        &self,
+        pointer_size: PointerSize,
+        endianness: Endianness,
+    ) -> Result<RawBlend, ConvertError> {
+        Converter::new(self, pointer_size, endianness)?.convert()
+    }
+}
+
+/// New addresses for the pointers of a 64-bit file converted to 32-bit, whose addresses may not fit in 32 bits.
+/// Every address gets its own new address, so pointers to the same place still match after the conversion.
+#[derive(Default)]
+struct AddressMap {
+    addresses: HashMap<u64, u64>,
+    last: u64,
+}
+
+impl AddressMap {
+    fn get(&mut self, address: u64) -> Result<u64, ConvertError> {
+        if address == 0 {
+            return Ok(0);
+        }
+
+        if let Some(&new_address) = self.addresses.get(&address) {
+            return Ok(new_address);
+        }
+
+        let new_address = self.last + 8;
+        if new_address > u64::from(u32::MAX) {
+            return Err(ConvertError::AddressOverflow);
+        }
+
+        self.last = new_address;
+        self.addresses.insert(address, new_address);
+        Ok(new_address)
+    }
+}
+
+struct Converter<'a> {
+    source: &'a RawBlend,
+    pointer_size: PointerSize,
+    endianness: Endianness,
+    /// The new addresses of the pointers, if they are narrowed from 64 to 32 bits.
+    addresses: Option<RefCell<AddressMap>>,
+}
+
+impl<'a> Converter<'a> {
+    fn new(
+        source: &'a RawBlend,
+        pointer_size: PointerSize,
+        endianness: Endianness,
+    ) -> Result<Self, ConvertError> {
+        let addresses = match (source.header.pointer_size, pointer_size) {
+            (PointerSize::Bits64, PointerSize::Bits32) => {
+                // Blocks get their new addresses first, in the order they are saved in
+                let mut addresses = AddressMap::default();
+                for block in &source.blocks {
+                    if let Some(address) = block.memory_address() {
+                        addresses.get(address.get())?;
+                    }
+                }
+                Some(RefCell::new(addresses))
+            }
+            _ => None,
+        };
+
+        Ok(Converter {
+            source,
+            pointer_size,
+            endianness,
+            addresses,
+        })
+    }
+
+    fn convert(&self) -> Result<RawBlend, ConvertError> {
+        let contents = self.block_contents()?;
+        let dna = self.convert_dna(&self.source.dna)?;
+
+        let blocks = self
+            .source
+            .blocks
+            .iter()
+            .zip(contents)
+            .map(|(block, content)| -> Result<Block, ConvertError> {
+                Ok(match block {
+                    Block::Rend {
+                        memory_address,
+                        dna_index,
+                        data,
+                    } => Block::Rend {
+                        memory_address: self.convert_block_address(*memory_address)?,
+                        dna_index: *dna_index,
+                        data: self.convert_leading_ints(data),
+                    },
+                    Block::Test {
+                        memory_address,
+                        dna_index,
+                        data,
+                    } => Block::Test {
+                        memory_address: self.convert_block_address(*memory_address)?,
+                        dna_index: *dna_index,
+                        data: self.convert_leading_ints(data),
+                    },
+                    Block::Global {
+                        memory_address,
+                        dna_index,
+                        data,
+                    } => Block::Global {
+                        memory_address: self.convert_block_address(*memory_address)?,
+                        dna_index: *dna_index,
+                        data: self.convert_block_data(content, data)?,
+                    },
+                    Block::Principal {
+                        code,
+                        memory_address,
+                        dna_index,
+                        data,
+                    } => Block::Principal {
+                        code: *code,
+                        memory_address: self.convert_block_address(*memory_address)?,
+                        dna_index: *dna_index,
+                        data: self.convert_block_data(content, data)?,
+                    },
+                    Block::Subsidiary {
+                        memory_address,
+                        dna_index,
+                        data,
+                    } => Block::Subsidiary {
+                        memory_address: self.convert_block_address(*memory_address)?,
+                        dna_index: *dna_index,
+                        data: self.convert_block_data(content, data)?,
+                    },
+                    Block::Dna(_) => Block::Dna(dna.clone()),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let format_version = match self.pointer_size {
+            PointerSize::Bits32 => 0,
+            PointerSize::Bits64 => self.source.header.format_version,
+        };
+        let header = Header {
+            pointer_size: self.pointer_size,
+            endianness: self.endianness,
+            format_version,
+            ..self.source.header.clone()
+        };
+
+        let mut blend = RawBlend::new(header, blocks, dna);
+        blend.dna_memory_address = self
+            .source
+            .dna_memory_address
+            .map(|address| self.convert_block_address(address))
+            .transpose()?;
+        blend.aliases = self.source.aliases.clone();
+        Ok(blend)
+    }
+
+    /// Returns the `Dna` with the sizes of the structs recomputed for the new pointer size.
+    fn convert_dna(&self, dna: &Dna) -> Result<Dna, ConvertError> {
+        let mut sizes = vec![None; dna.types.len()];
+
+        let types = (0..dna.types.len())
+            .map(|type_index| {
+                Ok(DnaType {
+                    name: dna.types[type_index].name.clone(),
+                    bytes_len: self.type_size(dna, type_index, &mut sizes)?,
+                })
+            })
+            .collect::<Result<_, LayoutError>>()?;
+
+        Ok(Dna {
+            names: dna.names.clone(),
+            types,
+            structs: dna.structs.clone(),
+        })
+    }
+
+    /// The size of a type once pointers have the new size. Only structs change size.
+    fn type_size(
+        &self,
+        dna: &Dna,
+        type_index: usize,
+        sizes: &mut Vec<Option<usize>>,
+    ) -> Result<usize, LayoutError> {
+        if let Some(size) = sizes[type_index] {
+            return Ok(size);
+        }
+
+        let size = match self.source.struct_index(type_index) {
+            None => dna.types[type_index].bytes_len,
+            Some(struct_index) => {
+                // Computing the layout checks the types and names of the fields
+                self.source.try_struct_layout(struct_index)?;
+
+                let mut size = 0;
+                for field in &dna.structs[struct_index].fields {
+                    let name = &dna.names[field.name_index];
+                    let (_, (_, field_info)) =
+                        parse_field(name).map_err(|_| LayoutError::InvalidFieldName {
+                            struct_index,
+                            name: name.clone(),
+                        })?;
+
+                    size += match field_info {
+                        FieldInfo::Pointer { .. } | FieldInfo::FnPointer => {
+                            self.pointer_size.bytes_num()
+                        }
+                        FieldInfo::PointerArray { len, .. } => self.pointer_size.bytes_num() * len,
+                        FieldInfo::ValueArray { len, .. } => {
+                            self.type_size(dna, field.type_index, sizes)? * len
+                        }
+                        FieldInfo::Value => self.type_size(dna, field.type_index, sizes)?,
+                    };
+                }
+                size
+            }
+        };
+
+        sizes[type_index] = Some(size);
+        Ok(size)
+    }
+
+    /// The size of a struct in the source file.
+    fn source_struct_size(&self, struct_index: usize) -> Result<usize, LayoutError> {
+        Ok(self.source.try_struct_layout(struct_index)?.size())
+    }
+
+    /// What a pointer to the type at `type_index` leads to.
+    fn pointee_content(&self, type_index: usize) -> BlockContent {
+        match self.source.struct_index(type_index) {
+            Some(struct_index) => BlockContent::Struct(struct_index),
+            // void has no size and tells us nothing about the data
+            None if self.source.dna.types[type_index].bytes_len == 0 => BlockContent::Unknown,
+            None => BlockContent::Primitive(type_index),
+        }
+    }
+
+    /// Finds out what every block holds. Blocks with a struct in their header are taken at their word, the others are
+    /// typed by the fields pointing to them, starting from the typed blocks and following pointers until no new block
+    /// is found.
+    fn block_contents(&self) -> Result<Vec<BlockContent>, ConvertError> {
+        let blocks = &self.source.blocks;
+
+        let mut contents: Vec<BlockContent> = blocks
+            .iter()
+            .map(|block| match block {
+                Block::Principal { dna_index, .. } | Block::Global { dna_index, .. } => {
+                    BlockContent::Struct(*dna_index)
+                }
+                // Blender saves raw data (arrays of floats, of pointers, etc) with the index 0
+                Block::Subsidiary { dna_index, .. } if *dna_index != 0 => {
+                    BlockContent::Struct(*dna_index)
+                }
+                _ => BlockContent::Unknown,
+            })
+            .collect();
+
+        let mut queue: VecDeque<usize> = (0..blocks.len())
+            .filter(|&index| contents[index] != BlockContent::Unknown)
+            .collect();
+
+        while let Some(index) = queue.pop_front() {
+            let data = match &blocks[index] {
+                Block::Principal { data, .. }
+                | Block::Subsidiary { data, .. }
+                | Block::Global { data, .. } => data,
+                _ => continue,
+            };
+
+            let mut targets = Vec::new();
+            self.pointer_targets(contents[index], &data.data, &mut targets)?;
+
+            for (address, content) in targets {
+                let target = NonZeroU64::new(address)
+                    .and_then(|address| self.source.block_at(address))
+                    .and_then(|block| self.source.block_position(block));
+
+                if let Some(target) = target {
+                    if contents[target] == BlockContent::Unknown && content != BlockContent::Unknown
+                    {
+                        contents[target] = content;
+                        queue.push_back(target);
+                    }
+                }
+            }
+        }
+
+        Ok(contents)
+    }
+
+    /// Collects the addresses stored in `data` together with what they point to.
+    fn pointer_targets(
+        &self,
+        content: BlockContent,
+        data: &[u8],
+        targets: &mut Vec<(u64, BlockContent)>,
+    ) -> Result<(), LayoutError> {
+        let pointer_size = self.source.header.pointer_size.bytes_num();
+
+        match content {
+            BlockContent::Struct(struct_index) => {
+                let size = self.source_struct_size(struct_index)?;
+                if size > 0 {
+                    for element in data.chunks_exact(size) {
+                        self.struct_pointer_targets(struct_index, element, targets)?;
+                    }
+                }
+            }
+            BlockContent::Pointers(type_index) => {
+                for pointer in data.chunks_exact(pointer_size) {
+                    targets.push((self.read_pointer(pointer), self.pointee_content(type_index)));
+                }
+            }
+            BlockContent::Primitive(_) | BlockContent::Unknown => {}
+        }
+
+        Ok(())
+    }
+
+    fn struct_pointer_targets(
+        &self,
+        struct_index: usize,
+        data: &[u8],
+        targets: &mut Vec<(u64, BlockContent)>,
+    ) -> Result<(), LayoutError> {
+        let pointer_size = self.source.header.pointer_size.bytes_num();
+        let layout = self.source.try_struct_layout(struct_index)?;
+
+        for field in layout.fields.values() {
+            let field_data = &data[field.data_start..field.data_start + field.data_len];
+
+            let content = match field.info {
+                FieldInfo::Pointer {
+                    indirection_count: 1,
+                }
+                | FieldInfo::PointerArray {
+                    indirection_count: 1,
+                    ..
+                } => self.pointee_content(field.type_index),
+                FieldInfo::Pointer {
+                    indirection_count: 2,
+                }
+                | FieldInfo::PointerArray {
+                    indirection_count: 2,
+                    ..
+                } => BlockContent::Pointers(field.type_index),
+                FieldInfo::Value | FieldInfo::ValueArray { .. } => {
+                    if let Some(inner) = self.source.struct_index(field.type_index) {
+                        let size = self.source_struct_size(inner)?;
+                        if size > 0 {
+                            for element in field_data.chunks_exact(size) {
+                                self.struct_pointer_targets(inner, element, targets)?;
+                            }
+                        }
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+
+            for pointer in field_data.chunks_exact(pointer_size) {
+                targets.push((self.read_pointer(pointer), content));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_pointer(&self, data: &[u8]) -> u64 {
+        let endianness = self.source.header.endianness;
+
+        match self.source.header.pointer_size {
+            PointerSize::Bits32 => u64::from(parse_u32(data, endianness)),
+            PointerSize::Bits64 => parse_u64(data, endianness),
+        }
+    }
+
+    /// Returns the address `address` has in the converted file.
+    fn convert_address(&self, address: u64) -> Result<u64, ConvertError> {
+        match &self.addresses {
+            Some(addresses) => addresses.borrow_mut().get(address),
+            None => Ok(address),
+        }
+    }
+
+    fn convert_block_address(&self, address: NonZeroU64) -> Result<NonZeroU64, ConvertError> {
+        Ok(NonZeroU64::new(self.convert_address(address.get())?)
+            .expect("non-null addresses stay non-null"))
+    }
+
+    fn write_pointer(&self, address: u64, out: &mut Vec<u8>) -> Result<(), ConvertError> {
+        let address = self.convert_address(address)?;
+
+        match self.pointer_size {
+            PointerSize::Bits32 => out.extend_from_slice(&write_u32(
+                u32::try_from(address).map_err(|_| ConvertError::AddressOverflow)?,
+                self.endianness,
+            )),
+            PointerSize::Bits64 => out.extend_from_slice(&write_u64(address, self.endianness)),
+        }
+
+        Ok(())
+    }
+
+    fn convert_pointers(&self, data: &[u8], out: &mut Vec<u8>) -> Result<(), ConvertError> {
+        for pointer in data.chunks_exact(self.source.header.pointer_size.bytes_num()) {
+            self.write_pointer(self.read_pointer(pointer), out)?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies primitives of `size` bytes, swapping their bytes if the endianness changes.
+    fn convert_primitives(&self, data: &[u8], size: usize, out: &mut Vec<u8>) {
+        if size <= 1 || self.source.header.endianness == self.endianness {
+            out.extend_from_slice(data);
+            return;
+        }
+
+        let mut values = data.chunks_exact(size);
+        for value in &mut values {
+            out.extend(value.iter().rev());
+        }
+        out.extend_from_slice(values.remainder());
+    }
+
+    fn convert_struct(
+        &self,
+        struct_index: usize,
+        data: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), ConvertError> {
+        let dna = &self.source.dna;
+        let layout = self.source.try_struct_layout(struct_index)?;
+
+        for field in layout.fields.values() {
+            let field_data = &data[field.data_start..field.data_start + field.data_len];
+
+            match field.info {
+                FieldInfo::Pointer { .. }
+                | FieldInfo::PointerArray { .. }
+                | FieldInfo::FnPointer => self.convert_pointers(field_data, out)?,
+                FieldInfo::Value | FieldInfo::ValueArray { .. } => {
+                    let inner = match self.source.struct_index(field.type_index) {
+                        Some(inner) => Some((inner, self.source_struct_size(inner)?)),
+                        None => None,
+                    };
+
+                    match inner {
+                        Some((inner, size)) if size > 0 => {
+                            for element in field_data.chunks_exact(size) {
+                                self.convert_struct(inner, element, out)?;
+                            }
+                        }
+                        _ if field.is_primitive => self.convert_primitives(
+                            field_data,
+                            dna.types[field.type_index].bytes_len,
+                            out,
+                        ),
+                        _ => out.extend_from_slice(field_data),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn convert_block_data(
+        &self,
+        content: BlockContent,
+        data: &BlockData,
+    ) -> Result<BlockData, ConvertError> {
+        let data_in = &data.data[..];
+        let mut out = Vec::with_capacity(data_in.len());
+
+        // Bytes that don't make up a whole element are copied unchanged
+        let remainder = match content {
+            BlockContent::Struct(struct_index) => match self.source_struct_size(struct_index)? {
+                0 => data_in,
+                size => {
+                    let mut elements = data_in.chunks_exact(size);
+                    for element in &mut elements {
+                        self.convert_struct(struct_index, element, &mut out)?;
+                    }
+                    elements.remainder()
+                }
+            },
+            BlockContent::Pointers(_) => {
+                let mut pointers =
+                    data_in.chunks_exact(self.source.header.pointer_size.bytes_num());
+                for pointer in &mut pointers {
+                    self.write_pointer(self.read_pointer(pointer), &mut out)?;
+                }
+                pointers.remainder()
+            }
+            BlockContent::Primitive(type_index) => {
+                self.convert_primitives(
+                    data_in,
+                    self.source.dna.types[type_index].bytes_len,
+                    &mut out,
+                );
+                &[]
+            }
+            BlockContent::Unknown => data_in,
+        };
+        out.extend_from_slice(remainder);
+
+        Ok(BlockData {
+            data: BlockBytes::Owned(out),
+            count: data.count,
+        })
+    }
+
+    /// `REND` and `TEST` blocks start with two ints, followed by bytes.
+    fn convert_leading_ints(&self, data: &BlockData) -> BlockData {
+        let mut out = Vec::with_capacity(data.data.len());
+        let ints_len = data.data.len().min(8);
+
+        self.convert_primitives(&data.data[..ints_len], 4, &mut out);
+        out.extend_from_slice(&data.data[ints_len..]);
+
+        BlockData {
+            data: BlockBytes::Owned(out),
+            count: data.count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConvertError;
+    use crate::{
+        parsers::{
+            blend::{Block, RawBlend},
+            layout::LayoutError,
+            Endianness, PointerSize,
+        },
+        Blend, Instance,
+    };
+
+    #[test]
+    fn corrupt_struct_index() {
+        let mut blend = RawBlend::from_path("examples/blend_files/2_80.blend")
+            .expect("error loading blend file");
+
+        for block in blend.blocks_mut().iter_mut() {
+            if let Block::Principal { dna_index, .. } = block {
+                *dna_index = 60000;
+                break;
+            }
+        }
+
+        assert_eq!(
+            blend
+                .convert_to(PointerSize::Bits32, Endianness::Big)
+                .unwrap_err(),
+            ConvertError::Layout(LayoutError::NoSuchStruct {
+                struct_index: 60000
+            })
+        );
+    }
+
+    fn cube(blend: &Blend) -> Instance<'_> {
+        blend
+            .instances_with_code(*b"OB")
+            .find(|obj| obj.get("id").get_string("name") == "OBCube")
+            .unwrap()
+    }
+
+    #[test]
+    fn convert_and_back() {
+        let blend = RawBlend::from_path("examples/blend_files/2_80.blend").unwrap();
+        let mut data = Vec::new();
+        blend
+            .convert_to(PointerSize::Bits32, Endianness::Big)
+            .unwrap()
+            .write_to(&mut data)
+            .unwrap();
+
+        let big_endian_32 = Blend::new(&data[..]).unwrap();
+        let converted = Blend {
+            blend: RawBlend::from_data(&data[..])
+                .unwrap()
+                .to_little_endian_64()
+                .unwrap(),
+        };
+        let original = Blend { blend };
+        let location = cube(&original).get_f32_vec("loc");
+
+        for blend in &[&original, &big_endian_32, &converted] {
+            let cube = cube(blend);
+            assert_eq!(cube.get_f32_vec("loc"), location);
+            assert_eq!(cube.get_f32_vec("size"), [1.0, 1.0, 1.0]);
+
+            let mesh = cube.get("data");
+            assert_eq!(mesh.get("id").get_string("name"), "MECube");
+            assert_eq!(mesh.get_i32("totvert"), 8);
+            let positions = mesh
+                .get_iter("mvert")
+                .map(|vert| vert.get_f32_vec("co"))
+                .collect::<Vec<_>>();
+            assert_eq!(positions.len(), 8);
+            assert!(positions.iter().all(|co| co.iter().all(|c| c.abs() == 1.0)));
+            assert_eq!(
+                mesh.get_iter("mat")
+                    .map(|mat| mat.get("id").get_string("name"))
+                    .collect::<Vec<_>>(),
+                ["MAMaterial"]
+            );
+
+            let scene = blend.file_global().unwrap().get("curscene");
+            assert_eq!(scene.get("id").get_string("name"), "SCScene");
+            assert_eq!(
+                blend.render_info().collect::<Vec<_>>(),
+                original.render_info().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                blend.thumbnail().unwrap().pixels,
+                original.thumbnail().unwrap().pixels
+            );
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::parsers::{Endianness, PointerSize, Result};
+use crate::parsers::{
+    primitive::{is_primitive_type_name, write_u16, write_u32},
+    Endianness, PointerSize, Result,
+};
 use nom::{
     bytes::complete::{tag, take, take_while},
     combinator::map,
@@ -6,28 +9,131 @@ use nom::{
     number::complete::{be_u16, be_u32, le_u16, le_u32},
     sequence::terminated,
 };
-use std::convert::TryInto;
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt, io,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Dna {
     pub names: Vec<String>,
     pub types: Vec<DnaType>,
     pub structs: Vec<DnaStruct>,
 }
 
-#[derive(Debug)]
+impl Dna {
+    /// Encodes the `Dna` as the data of a `DNA1` block. This is the inverse of `DnaParseContext::dna`.
+    ///
+    /// Returns an `InvalidData` error if the number of names, types or structs, or a size or index, doesn't fit the
+    /// sizes used by the format.
+    pub fn to_bytes(&self, endianness: Endianness) -> io::Result<Vec<u8>> {
+        fn pad(data: &mut Vec<u8>) {
+            data.resize(data.len().next_multiple_of(4), 0);
+        }
+
+        let mut data = Vec::new();
+        data.extend_from_slice(b"SDNA");
+
+        data.extend_from_slice(b"NAME");
+        data.extend_from_slice(&write_u32(
+            dna_value("number of names", self.names.len())?,
+            endianness,
+        ));
+        for name in &self.names {
+            data.extend_from_slice(name.as_bytes());
+            data.push(0);
+        }
+        pad(&mut data);
+
+        data.extend_from_slice(b"TYPE");
+        data.extend_from_slice(&write_u32(
+            dna_value("number of types", self.types.len())?,
+            endianness,
+        ));
+        for r#type in &self.types {
+            data.extend_from_slice(r#type.name.as_bytes());
+            data.push(0);
+        }
+        pad(&mut data);
+
+        data.extend_from_slice(b"TLEN");
+        for r#type in &self.types {
+            data.extend_from_slice(&write_u16(
+                dna_value("size of a type", r#type.bytes_len)?,
+                endianness,
+            ));
+        }
+        pad(&mut data);
+
+        data.extend_from_slice(b"STRC");
+        data.extend_from_slice(&write_u32(
+            dna_value("number of structs", self.structs.len())?,
+            endianness,
+        ));
+        for r#struct in &self.structs {
+            data.extend_from_slice(&write_u16(
+                dna_value("type index of a struct", r#struct.type_index)?,
+                endianness,
+            ));
+            data.extend_from_slice(&write_u16(
+                dna_value("number of fields of a struct", r#struct.fields.len())?,
+                endianness,
+            ));
+            for field in &r#struct.fields {
+                data.extend_from_slice(&write_u16(
+                    dna_value("type index of a field", field.type_index)?,
+                    endianness,
+                ));
+                data.extend_from_slice(&write_u16(
+                    dna_value("name index of a field", field.name_index)?,
+                    endianness,
+                ));
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// Converts `value` to the integer type the `DNA1` block stores it as, or returns an `InvalidData` error naming what
+/// the value is if it doesn't fit.
+fn dna_value<T, U>(what: &str, value: T) -> io::Result<U>
+where
+    T: Copy + fmt::Display,
+    U: TryFrom<T>,
+{
+    U::try_from(value).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the {} of the DNA ({}) doesn't fit in its block",
+                what, value
+            ),
+        )
+    })
+}
+
+#[derive(Debug, Clone)]
 pub struct DnaType {
     pub name: String,
     pub bytes_len: usize, //size in bytes of the type
 }
 
-#[derive(Debug)]
+impl DnaType {
+    /// Returns true if the type is one of the basic types of the DNA (`int`, `float`, `uint64_t`, etc) instead of a
+    /// struct.
+    pub fn is_primitive(&self) -> bool {
+        is_primitive_type_name(&self.name)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct DnaField {
     pub type_index: usize, //index on Dna::types array
     pub name_index: usize, //index on Dna::names array
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DnaStruct {
     pub type_index: usize, //index on Dna::types array
     pub fields: Vec<DnaField>,
@@ -70,12 +176,8 @@ impl DnaParseContext {
         )(input)?;
 
         let skip_len = {
-            let mut sum = *all_names_len.borrow();
-            let res = sum;
-            while sum % 4 != 0 {
-                sum += 1;
-            }
-            sum - res
+            let sum = *all_names_len.borrow();
+            sum.next_multiple_of(4) - sum
         };
 
         let (input, _) = take(skip_len)(input)?;
@@ -109,12 +211,8 @@ impl DnaParseContext {
         )(input)?;
 
         let skip_len = {
-            let mut sum = *all_type_names_len.borrow();
-            let res = sum;
-            while sum % 4 != 0 {
-                sum += 1;
-            }
-            sum - res
+            let sum = *all_type_names_len.borrow();
+            sum.next_multiple_of(4) - sum
         };
         let (input, _) = take(skip_len)(input)?;
 
@@ -128,12 +226,8 @@ impl DnaParseContext {
         )(input)?;
 
         let skip_len = {
-            let mut sum = types_len * 2;
-            let res = sum;
-            while sum % 4 != 0 {
-                sum += 1;
-            }
-            sum - res
+            let sum = types_len * 2;
+            sum.next_multiple_of(4) - sum
         };
         let (input, _) = take(skip_len)(input)?;
 
@@ -144,7 +238,7 @@ impl DnaParseContext {
                 .zip(type_lenghts)
                 .map(|(name, length)| DnaType {
                     name,
-                    bytes_len: length.try_into().expect("u32 to usize"),
+                    bytes_len: length.into(),
                 })
                 .collect(),
         ))
@@ -184,15 +278,15 @@ impl DnaParseContext {
                 next_input = input;
 
                 fields.push(DnaField {
-                    type_index: field_type_index.try_into().expect("u32 to usize"),
-                    name_index: field_name_index.try_into().expect("u32 to usize"),
+                    type_index: field_type_index.into(),
+                    name_index: field_name_index.into(),
                 });
             }
 
             final_input = next_input;
 
             structs.push(DnaStruct {
-                type_index: struct_name_index.try_into().expect("u32 to usize"),
+                type_index: struct_name_index.into(),
                 fields,
             });
         }
@@ -219,3 +313,48 @@ impl DnaParseContext {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Dna, DnaField, DnaStruct, DnaType};
+    use crate::parsers::Endianness;
+    use std::io::ErrorKind;
+
+    fn small_dna() -> Dna {
+        Dna {
+            names: vec![String::from("value")],
+            types: vec![
+                DnaType {
+                    name: String::from("int"),
+                    bytes_len: 4,
+                },
+                DnaType {
+                    name: String::from("Value"),
+                    bytes_len: 4,
+                },
+            ],
+            structs: vec![DnaStruct {
+                type_index: 1,
+                fields: vec![DnaField {
+                    type_index: 0,
+                    name_index: 0,
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn to_bytes_values_that_dont_fit() {
+        assert!(small_dna().to_bytes(Endianness::Little).is_ok());
+
+        let mut dna = small_dna();
+        dna.types[1].bytes_len = 70000;
+        let err = dna.to_bytes(Endianness::Little).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+        let mut dna = small_dna();
+        dna.structs[0].fields[0].name_index = usize::from(u16::MAX) + 1;
+        let err = dna.to_bytes(Endianness::Big).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,320 @@
+use crate::parsers::{
+    dna::{Dna, DnaStruct},
+    field::{parse_field, FieldInfo},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// The type of a struct field: the name of the DNA type and how the field holds it (value, array, pointer...).
+#[derive(Debug, Clone)]
+pub struct FieldType {
+    pub type_name: String,
+    pub info: FieldInfo,
+}
+
+impl FieldType {
+    /// How many pointers deep the field is, `0` for values. Function pointers count as a single pointer.
+    pub fn indirection_count(&self) -> usize {
+        match self.info {
+            FieldInfo::Value | FieldInfo::ValueArray { .. } => 0,
+            FieldInfo::Pointer { indirection_count }
+            | FieldInfo::PointerArray {
+                indirection_count, ..
+            } => indirection_count,
+            FieldInfo::FnPointer => 1,
+        }
+    }
+
+    /// The array dimensions of the field, empty if it is not an array.
+    pub fn dimensions(&self) -> &[usize] {
+        match &self.info {
+            FieldInfo::ValueArray { dimensions, .. }
+            | FieldInfo::PointerArray { dimensions, .. } => dimensions,
+            _ => &[],
+        }
+    }
+
+    /// Formats a field of this type named `name` as a C declaration, like `float loc[3]` or `Mesh *data`.
+    pub fn declaration(&self, name: &str) -> String {
+        let dimensions: String = self
+            .dimensions()
+            .iter()
+            .map(|d| format!("[{}]", d))
+            .collect();
+
+        match self.info {
+            FieldInfo::FnPointer => format!("{} (*{})()", self.type_name, name),
+            _ => format!(
+                "{} {}{}{}",
+                self.type_name,
+                "*".repeat(self.indirection_count()),
+                name,
+                dimensions
+            ),
+        }
+    }
+}
+
+/// A change to a single field of a struct present in both `Dna`s.
+#[derive(Debug, Clone)]
+pub enum FieldChange {
+    Added {
+        name: String,
+        field_type: FieldType,
+    },
+    Removed {
+        name: String,
+        field_type: FieldType,
+    },
+    /// The type of the field changed, or it became a pointer (or stopped being one).
+    Retyped {
+        name: String,
+        old: FieldType,
+        new: FieldType,
+    },
+    /// The field has the same type but its array dimensions changed.
+    Resized {
+        name: String,
+        old: FieldType,
+        new: FieldType,
+    },
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldChange::Added { name, field_type } => {
+                write!(f, "+ {}", field_type.declaration(name))
+            }
+            FieldChange::Removed { name, field_type } => {
+                write!(f, "- {}", field_type.declaration(name))
+            }
+            FieldChange::Retyped { name, old, new } | FieldChange::Resized { name, old, new } => {
+                write!(
+                    f,
+                    "~ {} -> {}",
+                    old.declaration(name),
+                    new.declaration(name)
+                )
+            }
+        }
+    }
+}
+
+/// A struct present in both `Dna`s whose fields or size changed.
+#[derive(Debug, Clone)]
+pub struct StructDiff {
+    pub name: String,
+    pub old_size: usize,
+    pub new_size: usize,
+    /// The fields that were added, removed or changed. Empty if only the size changed, which happens when a struct
+    /// used as a field grew or shrank.
+    pub field_changes: Vec<FieldChange>,
+}
+
+/// The differences between two `Dna`s, usually from files saved by different versions of Blender. Structs are matched
+/// by type name and fields by name, so a renamed field shows up as a removed and an added field. Created by
+/// `Dna::diff`.
+///
+/// The `Display` implementation prints a report with one line per struct and per field change.
+#[derive(Debug, Clone, Default)]
+pub struct DnaDiff {
+    /// The names of the structs only found in the new `Dna`.
+    pub added_structs: Vec<String>,
+    /// The names of the structs only found in the old `Dna`.
+    pub removed_structs: Vec<String>,
+    pub changed_structs: Vec<StructDiff>,
+}
+
+impl DnaDiff {
+    /// Returns true if both `Dna`s describe the same structs.
+    pub fn is_empty(&self) -> bool {
+        self.added_structs.is_empty()
+            && self.removed_structs.is_empty()
+            && self.changed_structs.is_empty()
+    }
+}
+
+impl fmt::Display for DnaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in &self.added_structs {
+            writeln!(f, "+ struct {}", name)?;
+        }
+
+        for name in &self.removed_structs {
+            writeln!(f, "- struct {}", name)?;
+        }
+
+        for r#struct in &self.changed_structs {
+            if r#struct.old_size == r#struct.new_size {
+                writeln!(
+                    f,
+                    "~ struct {} ({} bytes)",
+                    r#struct.name, r#struct.new_size
+                )?;
+            } else {
+                writeln!(
+                    f,
+                    "~ struct {} ({} -> {} bytes)",
+                    r#struct.name, r#struct.old_size, r#struct.new_size
+                )?;
+            }
+
+            for change in &r#struct.field_changes {
+                writeln!(f, "    {}", change)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Dna {
+    /// Compares this `Dna` with a newer one and reports the structs and fields that were added, removed or changed.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    /// let old = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let new = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    ///
+    /// let diff = old.blend.dna.diff(&new.blend.dna);
+    /// println!("{}", diff);
+    /// # assert!(diff.removed_structs.iter().any(|s| s == "Mesh_Runtime"));
+    /// # assert!(diff.changed_structs.iter().any(|s| s.name == "Mesh"));
+    /// # assert!(old.blend.dna.diff(&old.blend.dna).is_empty());
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if a field name can't be parsed.
+    pub fn diff(&self, new: &Dna) -> DnaDiff {
+        let old_structs = self.structs_by_name();
+        let new_structs = new.structs_by_name();
+
+        let mut diff = DnaDiff::default();
+
+        for r#struct in &self.structs {
+            let name = &self.types[r#struct.type_index].name;
+            if !new_structs.contains_key(&name[..]) {
+                diff.removed_structs.push(name.clone());
+            }
+        }
+
+        for new_struct in &new.structs {
+            let name = &new.types[new_struct.type_index].name;
+
+            let old_struct = match old_structs.get(&name[..]) {
+                Some(old_struct) => old_struct,
+                None => {
+                    diff.added_structs.push(name.clone());
+                    continue;
+                }
+            };
+
+            let struct_diff = StructDiff {
+                name: name.clone(),
+                old_size: self.types[old_struct.type_index].bytes_len,
+                new_size: new.types[new_struct.type_index].bytes_len,
+                field_changes: field_changes(
+                    self.field_types(old_struct),
+                    new.field_types(new_struct),
+                ),
+            };
+
+            if struct_diff.old_size != struct_diff.new_size || !struct_diff.field_changes.is_empty()
+            {
+                diff.changed_structs.push(struct_diff);
+            }
+        }
+
+        diff
+    }
+
+    fn structs_by_name(&self) -> HashMap<&str, &DnaStruct> {
+        self.structs
+            .iter()
+            .map(|s| (&self.types[s.type_index].name[..], s))
+            .collect()
+    }
+
+    /// The name and type of every field of `r#struct`, in order.
+    fn field_types(&self, r#struct: &DnaStruct) -> Vec<(String, FieldType)> {
+        r#struct
+            .fields
+            .iter()
+            .map(|field| {
+                let (_, (name, info)) = parse_field(&self.names[field.name_index])
+                    .expect("field name could not be parsed");
+
+                (
+                    String::from(name),
+                    FieldType {
+                        type_name: self.types[field.type_index].name.clone(),
+                        info,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Compares the fields of two versions of a struct. Changed and added fields are listed in the order of the new
+/// struct, followed by the removed fields.
+fn field_changes(old: Vec<(String, FieldType)>, new: Vec<(String, FieldType)>) -> Vec<FieldChange> {
+    let new_names: HashSet<&str> = new.iter().map(|(name, _)| &name[..]).collect();
+    let mut old_fields: HashMap<String, FieldType> = HashMap::new();
+    let mut removed = Vec::new();
+
+    for (name, field_type) in old {
+        if !new_names.contains(&name[..]) {
+            removed.push(FieldChange::Removed {
+                name: name.clone(),
+                field_type: field_type.clone(),
+            });
+        }
+        old_fields.insert(name, field_type);
+    }
+
+    let mut changes = Vec::new();
+
+    for (name, new_type) in new {
+        let old_type = match old_fields.remove(&name) {
+            Some(old_type) => old_type,
+            None => {
+                changes.push(FieldChange::Added {
+                    name,
+                    field_type: new_type,
+                });
+                continue;
+            }
+        };
+
+        let is_fn_pointer = |t: &FieldType| matches!(t.info, FieldInfo::FnPointer);
+
+        if old_type.type_name != new_type.type_name
+            || old_type.indirection_count() != new_type.indirection_count()
+            || is_fn_pointer(&old_type) != is_fn_pointer(&new_type)
+        {
+            changes.push(FieldChange::Retyped {
+                name,
+                old: old_type,
+                new: new_type,
+            });
+        } else if old_type.dimensions() != new_type.dimensions() {
+            changes.push(FieldChange::Resized {
+                name,
+                old: old_type,
+                new: new_type,
+            });
+        }
+    }
+
+    changes.extend(removed);
+    changes
+}
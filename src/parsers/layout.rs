@@ -0,0 +1,207 @@
+use crate::parsers::{
+    blend::Header,
+    dna::Dna,
+    field::{parse_field, FieldInfo},
+};
+use linked_hash_map::LinkedHashMap;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+/// Represents a field inside a struct. The data `FieldTemplate` keeps is used to interpret the raw bytes of the block.
+#[derive(Debug, Clone)]
+pub struct FieldTemplate {
+    //pub name: String,
+    pub info: FieldInfo,
+    /// The index of this field's type inside the `Dna::types` array.
+    pub type_index: usize,
+    /// The type name of this field. Used for pretty printing and some sanity checks.
+    pub type_name: String,
+    /// The index of the data in the `Instance` owned by this field.
+    pub data_start: usize,
+    /// The length in bytes of the data in the `Instance` owned by this field.
+    pub data_len: usize,
+    /// A field can represent a primitive or a struct.
+    pub is_primitive: bool,
+}
+
+/// The fields of a struct and where their data is inside an instance of that struct. A layout only depends on the
+/// `Dna` and the pointer size of the file, so it is computed once per struct and shared by all its instances.
+#[derive(Debug)]
+pub struct StructLayout {
+    /// The index of the struct inside the `Dna::structs` array.
+    pub struct_index: usize,
+    /// The index of the struct's type inside the `Dna::types` array.
+    pub type_index: usize,
+    /// The fields of the struct.
+    pub fields: LinkedHashMap<String, FieldTemplate>, //We use a LinkedHashMap here because we want to preserve insertion order
+}
+
+/// Returned when the layout of a struct can't be computed. Only happens with corrupt files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The struct index, usually read from a block header, is not the index of a struct in `Dna::structs`.
+    NoSuchStruct { struct_index: usize },
+    /// The struct, or one of its fields, refers to a type or a name missing from the `Dna`.
+    InvalidIndex { struct_index: usize },
+    /// The name of a field, like `*mat[4]`, can't be parsed.
+    InvalidFieldName { struct_index: usize, name: String },
+    /// The fields of the struct don't add up to its size.
+    WrongSize {
+        struct_index: usize,
+        size: usize,
+        fields_size: usize,
+    },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::NoSuchStruct { struct_index } => {
+                write!(f, "the DNA has no struct at index {}", struct_index)
+            }
+            LayoutError::InvalidIndex { struct_index } => write!(
+                f,
+                "struct {} refers to a type or name missing from the DNA",
+                struct_index
+            ),
+            LayoutError::InvalidFieldName { struct_index, name } => write!(
+                f,
+                "struct {} has a field with the invalid name '{}'",
+                struct_index, name
+            ),
+            LayoutError::WrongSize {
+                struct_index,
+                size,
+                fields_size,
+            } => write!(
+                f,
+                "struct {} is {} bytes long but its fields take {} bytes",
+                struct_index, size, fields_size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl StructLayout {
+    /// Computes the layout of the struct at `struct_index`. Returns an error if the `Dna` is corrupt: if a field name
+    /// can't be parsed, if an index is out of bounds or if the fields don't add up to the size of the struct.
+    pub fn new(dna: &Dna, header: &Header, struct_index: usize) -> Result<Self, LayoutError> {
+        let invalid_index = || LayoutError::InvalidIndex { struct_index };
+
+        let dna_struct = dna
+            .structs
+            .get(struct_index)
+            .ok_or(LayoutError::NoSuchStruct { struct_index })?;
+        let dna_type = dna
+            .types
+            .get(dna_struct.type_index)
+            .ok_or_else(invalid_index)?;
+
+        let mut fields = LinkedHashMap::new();
+        let mut data_start = 0;
+
+        for field in &dna_struct.fields {
+            let field_dna_type = dna.types.get(field.type_index).ok_or_else(invalid_index)?;
+            let field_full_name = dna.names.get(field.name_index).ok_or_else(invalid_index)?;
+
+            let is_primitive = field_dna_type.is_primitive();
+            let (_, (field_name, field_info)) =
+                parse_field(field_full_name).map_err(|_| LayoutError::InvalidFieldName {
+                    struct_index,
+                    name: field_full_name.clone(),
+                })?;
+
+            let field_bytes_len = match field_info {
+                FieldInfo::Pointer { .. } | FieldInfo::FnPointer => header.pointer_size.bytes_num(),
+                FieldInfo::PointerArray { len, .. } => header.pointer_size.bytes_num() * len,
+                FieldInfo::ValueArray { len, .. } => field_dna_type.bytes_len * len,
+                FieldInfo::Value => field_dna_type.bytes_len,
+            };
+
+            fields.insert(
+                String::from(field_name),
+                FieldTemplate {
+                    info: field_info,
+                    type_index: field.type_index,
+                    type_name: field_dna_type.name.clone(),
+                    data_start,
+                    data_len: field_bytes_len,
+                    is_primitive,
+                },
+            );
+
+            data_start += field_bytes_len;
+        }
+        if dna_type.bytes_len != data_start {
+            return Err(LayoutError::WrongSize {
+                struct_index,
+                size: dna_type.bytes_len,
+                fields_size: data_start,
+            });
+        }
+
+        Ok(StructLayout {
+            struct_index,
+            type_index: dna_struct.type_index,
+            fields,
+        })
+    }
+
+    /// The size in bytes of an instance of the struct.
+    pub fn size(&self) -> usize {
+        self.fields
+            .values()
+            .next_back()
+            .map_or(0, |field| field.data_start + field.data_len)
+    }
+}
+
+/// Holds the layout of every struct in a `Dna`. Layouts are computed the first time they are requested.
+#[derive(Debug, Default)]
+pub struct LayoutCache {
+    layouts: Vec<OnceLock<Result<Arc<StructLayout>, LayoutError>>>,
+    /// Maps the index of a struct's type in `Dna::types` to the index of the struct in `Dna::structs`.
+    struct_indices: HashMap<usize, usize>,
+}
+
+impl LayoutCache {
+    /// Creates an empty cache for the structs of `dna`.
+    pub fn new(dna: &Dna) -> Self {
+        let mut struct_indices = HashMap::with_capacity(dna.structs.len());
+        for (struct_index, dna_struct) in dna.structs.iter().enumerate() {
+            struct_indices
+                .entry(dna_struct.type_index)
+                .or_insert(struct_index);
+        }
+
+        LayoutCache {
+            layouts: (0..dna.structs.len()).map(|_| OnceLock::new()).collect(),
+            struct_indices,
+        }
+    }
+
+    /// Returns the index in `Dna::structs` of the struct of the type at `type_index`, or `None` if the type is not a
+    /// struct.
+    pub fn struct_index(&self, type_index: usize) -> Option<usize> {
+        self.struct_indices.get(&type_index).copied()
+    }
+
+    /// Returns the layout of the struct at `struct_index`, computing it if needed. Errors are cached like layouts.
+    pub fn get(
+        &self,
+        dna: &Dna,
+        header: &Header,
+        struct_index: usize,
+    ) -> Result<Arc<StructLayout>, LayoutError> {
+        self.layouts
+            .get(struct_index)
+            .ok_or(LayoutError::NoSuchStruct { struct_index })?
+            .get_or_init(|| StructLayout::new(dna, header, struct_index).map(Arc::new))
+            .clone()
+    }
+}
@@ -1,7 +1,14 @@
+pub mod aliases;
 pub mod blend;
+pub mod buffer;
+pub mod compression;
+pub mod convert;
 pub mod dna;
+pub mod dna_diff;
 pub mod field;
+pub mod layout;
 pub mod primitive;
+pub mod query;
 
 use nom::{
     error::{ErrorKind, ParseError},
@@ -12,7 +19,7 @@ use std::io;
 type Result<'a, T> = IResult<&'a [u8], T, BlendParseError>;
 
 /// Size of a pointer on the machine used to create the .blend file.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum PointerSize {
     Bits32,
     Bits64,
@@ -29,7 +36,7 @@ impl PointerSize {
 }
 
 /// Endianness of the machine used to create the .blend file.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Endianness {
     Little,
     Big,
@@ -64,9 +71,17 @@ pub enum BlendParseError {
     /// Returned when the DNA block is not found at the end of the blend file.
     NoDnaBlockFound,
 
-    /// Returned when the file doesn't start with `b"BLENDER"`. The assumption is that the file
-    /// is a gzip compressed blend file, but this isn't actually tested for.
+    /// Returned when the file doesn't start with `b"BLENDER"`. This happens when the file is
+    /// compressed with gzip or zstd and the matching cargo feature (`gzip` or `zstd`) is not
+    /// enabled, or when the file is not a blend file at all.
     CompressedFileNotSupported,
+
+    /// Returned when the file uses the header introduced in Blender 5.0 but with a file format version
+    /// this crate doesn't know how to read.
+    UnsupportedFormatVersion,
+
+    /// Returned when the file starts like a gzip or zstd stream but decompressing it fails.
+    CorruptCompressedData(io::Error),
 }
 
 impl ParseError<&[u8]> for BlendParseError {
@@ -1,20 +1,54 @@
-use super::Endianness;
+use super::{field::FieldInfo, layout::FieldTemplate, Endianness, PointerSize};
 use nom::number::complete::{
     be_f32, be_f64, be_i16, be_i32, be_i64, be_i8, be_u16, be_u32, be_u64, le_f32, le_f64, le_i16,
     le_i32, le_i64, le_i8, le_u16, le_u32, le_u64,
 };
+use std::fmt;
 
-pub(crate) trait BlendPrimitive {
+/// The names of the types Blender's DNA treats as primitives. Every other type is a struct, or an opaque type that
+/// is only used behind pointers. Types are classified by name because their position in `Dna::types` changes when
+/// Blender adds new basic types.
+pub const PRIMITIVE_TYPE_NAMES: &[&str] = &[
+    "char", "uchar", "short", "ushort", "int", "long", "ulong", "float", "double", "int64_t",
+    "uint64_t", "void", "int8_t", "uint8_t", "int16_t", "uint16_t", "int32_t", "uint32_t", "bool",
+];
+
+/// Returns true if `name` is the name of a primitive type. See `PRIMITIVE_TYPE_NAMES`.
+pub fn is_primitive_type_name(name: &str) -> bool {
+    PRIMITIVE_TYPE_NAMES.contains(&name)
+}
+
+/// A Rust type that can be read from (and written to) fields of primitive DNA types. Used by `Instance::get_value` and
+/// `Instance::get_vec`.
+pub trait BlendPrimitive: Copy {
+    /// The number of bytes the value takes in a blend file.
+    const SIZE: usize = std::mem::size_of::<Self>();
+
+    /// Reads the value from `data`, which must be exactly as long as the primitive.
     fn parse(data: &[u8], endianness: Endianness) -> Self;
-    fn blender_name() -> &'static str;
+    /// The names of the DNA types this Rust type can be read from. `long` and `ulong` are listed for both the 32 and
+    /// 64-bit integers, the size of the field decides which one fits.
+    fn blender_names() -> &'static [&'static str];
+    /// Writes the value into `data`, which must be exactly as long as the primitive.
+    fn write(self, data: &mut [u8], endianness: Endianness);
+
+    /// The DNA type name usually read as this Rust type.
+    fn blender_name() -> &'static str {
+        Self::blender_names()[0]
+    }
 }
 
 impl BlendPrimitive for char {
+    const SIZE: usize = 1;
+
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_u8(data, endianness) as char
     }
-    fn blender_name() -> &'static str {
-        "char"
+    fn blender_names() -> &'static [&'static str] {
+        &["char"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        (self as u8).write(data, endianness)
     }
 }
 
@@ -22,8 +56,14 @@ impl BlendPrimitive for i8 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_i8(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "char"
+    fn blender_names() -> &'static [&'static str] {
+        &["char", "int8_t"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
     }
 }
 
@@ -31,8 +71,26 @@ impl BlendPrimitive for u8 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_u8(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "uchar"
+    fn blender_names() -> &'static [&'static str] {
+        &["uchar", "uint8_t", "char"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
+    }
+}
+
+impl BlendPrimitive for bool {
+    fn parse(data: &[u8], endianness: Endianness) -> Self {
+        parse_u8(data, endianness) != 0
+    }
+    fn blender_names() -> &'static [&'static str] {
+        &["bool"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        u8::from(self).write(data, endianness)
     }
 }
 
@@ -40,8 +98,14 @@ impl BlendPrimitive for u16 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_u16(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "ushort"
+    fn blender_names() -> &'static [&'static str] {
+        &["ushort", "uint16_t"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
     }
 }
 
@@ -49,8 +113,14 @@ impl BlendPrimitive for i16 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_i16(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "short"
+    fn blender_names() -> &'static [&'static str] {
+        &["short", "int16_t"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
     }
 }
 
@@ -58,8 +128,14 @@ impl BlendPrimitive for i32 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_i32(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "int"
+    fn blender_names() -> &'static [&'static str] {
+        &["int", "int32_t", "long"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
     }
 }
 
@@ -67,8 +143,14 @@ impl BlendPrimitive for u32 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_u32(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "ulong"
+    fn blender_names() -> &'static [&'static str] {
+        &["ulong", "uint32_t"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
     }
 }
 
@@ -76,8 +158,14 @@ impl BlendPrimitive for f32 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_f32(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "float"
+    fn blender_names() -> &'static [&'static str] {
+        &["float"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
     }
 }
 
@@ -85,8 +173,14 @@ impl BlendPrimitive for f64 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_f64(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "double"
+    fn blender_names() -> &'static [&'static str] {
+        &["double"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
     }
 }
 
@@ -94,8 +188,14 @@ impl BlendPrimitive for u64 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_u64(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "uint64_t"
+    fn blender_names() -> &'static [&'static str] {
+        &["uint64_t", "ulong"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
     }
 }
 
@@ -103,8 +203,201 @@ impl BlendPrimitive for i64 {
     fn parse(data: &[u8], endianness: Endianness) -> Self {
         parse_i64(data, endianness)
     }
-    fn blender_name() -> &'static str {
-        "int64_t"
+    fn blender_names() -> &'static [&'static str] {
+        &["int64_t", "long"]
+    }
+    fn write(self, data: &mut [u8], endianness: Endianness) {
+        data.copy_from_slice(&match endianness {
+            Endianness::Little => self.to_le_bytes(),
+            Endianness::Big => self.to_be_bytes(),
+        });
+    }
+}
+
+/// The Rust types primitive DNA types are read as. Code that handles every primitive type dispatches on this instead
+/// of matching type names itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveKind {
+    I8,
+    U8,
+    Bool,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+    F32,
+    F64,
+}
+
+impl PrimitiveKind {
+    /// Returns the kind of the primitive DNA type `type_name` whose values are `element_len` bytes long, or `None` for
+    /// `void` and types that are not primitives. `long` and `ulong` are 32 or 64 bits wide depending on the platform
+    /// the file was saved on, `element_len` decides which.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::parsers::primitive::PrimitiveKind;
+    /// assert_eq!(PrimitiveKind::from_type_name("short", 2), Some(PrimitiveKind::I16));
+    /// assert_eq!(PrimitiveKind::from_type_name("long", 8), Some(PrimitiveKind::I64));
+    /// assert_eq!(PrimitiveKind::from_type_name("long", 4), Some(PrimitiveKind::I32));
+    /// assert_eq!(PrimitiveKind::from_type_name("void", 0), None);
+    /// assert_eq!(PrimitiveKind::from_type_name("Object", 1200), None);
+    /// ```
+    pub fn from_type_name(type_name: &str, element_len: usize) -> Option<Self> {
+        let kind = match type_name {
+            "char" | "int8_t" => PrimitiveKind::I8,
+            "uchar" | "uint8_t" => PrimitiveKind::U8,
+            "bool" => PrimitiveKind::Bool,
+            "short" | "int16_t" => PrimitiveKind::I16,
+            "ushort" | "uint16_t" => PrimitiveKind::U16,
+            "int" | "int32_t" => PrimitiveKind::I32,
+            "uint32_t" => PrimitiveKind::U32,
+            "int64_t" => PrimitiveKind::I64,
+            "uint64_t" => PrimitiveKind::U64,
+            "long" if element_len == 8 => PrimitiveKind::I64,
+            "long" => PrimitiveKind::I32,
+            "ulong" if element_len == 8 => PrimitiveKind::U64,
+            "ulong" => PrimitiveKind::U32,
+            "float" => PrimitiveKind::F32,
+            "double" => PrimitiveKind::F64,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    /// Returns the kind of the primitives stored in `field`, or behind it if it is a pointer. The size of the elements
+    /// of `long` arrays is only known from the size of the array. Behind pointers, `long` is assumed to be as wide as
+    /// a pointer like on the platforms Blender saves 64-bit files on (except Windows).
+    pub fn from_field(field: &FieldTemplate, pointer_size: PointerSize) -> Option<Self> {
+        if !field.is_primitive {
+            return None;
+        }
+
+        let element_len = match field.info {
+            FieldInfo::Value => field.data_len,
+            FieldInfo::ValueArray { len, .. } => field.data_len / len.max(1),
+            _ => pointer_size.bytes_num(),
+        };
+
+        Self::from_type_name(&field.type_name, element_len)
+    }
+
+    /// The number of bytes a value of this kind takes in a blend file.
+    pub fn size(self) -> usize {
+        match self {
+            PrimitiveKind::I8 | PrimitiveKind::U8 | PrimitiveKind::Bool => 1,
+            PrimitiveKind::I16 | PrimitiveKind::U16 => 2,
+            PrimitiveKind::I32 | PrimitiveKind::U32 | PrimitiveKind::F32 => 4,
+            PrimitiveKind::I64 | PrimitiveKind::U64 | PrimitiveKind::F64 => 8,
+        }
+    }
+
+    /// Returns true for `F32` and `F64`.
+    pub fn is_float(self) -> bool {
+        matches!(self, PrimitiveKind::F32 | PrimitiveKind::F64)
+    }
+
+    /// The name of the Rust type values of this kind are read as, like `"i16"`.
+    pub fn rust_name(self) -> &'static str {
+        match self {
+            PrimitiveKind::I8 => "i8",
+            PrimitiveKind::U8 => "u8",
+            PrimitiveKind::Bool => "bool",
+            PrimitiveKind::I16 => "i16",
+            PrimitiveKind::U16 => "u16",
+            PrimitiveKind::I32 => "i32",
+            PrimitiveKind::U32 => "u32",
+            PrimitiveKind::I64 => "i64",
+            PrimitiveKind::U64 => "u64",
+            PrimitiveKind::F32 => "f32",
+            PrimitiveKind::F64 => "f64",
+        }
+    }
+
+    /// Reads a value of this kind from `data`, which must be exactly `size()` bytes long.
+    pub fn parse(self, data: &[u8], endianness: Endianness) -> PrimitiveValue {
+        match self {
+            PrimitiveKind::I8 => PrimitiveValue::I8(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::U8 => PrimitiveValue::U8(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::Bool => PrimitiveValue::Bool(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::I16 => PrimitiveValue::I16(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::U16 => PrimitiveValue::U16(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::I32 => PrimitiveValue::I32(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::U32 => PrimitiveValue::U32(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::I64 => PrimitiveValue::I64(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::U64 => PrimitiveValue::U64(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::F32 => PrimitiveValue::F32(BlendPrimitive::parse(data, endianness)),
+            PrimitiveKind::F64 => PrimitiveValue::F64(BlendPrimitive::parse(data, endianness)),
+        }
+    }
+}
+
+/// A primitive value read as the Rust type matching its DNA type. See `Instance::get_primitive`. Its `Debug`
+/// implementation (and `Serialize`, with the `serde` feature) is the one of the value it holds.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PrimitiveValue {
+    I8(i8),
+    U8(u8),
+    Bool(bool),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+}
+
+impl PrimitiveValue {
+    /// Returns the value as an `i128`, which holds every integer kind, or `None` for floats. `bool` is 0 or 1.
+    pub fn as_i128(self) -> Option<i128> {
+        let value = match self {
+            PrimitiveValue::I8(v) => i128::from(v),
+            PrimitiveValue::U8(v) => i128::from(v),
+            PrimitiveValue::Bool(v) => i128::from(v),
+            PrimitiveValue::I16(v) => i128::from(v),
+            PrimitiveValue::U16(v) => i128::from(v),
+            PrimitiveValue::I32(v) => i128::from(v),
+            PrimitiveValue::U32(v) => i128::from(v),
+            PrimitiveValue::I64(v) => i128::from(v),
+            PrimitiveValue::U64(v) => i128::from(v),
+            PrimitiveValue::F32(_) | PrimitiveValue::F64(_) => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Returns the value as an `f64`. 64-bit integers above 2^53 lose precision.
+    pub fn as_f64(self) -> f64 {
+        match self {
+            PrimitiveValue::F32(v) => f64::from(v),
+            PrimitiveValue::F64(v) => v,
+            // Every integer kind fits in an `i128`
+            _ => self.as_i128().unwrap_or_default() as f64,
+        }
+    }
+}
+
+impl fmt::Debug for PrimitiveValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PrimitiveValue::I8(v) => v.fmt(f),
+            PrimitiveValue::U8(v) => v.fmt(f),
+            PrimitiveValue::Bool(v) => v.fmt(f),
+            PrimitiveValue::I16(v) => v.fmt(f),
+            PrimitiveValue::U16(v) => v.fmt(f),
+            PrimitiveValue::I32(v) => v.fmt(f),
+            PrimitiveValue::U32(v) => v.fmt(f),
+            PrimitiveValue::I64(v) => v.fmt(f),
+            PrimitiveValue::U64(v) => v.fmt(f),
+            PrimitiveValue::F32(v) => v.fmt(f),
+            PrimitiveValue::F64(v) => v.fmt(f),
+        }
     }
 }
 
@@ -183,3 +476,31 @@ pub fn parse_u64(slice: &[u8], endianness: Endianness) -> u64 {
     };
     val
 }
+
+pub fn write_u16(value: u16, endianness: Endianness) -> [u8; 2] {
+    match endianness {
+        Endianness::Little => value.to_le_bytes(),
+        Endianness::Big => value.to_be_bytes(),
+    }
+}
+
+pub fn write_u32(value: u32, endianness: Endianness) -> [u8; 4] {
+    match endianness {
+        Endianness::Little => value.to_le_bytes(),
+        Endianness::Big => value.to_be_bytes(),
+    }
+}
+
+pub fn write_u64(value: u64, endianness: Endianness) -> [u8; 8] {
+    match endianness {
+        Endianness::Little => value.to_le_bytes(),
+        Endianness::Big => value.to_be_bytes(),
+    }
+}
+
+pub fn write_i64(value: i64, endianness: Endianness) -> [u8; 8] {
+    match endianness {
+        Endianness::Little => value.to_le_bytes(),
+        Endianness::Big => value.to_be_bytes(),
+    }
+}
@@ -0,0 +1,126 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{take_till, take_till1, take_while1},
+    character::complete::{char, digit1, space0},
+    combinator::{all_consuming, map, map_res, peek},
+    multi::{many0, separated_list1},
+    sequence::{delimited, pair, terminated},
+    IResult,
+};
+use std::fmt;
+
+/// A parsed query, like `OB[id.name=OBCube].data.mvert[*].co`: a list of segments separated by dots. See
+/// `Blend::query` for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    pub segments: Vec<QuerySegment>,
+}
+
+/// A field name (or a block code for the first segment of `Blend::query`) and the selectors following it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuerySegment {
+    pub name: String,
+    pub selectors: Vec<Selector>,
+}
+
+/// What to keep from a list, written between brackets after a segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    /// `[*]`, every element.
+    All,
+    /// `[12]`, a single element.
+    Index(usize),
+    /// `[id.name=OBCube]`, the elements for which the query `path` returns a value equal to `value`. The value can be
+    /// quoted to include `]`.
+    Filter { path: Query, value: String },
+}
+
+/// Returned when a query can't be parsed. `position` is the byte offset where parsing failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParseError {
+    pub query: String,
+    pub position: usize,
+}
+
+impl fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid query '{}' at position {}",
+            self.query, self.position
+        )
+    }
+}
+
+impl std::error::Error for QueryParseError {}
+
+type Result<'a, T> = IResult<&'a str, T>;
+
+fn identifier(input: &str) -> Result<'_, &str> {
+    take_while1(|c: char| c.is_ascii_alphanumeric() || c == '_')(input)
+}
+
+fn literal(input: &str) -> Result<'_, &str> {
+    alt((
+        delimited(char('"'), take_till(|c| c == '"'), char('"')),
+        map(take_till1(|c| c == ']'), str::trim_end),
+    ))(input)
+}
+
+fn filter(input: &str) -> Result<'_, Selector> {
+    let (input, path) = query(input)?;
+    let (input, _) = delimited(space0, char('='), space0)(input)?;
+    let (input, value) = literal(input)?;
+
+    Ok((
+        input,
+        Selector::Filter {
+            path,
+            value: String::from(value),
+        },
+    ))
+}
+
+fn selector(input: &str) -> Result<'_, Selector> {
+    delimited(
+        char('['),
+        alt((
+            map(char('*'), |_| Selector::All),
+            map_res(terminated(digit1, peek(char(']'))), |index: &str| {
+                index.parse().map(Selector::Index)
+            }),
+            filter,
+        )),
+        char(']'),
+    )(input)
+}
+
+fn segment(input: &str) -> Result<'_, QuerySegment> {
+    map(pair(identifier, many0(selector)), |(name, selectors)| {
+        QuerySegment {
+            name: String::from(name),
+            selectors,
+        }
+    })(input)
+}
+
+fn query(input: &str) -> Result<'_, Query> {
+    map(separated_list1(char('.'), segment), |segments| Query {
+        segments,
+    })(input)
+}
+
+/// Parses a query, which must be entirely made of segments and selectors.
+pub fn parse_query(input: &str) -> std::result::Result<Query, QueryParseError> {
+    match all_consuming(query)(input) {
+        Ok((_, query)) => Ok(query),
+        Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => Err(QueryParseError {
+            query: String::from(input),
+            position: input.len() - err.input.len(),
+        }),
+        Err(nom::Err::Incomplete(_)) => Err(QueryParseError {
+            query: String::from(input),
+            position: input.len(),
+        }),
+    }
+}
@@ -1,13 +1,37 @@
+mod dump;
+mod edit;
+mod error;
+mod from_instance;
+mod graph;
+mod library;
+mod path;
+mod query;
+#[cfg(feature = "serde")]
+mod serialize;
+
+pub use crate::parsers::layout::{FieldTemplate, StructLayout};
 use crate::parsers::{
-    blend::{Block, BlockData, Header as BlendHeader, RawBlend},
-    dna::{Dna, DnaStruct, DnaType},
-    field::{parse_field, FieldInfo},
+    blend::{Block, BlockData, RawBlend},
+    dna::Dna,
+    field::FieldInfo,
+    layout::LayoutError,
     primitive::*,
     BlendParseError, Endianness, PointerSize,
 };
-use linked_hash_map::LinkedHashMap;
+pub use dump::Dumper;
+pub use edit::{InstanceLocation, InstanceMut, LocationError};
+pub use error::AccessError;
+pub use from_instance::{FromField, FromInstance, FromInstanceError};
+pub use graph::DotExporter;
+pub use library::{BlendLibrarySet, LinkedLibrary};
+use path::InstancePath;
+pub use query::QueryValue;
+#[cfg(feature = "serde")]
+pub use serialize::{SerializableInstance, SerializeOptions};
 use std::fmt;
-use std::{io::Read, mem::size_of, num::NonZeroU64, path::Path};
+use std::{
+    collections::HashSet, convert::TryFrom, io::Read, num::NonZeroU64, path::Path, sync::Arc,
+};
 
 /// An `Instance`'s data can be a reference to a `Block` if the `Instance` represents a root or subsidiary block,
 /// or it can be raw bytes if the `Instance` was created by accessing a field in another `Instance`.
@@ -26,21 +50,17 @@ pub enum PointerInfo<'a> {
 }
 
 impl<'a> InstanceDataFormat<'a> {
-    /// `get` accesses only a specific slice of the underlying data.
-    pub fn get(&self, start: usize, len: usize) -> &'a [u8] {
-        &self.data()[start..start + len]
+    /// `get` accesses only a specific slice of the underlying data. Returns `None` if the data is too short.
+    pub fn get(&self, start: usize, len: usize) -> Option<&'a [u8]> {
+        self.data()?.get(start..start.checked_add(len)?)
     }
 
-    /// Simplifies the access to the underlying data inside the `InstanceDataFormat`.
-    pub fn data(&self) -> &'a [u8] {
+    /// Simplifies the access to the underlying data inside the `InstanceDataFormat`. Returns `None` for the `DNA1`
+    /// block, whose data is parsed into a `Dna`.
+    pub fn data(&self) -> Option<&'a [u8]> {
         match self {
-            InstanceDataFormat::Block(block) => match block {
-                Block::Principal { data, .. }
-                | Block::Subsidiary { data, .. }
-                | Block::Global { data, .. } => &data.data[..],
-                _ => unimplemented!(),
-            },
-            InstanceDataFormat::Raw(data) => &data[..],
+            InstanceDataFormat::Block(block) => block.data().map(|data| &data.data[..]),
+            InstanceDataFormat::Raw(data) => Some(data),
         }
     }
 
@@ -62,34 +82,12 @@ impl<'a> InstanceDataFormat<'a> {
     /// Returns the memory address of the underlying block, if it has one.
     pub fn memory_address(&self) -> Option<NonZeroU64> {
         match self {
-            InstanceDataFormat::Block(block) => match block {
-                Block::Principal { memory_address, .. }
-                | Block::Subsidiary { memory_address, .. }
-                | Block::Global { memory_address, .. } => Some(*memory_address),
-                _ => unimplemented!(),
-            },
+            InstanceDataFormat::Block(block) => block.memory_address(),
             InstanceDataFormat::Raw(_) => None,
         }
     }
 }
 
-/// Represents a field inside a struct. The data `FieldTemplate` keeps is used to interpret the raw bytes of the block.
-#[derive(Debug, Clone)]
-pub struct FieldTemplate {
-    //pub name: String,
-    pub info: FieldInfo,
-    /// The index of this field's type inside the `Dna::types` array.
-    pub type_index: usize,
-    /// The type name of this field. Used for pretty printing and some sanity checks.
-    pub type_name: String,
-    /// The index of the data in the `Instance` owned by this field.
-    pub data_start: usize,
-    /// The length in bytes of the data in the `Instance` owned by this field.
-    pub data_len: usize,
-    /// A field can represent a primitive or a struct.
-    pub is_primitive: bool,
-}
-
 /// Represents a block of data inside the blend file. An `Instance` can be a camera, a mesh, a material, or anything
 /// else Blender uses internally, like material nodes, user settings or render options. An `Instance` is conceptually a
 /// `struct`: a collection of named fields which can themselves be structs or primitives.
@@ -98,378 +96,32 @@ pub struct Instance<'a> {
     /// References to the `Dna` and the `ParsedBlend` are kept because we only interpret data when the user accesses it.
     dna: &'a Dna,
     blend: &'a RawBlend,
-    pub type_name: String,
+    /// The block `data` belongs to. Instances created from a field share the block of the instance they come from.
+    block: &'a Block,
+    /// The libraries used to follow links into other files, if this `Instance` was created by a `BlendLibrarySet`.
+    libraries: Option<&'a BlendLibrarySet>,
+    /// How this `Instance` was reached from a root block. See `Instance::path`.
+    path: InstancePath<'a>,
+    pub type_name: &'a str,
     /// The raw binary data this `Instance` owns.
     pub data: InstanceDataFormat<'a>,
-    /// The fields of this `Instance`.
-    pub fields: LinkedHashMap<String, FieldTemplate>, //We use a LinkedHashMap here because we want to preserve insertion order
+    /// The fields of this `Instance`. Shared by every `Instance` of the same struct.
+    pub layout: Arc<StructLayout>,
 }
 
 impl<'a> std::fmt::Debug for Instance<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Instance")
             .field("type_name", &self.type_name)
-            .field("fields", &self.fields)
+            .field("path", &self.path.to_string())
+            .field("fields", &self.layout.fields)
             .finish()
     }
 }
 
-#[allow(clippy::cognitive_complexity)]
-fn fmt_instance(
-    seen_addresses: &mut std::collections::HashSet<NonZeroU64>,
-    f: &mut fmt::Formatter,
-    inst: &Instance,
-    ident: usize,
-) -> fmt::Result {
-    let ident_str: String = " ".repeat(4 * ident);
-
-    write!(f, "{}", inst.type_name)?;
-
-    match (inst.data.code(), inst.data.memory_address()) {
-        (Some(code), Some(memory_address)) => {
-            write!(
-                f,
-                " (code:{}|@{})",
-                String::from_utf8_lossy(&code[0..=1]),
-                memory_address
-            )?;
-        }
-        (Some(code), None) => {
-            write!(f, " (code:{})", String::from_utf8_lossy(&code[0..=1]))?;
-        }
-        (None, Some(memory_address)) => {
-            write!(f, " (@{})", memory_address)?;
-        }
-        (None, None) => {}
-    }
-
-    writeln!(f, " {{")?;
-
-    for (field_name, field) in inst.fields.iter().filter(|(n, _)| !n.starts_with("_pad")) {
-        match &field.info {
-            FieldInfo::Value => {
-                write!(f, "{}    {}: ", ident_str, field_name)?;
-                match &field.type_name[..] {
-                    "char" => writeln!(f, "{} = {};", field.type_name, inst.get_i8(field_name))?,
-                    "uchar" => writeln!(f, "{} = {};", field.type_name, inst.get_u8(field_name))?,
-                    "short" => writeln!(f, "{} = {};", field.type_name, inst.get_i16(field_name))?,
-                    "ushort" => writeln!(f, "{} = {};", field.type_name, inst.get_u16(field_name))?,
-                    "int" => writeln!(f, "{} = {};", field.type_name, inst.get_i32(field_name))?,
-                    "long" => writeln!(f, "{} = {};", field.type_name, inst.get_i32(field_name))?,
-                    "ulong" => writeln!(f, "{} = {};", field.type_name, inst.get_u32(field_name))?,
-                    "float" => writeln!(f, "{} = {};", field.type_name, inst.get_f32(field_name))?,
-                    "double" => writeln!(f, "{} = {};", field.type_name, inst.get_f64(field_name))?,
-                    "int64_t" => writeln!(f, "{} = {};", field.type_name, inst.get_i64(field_name))?,
-                    "uint64_t" => writeln!(f, "{} = {};", field.type_name, inst.get_u64(field_name))?,
-                    "void" =>  writeln!(f, "{} = ();", field.type_name)?,
-                    "int8_t" => writeln!(f, "{} = {};", field.type_name, inst.get_i8(field_name))?,
-                    _ if field.is_primitive => panic!("unknown primitive {:?}", field),
-                    _ => {
-                        if field.type_name == "ListBase" {
-                            if inst.is_valid(field_name) {
-                                let list_base_instance = inst
-                                    .get_iter(field_name)
-                                    .next()
-                                    .expect("a valid ListBase always has at least one element");
-                                writeln!(f, "ListBase<{}>[?] = [", list_base_instance.type_name)?;
-                                if list_base_instance.data.code().is_none() {
-                                    if !seen_addresses
-                                        .contains(&list_base_instance.memory_address())
-                                    {
-                                        seen_addresses.insert(list_base_instance.memory_address());
-                                        write!(f, "{}        ", ident_str)?;
-                                        fmt_instance(
-                                            seen_addresses,
-                                            f,
-                                            &list_base_instance,
-                                            ident + 2,
-                                        )?;
-                                    } else {
-                                        writeln!(
-                                            f,
-                                            "{}        @{},",
-                                            ident_str,
-                                            list_base_instance.memory_address()
-                                        )?;
-                                    }
-                                } else {
-                                    unimplemented!()
-                                }
-                                writeln!(f, "{}    ];", ident_str)?;
-                            } else {
-                                writeln!(f, "ListBase<unknown>[] = null;")?;
-                            }
-                        } else {
-                            fmt_instance(seen_addresses, f, &inst.get(field_name), ident + 1)?;
-                        }
-                    }
-                }
-            }
-            FieldInfo::ValueArray { dimensions, .. } => {
-                write!(
-                    f,
-                    "{}    {}: {}{:?} = ",
-                    ident_str, field_name, field.type_name, dimensions
-                )?;
-                match &field.type_name[..] {
-                    "char" => {
-                        let data = inst.data.get(field.data_start, field.data_len);
-
-                        // Some char arrays might be interpreted as strings if their first element is 0.
-                        if let Ok(string_data) = String::from_utf8(
-                            data.iter().take_while(|&&b| b != 0).cloned().collect(),
-                        ) {
-                            writeln!(f, "\"{}\";", string_data)?;
-                        } else {
-                            writeln!(f, "{:?};", inst.get_u8_vec(field_name))?;
-                        }
-                    }
-                    "uchar" => writeln!(f, "{:?};", inst.get_u8_vec(field_name))?,
-                    "short" => writeln!(f, "{:?};", inst.get_i16_vec(field_name))?,
-                    "ushort" => writeln!(f, "{:?};", inst.get_u16_vec(field_name))?,
-                    "int" => writeln!(f, "{:?};", inst.get_i32_vec(field_name))?,
-                    "long" => writeln!(f, "{:?};", inst.get_i32_vec(field_name))?,
-                    "ulong" => writeln!(f, "{:?};", inst.get_u32_vec(field_name))?,
-                    "float" => writeln!(f, "{:?};", inst.get_f32_vec(field_name))?,
-                    "double" => writeln!(f, "{:?};", inst.get_f64_vec(field_name))?,
-                    "int64_t" => writeln!(f, "{:?};", inst.get_i64_vec(field_name))?,
-                    "void" => writeln!(f, "void;")?,
-                    "int8_t" => writeln!(f, "{:?};", inst.get_i8_vec(field_name))?,
-                    "uint64_t" => writeln!(f, "{:?};", inst.get_u64_vec(field_name))?,
-                    _ if field.is_primitive => panic!("unknown primitive"),
-                    _ => {
-                        writeln!(f, "[")?;
-                        if let Some(i) = inst.get_iter(field_name).next() {
-                            write!(f, "{}        ", ident_str)?;
-                            fmt_instance(seen_addresses, f, &i, ident + 2)?;
-                        }
-                        writeln!(f, "{}    ];", ident_str)?;
-                    }
-                }
-            }
-            FieldInfo::Pointer {
-                indirection_count: 1,
-            } => {
-                if ["next", "prev", "first", "last"]
-                    .iter()
-                    .any(|n| n == field_name)
-                {
-                    if inst.is_valid(field_name) {
-                        writeln!(
-                            f,
-                            "{}    {}: {} = (@{});",
-                            ident_str,
-                            field_name,
-                            inst.get(field_name).type_name,
-                            inst.parse_ptr_address(inst.data.get(field.data_start, field.data_len))
-                                .unwrap()
-                        )?
-                    } else {
-                        writeln!(
-                            f,
-                            "{}    {}: {} = null;",
-                            ident_str, field_name, field.type_name
-                        )?
-                    }
-                } else if inst.is_valid(field_name) {
-
-                    let mut has_non_primitive_data = true;
-
-                    match field.info {
-                        FieldInfo::Pointer { indirection_count } if indirection_count == 1 => {
-                            let pointer = inst.get_ptr(field);
-                            let block = match pointer {
-                                PointerInfo::Block(block) => block,
-                                PointerInfo::Null | PointerInfo::Invalid => panic!(
-                                    "field '{}' is null or doesn't point to a valid block. ({:?})",
-                                    field_name, field
-                                ),
-                            };
-
-                            if let Block::Subsidiary { dna_index, .. } = block {
-                                has_non_primitive_data = if field.type_index >= 13 {
-                                    dna_index >= &13 || inst
-                                        .dna()
-                                        .structs
-                                        .iter()
-                                        .any(|s| s.type_index == field.type_index)
-                                } else {
-                                    let r#struct = &inst.dna().structs[*dna_index];
-                                    r#struct.type_index >= 13
-                                };
-                            }
-                        }
-                        _ => (),
-                    }
-
-                    if has_non_primitive_data {
-                        let ptr_inst = inst.get(field_name);
-                        //assert!(!seen_addresses.contains(&ptr_inst.memory_address()));
-
-                        if ptr_inst.data.code().is_none()
-                            && !seen_addresses.contains(&ptr_inst.memory_address())
-                        {
-                            if ptr_inst.type_name == "Link" {
-                                match ptr_inst.data {
-                                    InstanceDataFormat::Block(block) => match block {
-                                        Block::Principal { data, .. }
-                                        | Block::Subsidiary { data, .. } => {
-                                            writeln!(
-                                                f,
-                                                "{}    {}: {}^ = {:?};",
-                                                ident_str, field_name, field.type_name, data.data
-                                            )?
-                                        }
-                                        _ => unimplemented!(),
-                                    }
-                                    _ => {
-                                        writeln!(
-                                            f,
-                                            "{}    {}: {}! = {:?};",
-                                            ident_str, field_name, field.type_name, ptr_inst
-                                        )?
-                                    }
-                                }
-                                
-                            } else {
-                                seen_addresses.insert(ptr_inst.memory_address());
-                                match ptr_inst.data {
-                                    InstanceDataFormat::Block(block) => match block {
-                                        Block::Principal { data, .. }
-                                        | Block::Subsidiary { data, .. } => {
-                                            if data.count > 1 {
-                                                writeln!(
-                                                    f,
-                                                    "{}    {}: {}[{}] = [",
-                                                    ident_str,
-                                                    field_name,
-                                                    field.type_name,
-                                                    data.count
-                                                )?;
-                                                if let Some(p) = inst.get_iter(field_name).next() {
-                                                    write!(f, "{}        ", ident_str)?;
-                                                    fmt_instance(seen_addresses, f, &p, ident + 2)?;
-                                                }
-                                                writeln!(f, "{}    ];", ident_str)?;
-                                            } else {
-                                                write!(
-                                                    f,
-                                                    "{}    {}: {} = ",
-                                                    ident_str, field_name, field.type_name
-                                                )?;
-                                                fmt_instance(
-                                                    seen_addresses,
-                                                    f,
-                                                    &ptr_inst,
-                                                    ident + 1,
-                                                )?;
-                                            }
-                                        }
-                                        _ => unimplemented!(),
-                                    },
-                                    _ => unimplemented!(),
-                                }
-                            }
-                        } else {
-                            writeln!(
-                                f,
-                                "{}    {}: {} = (@{});",
-                                ident_str,
-                                field_name,
-                                field.type_name,
-                                inst.parse_ptr_address(
-                                    inst.data.get(field.data_start, field.data_len)
-                                )
-                                .unwrap()
-                            )?
-                        }
-                    } else { // the type of this field cannot be determined, it's probably an array of primitives
-                        write!(f, "{}    {}: {}* = ", ident_str, field_name, field.type_name)?;
-                        let ptr_inst = inst.get_ptr(field);
-
-                        match ptr_inst {
-                            PointerInfo::Block(block) => {
-                                match block {
-                                    Block::Principal { data, .. }
-                                    | Block::Subsidiary { data, .. } => {
-                                        writeln!(f, "{:?};", data.data)?
-                                    }
-                                    _ => (),
-                                }
-                            },
-                            PointerInfo::Invalid => writeln!(f, "invalid;")?,
-                            PointerInfo::Null => writeln!(f, "null;")?,
-                        }
-                    }
-                } else {
-                    writeln!(
-                        f,
-                        "{}    {}: {} = null;",
-                        ident_str, field_name, field.type_name
-                    )?;
-                }
-            }
-            FieldInfo::Pointer {
-                indirection_count: 2,
-            } => {
-                if inst.is_valid(field_name) {
-                    let mut instances = inst.get_iter(field_name);
-                    write!(
-                        f,
-                        "{}    {}: {}[%?] = [",
-                        ident_str,
-                        field_name,
-                        field.type_name,
-                        //instances.len(),
-                    )?;
-                    if let Some(instance) = instances.next() {
-                        write!(f, "{}", instance)?;
-                    }
-                    writeln!(f, "];")?;
-                } else {
-                    writeln!(
-                        f,
-                        "{}    {}: {}[] = null;",
-                        ident_str, field_name, field.type_name
-                    )?;
-                }
-            }
-            FieldInfo::FnPointer => writeln!(f, "{}    {}: fn() = null", ident_str, field_name)?,
-            FieldInfo::PointerArray { dimensions, .. } => {
-                let mut instances = inst.get_iter(field_name);
-                writeln!(
-                    f,
-                    "{}    {}: {}{:?} = [",
-                    ident_str, field_name, field.type_name, dimensions,
-                )?;
-
-                if let Some(instance) = instances.next() {
-                    if instance.data.code().is_none()
-                        && !seen_addresses.contains(&instance.memory_address())
-                    {
-                        seen_addresses.insert(instance.memory_address());
-                        write!(f, "{}        ", ident_str)?;
-                        if instance.type_name == "Link" {
-                            writeln!(f, "(not enough type information);")?
-                        } else {
-                            fmt_instance(seen_addresses, f, &instance, ident + 2)?;
-                        }
-                    } else {
-                        write!(f, "{}        @{}", ident_str, instance.memory_address())?;
-                    }
-                }
-                writeln!(f, "{}    ];", ident_str)?;
-            }
-            _ => unimplemented!("unknown type"),
-        }
-    }
-
-    writeln!(f, "{}}}", ident_str)
-}
-
 impl fmt::Display for Instance<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt_instance(&mut std::collections::HashSet::new(), f, self, 0)
+        Dumper::default().dump(self, f)
     }
 }
 
@@ -487,6 +139,138 @@ fn parse_ptr_address(
 }
 
 impl<'a> Instance<'a> {
+    /// Creates an `Instance` of the struct described by `layout` over the data of `block`.
+    fn new(blend: &'a RawBlend, block: &'a Block, layout: Arc<StructLayout>) -> Self {
+        Instance {
+            dna: &blend.dna,
+            blend,
+            block,
+            libraries: None,
+            path: InstancePath::default(),
+            type_name: &blend.dna.types[layout.type_index].name,
+            data: InstanceDataFormat::Block(block),
+            layout,
+        }
+    }
+
+    /// Creates an `Instance` of the struct described by `layout` over `data`, which is a part of the data of `block`.
+    fn new_raw(
+        blend: &'a RawBlend,
+        block: &'a Block,
+        data: &'a [u8],
+        layout: Arc<StructLayout>,
+    ) -> Self {
+        Instance {
+            dna: &blend.dna,
+            blend,
+            block,
+            libraries: None,
+            path: InstancePath::default(),
+            type_name: &blend.dna.types[layout.type_index].name,
+            data: InstanceDataFormat::Raw(data),
+            layout,
+        }
+    }
+
+    fn with_path(mut self, path: InstancePath<'a>) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Starts the path of an `Instance` created directly from a block, like the ones returned by `Blend`.
+    fn with_root_path(self) -> Self {
+        let path = match self.block {
+            Block::Principal { code, .. } => {
+                InstancePath::block(*code, self.type_name, self.blend, self.block)
+            }
+            Block::Subsidiary { memory_address, .. } => {
+                InstancePath::address(self.type_name, *memory_address)
+            }
+            Block::Global { .. } => InstancePath::code(*b"GLOB", self.type_name),
+            _ => InstancePath::default(),
+        };
+
+        self.with_path(path)
+    }
+
+    /// Returns the fields and list indices followed to reach this `Instance` from the block it was read from, like
+    /// `OB"Cube".data<Mesh>.mloop[12]`. Pointer fields are followed by the type of the struct they point to. The path
+    /// is also part of the errors returned by the `try_[]` methods and of the panic messages of the others.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let obj = blend
+    ///     .instances_with_code(*b"OB")
+    ///     .find(|obj| obj.get("id").get_string("name") == "OBCube")
+    ///     .unwrap();
+    /// let material = obj.get("data").get_iter("mat").next().unwrap();
+    ///
+    /// assert_eq!(material.path(), "OB\"Cube\".data<Mesh>.mat[0]");
+    /// # assert_eq!(
+    /// #     material.try_get_f32("no_such_field").unwrap_err().to_string(),
+    /// #     "struct Material has no field 'no_such_field' at 'OB\"Cube\".data<Mesh>.mat[0]'"
+    /// # );
+    /// # }
+    /// ```
+    pub fn path(&self) -> String {
+        self.path.to_string()
+    }
+
+    /// The path of the field `name` of this `Instance`, for error messages.
+    fn field_path(&self, name: &str) -> String {
+        self.path.field(name, None).to_string()
+    }
+
+    /// Creates a `WrongType` error for the field `name`, described by `field`.
+    fn wrong_type(
+        &self,
+        name: &str,
+        field: &FieldTemplate,
+        expected: impl Into<String>,
+    ) -> AccessError {
+        AccessError::wrong_type(self.field_path(name), name, field, expected)
+    }
+
+    /// Returns the layout of the struct at `struct_index`, or an `InvalidStruct` error for the field `name` if the
+    /// index, usually read from a block header, is corrupt.
+    fn try_layout(
+        &self,
+        name: &str,
+        struct_index: usize,
+    ) -> Result<Arc<StructLayout>, AccessError> {
+        self.blend
+            .try_struct_layout(struct_index)
+            .map_err(|error| AccessError::InvalidStruct {
+                field: self.field_path(name),
+                error,
+            })
+    }
+
+    /// Makes this `Instance`, and every instance accessed through it, follow links with `libraries`.
+    fn with_libraries(mut self, libraries: Option<&'a BlendLibrarySet>) -> Self {
+        self.libraries = libraries;
+        self
+    }
+
+    /// Replaces this `Instance` with the datablock it stands for if it is the placeholder of a linked datablock and it
+    /// has libraries. Other instances are returned unchanged. `path` returns the path of the placeholder for the
+    /// error returned when the datablock can't be found.
+    fn try_follow_link(self, path: impl FnOnce() -> String) -> Result<Self, AccessError> {
+        match self.libraries {
+            Some(libraries) if library::is_placeholder(&self) => libraries
+                .resolve(&self)
+                .ok_or_else(|| AccessError::MissingLinkedData {
+                    field: path(),
+                    name: self.try_get_string("name").unwrap_or_default(),
+                }),
+            _ => Ok(self),
+        }
+    }
+
     pub fn dna(&self) -> &Dna {
         self.dna
     }
@@ -500,7 +284,9 @@ impl<'a> Instance<'a> {
     /// # Panics
     /// Panics if the instance's underlying data doesn't have a code
     pub fn code(&self) -> [u8; 4] {
-        self.data.code().expect("instance doesn't have a code")
+        self.data
+            .code()
+            .unwrap_or_else(|| panic!("instance '{}' doesn't have a code", self.path))
     }
 
     /// If this `Instance` was created from a primary/root or subsidiary `Block` it will have a memory address. Blender
@@ -511,16 +297,32 @@ impl<'a> Instance<'a> {
     pub fn memory_address(&self) -> NonZeroU64 {
         self.data
             .memory_address()
-            .expect("instance doesn't have memory address")
+            .unwrap_or_else(|| panic!("instance '{}' doesn't have memory address", self.path))
     }
 
-    /// `expect_field` simplifies accessing a field since most of the time panicking is the correct response for an
-    /// invalid field name.
-    fn expect_field(&self, name: &str) -> &FieldTemplate {
-        match &self.fields.get(name) {
-            Some(field) => field,
-            None => panic!("invalid field '{}'", name),
-        }
+    /// Returns the field called `name`, or one of its aliases if this struct has no field called `name`. See
+    /// `RawBlend::aliases`.
+    fn find_field(&self, name: &str) -> Option<&FieldTemplate> {
+        find_field(self.blend, &self.layout, name)
+    }
+
+    /// Same as `find_field`, but returns a `NoSuchField` error when the field is not found.
+    fn try_field(&self, name: &str) -> Result<&FieldTemplate, AccessError> {
+        self.find_field(name)
+            .ok_or_else(|| AccessError::NoSuchField {
+                struct_name: String::from(self.type_name),
+                name: String::from(name),
+                path: Some(self.path()),
+            })
+    }
+
+    /// Returns the data of `field`, or a `TruncatedData` error if the data of this instance is too short to hold it.
+    fn try_field_data(&self, name: &str, field: &FieldTemplate) -> Result<&'a [u8], AccessError> {
+        self.data
+            .get(field.data_start, field.data_len)
+            .ok_or_else(|| AccessError::TruncatedData {
+                field: self.field_path(name),
+            })
     }
 
     fn parse_ptr_address(&self, data: &[u8]) -> Option<NonZeroU64> {
@@ -545,424 +347,647 @@ impl<'a> Instance<'a> {
             ),
         }
 
-        if self.data.data().len() < field.data_start + field.data_len {
-            return PointerInfo::Invalid;
-        }
-
-        let address = self.parse_ptr_address(self.data.get(field.data_start, field.data_len));
+        let address = match self.data.get(field.data_start, field.data_len) {
+            Some(data) => self.parse_ptr_address(data),
+            None => return PointerInfo::Invalid,
+        };
 
         match address {
             None => PointerInfo::Null,
-            Some(address) => {
-                match self.blend.blocks.iter().find(|b| match b {
-                    Block::Principal { memory_address, .. }
-                    | Block::Subsidiary { memory_address, .. } => *memory_address == address,
-                    _ => false,
-                }) {
-                    Some(block) => PointerInfo::Block(block),
-                    None => PointerInfo::Invalid,
+            Some(address) => match self.blend.block_at(address) {
+                Some(block) => PointerInfo::Block(block),
+                None => PointerInfo::Invalid,
+            },
+        }
+    }
+
+    /// Returns the block `field` points to, its data and the struct index saved in its header.
+    fn try_pointer(
+        &self,
+        name: &str,
+        field: &FieldTemplate,
+    ) -> Result<(&'a Block, &'a BlockData, usize), AccessError> {
+        self.try_field_data(name, field)?;
+
+        match self.get_ptr(field) {
+            PointerInfo::Block(block) => match block {
+                Block::Principal {
+                    data, dna_index, ..
                 }
-            }
+                | Block::Subsidiary {
+                    data, dna_index, ..
+                } => Ok((block, data, *dna_index)),
+                _ => Err(AccessError::DanglingPointer {
+                    field: self.field_path(name),
+                }),
+            },
+            PointerInfo::Null => Err(AccessError::NullPointer {
+                field: self.field_path(name),
+            }),
+            PointerInfo::Invalid => Err(AccessError::DanglingPointer {
+                field: self.field_path(name),
+            }),
         }
     }
 
     /// Tests whether a field is valid and can be accessed using the `get` methods without panicking. Which `get`
-    /// method you have to use depends on the field type.
+    /// method you have to use depends on the field type. Returns false for fields the struct doesn't have, function
+    /// pointers, pointers that don't lead to a block of data and, in corrupt files, fields whose data is cut short and
+    /// pointers to blocks whose struct index is not in the DNA.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").next().unwrap();
+    /// assert!(obj.is_valid("data"));
+    /// assert!(!obj.is_valid("track"));
+    /// assert!(!obj.is_valid("not_a_field"));
+    /// # }
+    /// ```
     pub fn is_valid<T: AsRef<str>>(&self, name: T) -> bool {
         let name = name.as_ref();
 
-        if !self.fields.contains_key(name) {
-            return false;
-        }
+        let field = match self.find_field(name) {
+            Some(field) => field,
+            None => return false,
+        };
 
-        let field = self.expect_field(name);
+        let pointer_size = self.blend.header.pointer_size.bytes_num();
+        // Blocks of raw data have the struct index 0, which is valid in any DNA
+        let is_readable = |block: &Block| match block {
+            Block::Principal { dna_index, .. } | Block::Subsidiary { dna_index, .. } => {
+                self.blend.try_struct_layout(*dna_index).is_ok()
+            }
+            _ => false,
+        };
+        let is_block = |address: NonZeroU64| self.blend.block_at(address).is_some_and(is_readable);
 
         match field.info {
-            FieldInfo::Pointer { indirection_count } if indirection_count == 1 => {
-                assert_eq!(
-                    field.data_len,
-                    size_of::<u64>(),
-                    "field '{}' doesn't have enough data for a pointer address. ({:?})",
-                    name,
-                    field
-                );
-
-                let pointer = self.get_ptr(field);
-
-                match pointer {
-                    PointerInfo::Null | PointerInfo::Invalid => false,
-                    PointerInfo::Block(block) => match block {
-                        Block::Principal { .. } | Block::Subsidiary { .. } => true,
-                        _ => unimplemented!(),
-                    },
-                }
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } => {
+                field.data_len == pointer_size
+                    && matches!(self.get_ptr(field), PointerInfo::Block(block) if is_readable(block))
             }
-            FieldInfo::Pointer { indirection_count } if indirection_count == 2 => {
-                let pointer = self.get_ptr(field);
-
-                let block = match pointer {
-                    PointerInfo::Block(block) => block,
-                    PointerInfo::Null | PointerInfo::Invalid => return false,
-                };
-
-                let pointer_size = self.blend.header.pointer_size.bytes_num();
-                let pointer_count = match block {
-                    Block::Principal { data, .. } | Block::Subsidiary { data, .. } => {
-                        data.data.len() / pointer_size
-                    }
-                    _ => unimplemented!(),
-                };
+            FieldInfo::Pointer {
+                indirection_count: 2,
+            } => {
+                if field.data_len != pointer_size {
+                    return false;
+                }
 
-                for i in 0..pointer_count {
-                    match block {
-                        Block::Principal { data, .. } | Block::Subsidiary { data, .. } => {
-                            let address = self.parse_ptr_address(&data.data[i * pointer_size..]);
-                            //parse_u64(&block.data[i * ptr_size..], self.blend.header.endianness);
-
-                            match address {
-                                Some(address) => {
-                                    if !self.blend.blocks.iter().any(|b| match b {
-                                        Block::Principal { memory_address, .. }
-                                        | Block::Subsidiary { memory_address, .. } => {
-                                            *memory_address == address
-                                        }
-                                        _ => false,
-                                    }) {
-                                        return false;
-                                    } else {
-                                        continue;
-                                    }
-                                }
-                                None => return false,
-                            }
-                        }
-                        _ => unimplemented!(),
-                    }
+                match self.get_ptr(field) {
+                    PointerInfo::Block(
+                        Block::Principal { data, .. } | Block::Subsidiary { data, .. },
+                    ) => data
+                        .data
+                        .chunks_exact(pointer_size)
+                        .all(|pointer| self.parse_ptr_address(pointer).is_some_and(is_block)),
+                    _ => false,
                 }
-                true
             }
-            FieldInfo::FnPointer => false,
-            FieldInfo::PointerArray { .. } => unimplemented!(), //todo: fix
-            FieldInfo::Value => {
-                if field.type_name == "ListBase" {
-                    let instance = self.get(name);
-                    instance.is_valid("first") && instance.is_valid("last")
-                } else {
-                    true
-                }
+            // Null pointers are skipped by `get_iter`
+            FieldInfo::PointerArray {
+                indirection_count: 1,
+                ..
+            } => self.try_field_data(name, field).is_ok_and(|data| {
+                data.chunks_exact(pointer_size)
+                    .all(|pointer| self.parse_ptr_address(pointer).is_none_or(is_block))
+            }),
+            FieldInfo::Value if field.type_name == "ListBase" => match self.try_get(name) {
+                Ok(instance) => instance.is_valid("first") && instance.is_valid("last"),
+                Err(_) => false,
+            },
+            FieldInfo::Value | FieldInfo::ValueArray { .. } => {
+                self.try_field_data(name, field).is_ok()
             }
-            FieldInfo::ValueArray { .. } => true,
-            _ => panic!(
-                "is_valid called for unknown field '{}'. ({:?})",
-                name, field,
-            ),
+            _ => false,
         }
     }
 
-    /// `get_value` abstracts accessing primitives and is used by all `get_[]` functions (`get_i8`, `get_f32`, etc).
-    fn get_value<T: AsRef<str>, U: BlendPrimitive>(&self, name: T) -> U {
-        let name = name.as_ref();
-        let field = self.expect_field(name);
+    /// Returns an error if the DNA type of `field` can't be read as `U`.
+    fn check_type<U: BlendPrimitive>(
+        &self,
+        name: &str,
+        field: &FieldTemplate,
+    ) -> Result<(), AccessError> {
+        if U::blender_names().contains(&&field.type_name[..]) {
+            Ok(())
+        } else {
+            Err(self.wrong_type(name, field, std::any::type_name::<U>()))
+        }
+    }
 
-        let blender_type_name = U::blender_name();
+    /// Reads a primitive field as any Rust type of the same size. Used by all `get_[]` functions (`get_i8`,
+    /// `get_f32`, etc).
+    fn try_read_value<U: BlendPrimitive>(&self, name: &str) -> Result<U, AccessError> {
+        let field = self.try_field(name)?;
 
         match field.info {
-            FieldInfo::Value if field.is_primitive /*&& field.type_name == blender_type_name*/ => {
-                assert_eq!(
-                    field.data_len,
-                    size_of::<U>(),
-                    "field '{}' doesn't have enough data for a {}. ({:?})",
-                    name,
-                    blender_type_name,
-                    field,
-                );
-
-                U::parse(
-                    self.data.get(field.data_start, field.data_len),
-                    self.blend.header.endianness,
-                )
-            }
-            _ => panic!(
-                "field '{}' is not {}. ({:?})",
-                name, blender_type_name, field
-            ),
+            FieldInfo::Value if field.is_primitive && field.data_len == U::SIZE => Ok(U::parse(
+                self.try_field_data(name, field)?,
+                self.blend.header.endianness,
+            )),
+            _ => Err(self.wrong_type(name, field, std::any::type_name::<U>())),
         }
     }
 
     pub fn get_u8<T: AsRef<str>>(&self, name: T) -> u8 {
-        self.get_value(name)
+        expect(self.try_get_u8(name))
     }
 
-    pub fn get_i8<T: AsRef<str>>(&self, name: T) -> i8 {
-        self.get_value(name)
+    pub fn try_get_u8<T: AsRef<str>>(&self, name: T) -> Result<u8, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_char<T: AsRef<str>>(&self, name: T) -> char {
-        self.get_u8(name) as char
+    pub fn get_i8<T: AsRef<str>>(&self, name: T) -> i8 {
+        expect(self.try_get_i8(name))
     }
 
-    pub fn get_u16<T: AsRef<str>>(&self, name: T) -> u16 {
-        self.get_value(name)
+    pub fn try_get_i8<T: AsRef<str>>(&self, name: T) -> Result<i8, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_i16<T: AsRef<str>>(&self, name: T) -> i16 {
-        self.get_value(name)
+    pub fn get_char<T: AsRef<str>>(&self, name: T) -> char {
+        expect(self.try_get_char(name))
     }
 
-    pub fn get_i32<T: AsRef<str>>(&self, name: T) -> i32 {
-        self.get_value(name)
+    pub fn try_get_char<T: AsRef<str>>(&self, name: T) -> Result<char, AccessError> {
+        self.try_get_u8(name).map(char::from)
     }
 
-    pub fn get_u32<T: AsRef<str>>(&self, name: T) -> u32 {
-        self.get_value(name)
+    pub fn get_u16<T: AsRef<str>>(&self, name: T) -> u16 {
+        expect(self.try_get_u16(name))
     }
 
-    pub fn get_f32<T: AsRef<str>>(&self, name: T) -> f32 {
-        self.get_value(name)
+    pub fn try_get_u16<T: AsRef<str>>(&self, name: T) -> Result<u16, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_f64<T: AsRef<str>>(&self, name: T) -> f64 {
-        self.get_value(name)
+    pub fn get_i16<T: AsRef<str>>(&self, name: T) -> i16 {
+        expect(self.try_get_i16(name))
     }
 
-    pub fn get_u64<T: AsRef<str>>(&self, name: T) -> u64 {
-        self.get_value(name)
+    pub fn try_get_i16<T: AsRef<str>>(&self, name: T) -> Result<i16, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_i64<T: AsRef<str>>(&self, name: T) -> i64 {
-        self.get_value(name)
+    pub fn get_i32<T: AsRef<str>>(&self, name: T) -> i32 {
+        expect(self.try_get_i32(name))
     }
 
-    /// `get_value_vec` abstracts accessing primitive arrays and is used by all `get_[]_vec` functions (`get_i8_vec`,
-    /// `get_f32_vec`, etc).
-    fn get_value_vec<T: AsRef<str>, U: BlendPrimitive>(&self, name: T) -> Vec<U> {
-        let name = name.as_ref();
-        let field = self.expect_field(name);
-        let blender_type_name = U::blender_name();
-
-        let data = match field.info {
-            FieldInfo::ValueArray { len, .. } if field.is_primitive => {
-                assert_eq!(
-                    field.data_len / len,
-                    size_of::<U>(),
-                    "field '{}' doesn't have enough data for a {} array. ({:?})",
-                    name,
-                    blender_type_name,
-                    field,
-                );
-
-                self.data.get(field.data_start, field.data_len)
-            }
-            FieldInfo::Pointer { indirection_count } if indirection_count == 1 => {
-                let pointer = self.get_ptr(field);
-                let block = match pointer {
-                    PointerInfo::Block(block) => block,
-                    PointerInfo::Null | PointerInfo::Invalid => panic!(
-                        "field '{}' is a null or invalid pointer. ({:?})",
-                        name, field
-                    ),
-                };
-
-                let size = size_of::<U>();
-                match block {
-                    Block::Principal { data, .. } | Block::Subsidiary { data, .. } => {
-                        assert!(data.data.len() % size == 0);
+    pub fn try_get_i32<T: AsRef<str>>(&self, name: T) -> Result<i32, AccessError> {
+        self.try_read_value(name.as_ref())
+    }
 
-                        &data.data[..]
-                    }
-                    _ => unimplemented!(),
-                }
-            }
-            _ => panic!(
-                "field '{}' is not a {} array. ({:?})",
-                name, blender_type_name, field
-            ),
-        };
+    pub fn get_u32<T: AsRef<str>>(&self, name: T) -> u32 {
+        expect(self.try_get_u32(name))
+    }
 
-        data.chunks(size_of::<U>())
-            .map(|s| U::parse(s, self.blend.header.endianness))
-            .collect()
+    pub fn try_get_u32<T: AsRef<str>>(&self, name: T) -> Result<u32, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_u8_vec<T: AsRef<str>>(&self, name: T) -> Vec<u8> {
-        self.get_value_vec(name)
+    pub fn get_f32<T: AsRef<str>>(&self, name: T) -> f32 {
+        expect(self.try_get_f32(name))
     }
 
-    pub fn get_i8_vec<T: AsRef<str>>(&self, name: T) -> Vec<i8> {
-        self.get_value_vec(name)
+    pub fn try_get_f32<T: AsRef<str>>(&self, name: T) -> Result<f32, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_i32_vec<T: AsRef<str>>(&self, name: T) -> Vec<i32> {
-        self.get_value_vec(name)
+    pub fn get_f64<T: AsRef<str>>(&self, name: T) -> f64 {
+        expect(self.try_get_f64(name))
     }
 
-    pub fn get_u32_vec<T: AsRef<str>>(&self, name: T) -> Vec<u32> {
-        self.get_value_vec(name)
+    pub fn try_get_f64<T: AsRef<str>>(&self, name: T) -> Result<f64, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_i16_vec<T: AsRef<str>>(&self, name: T) -> Vec<i16> {
-        self.get_value_vec(name)
+    pub fn get_u64<T: AsRef<str>>(&self, name: T) -> u64 {
+        expect(self.try_get_u64(name))
     }
 
-    pub fn get_u16_vec<T: AsRef<str>>(&self, name: T) -> Vec<u16> {
-        self.get_value_vec(name)
+    pub fn try_get_u64<T: AsRef<str>>(&self, name: T) -> Result<u64, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_f32_vec<T: AsRef<str>>(&self, name: T) -> Vec<f32> {
-        self.get_value_vec(name)
+    pub fn get_i64<T: AsRef<str>>(&self, name: T) -> i64 {
+        expect(self.try_get_i64(name))
     }
 
-    pub fn get_f64_vec<T: AsRef<str>>(&self, name: T) -> Vec<f64> {
-        self.get_value_vec(name)
+    pub fn try_get_i64<T: AsRef<str>>(&self, name: T) -> Result<i64, AccessError> {
+        self.try_read_value(name.as_ref())
     }
 
-    pub fn get_u64_vec<T: AsRef<str>>(&self, name: T) -> Vec<u64> {
-        self.get_value_vec(name)
+    /// Reads a primitive array, or a block of primitives behind a pointer, as any Rust type of the same size. Used by
+    /// all `get_[]_vec` functions (`get_i8_vec`, `get_f32_vec`, etc).
+    fn try_read_value_vec<U: BlendPrimitive>(&self, name: &str) -> Result<Vec<U>, AccessError> {
+        let field = self.try_field(name)?;
+        let data = self.try_primitives_data(name, field, U::SIZE, || {
+            format!("an array of {}", std::any::type_name::<U>())
+        })?;
+
+        Ok(data
+            .chunks(U::SIZE)
+            .map(|s| U::parse(s, self.blend.header.endianness))
+            .collect())
     }
 
-    pub fn get_i64_vec<T: AsRef<str>>(&self, name: T) -> Vec<i64> {
-        self.get_value_vec(name)
+    /// Returns the data of a primitive array, or of the block behind a pointer, checking that it is made of elements
+    /// `element_len` bytes long.
+    fn try_primitives_data(
+        &self,
+        name: &str,
+        field: &FieldTemplate,
+        element_len: usize,
+        expected: impl Fn() -> String,
+    ) -> Result<&'a [u8], AccessError> {
+        match field.info {
+            FieldInfo::ValueArray { len, .. }
+                if field.is_primitive && field.data_len == len * element_len =>
+            {
+                self.try_field_data(name, field)
+            }
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } => {
+                let (_, data, _) = self.try_pointer(name, field)?;
+
+                if data.data.len() % element_len != 0 {
+                    return Err(self.wrong_type(name, field, expected()));
+                }
+
+                Ok(&data.data[..])
+            }
+            _ => Err(self.wrong_type(name, field, expected())),
+        }
     }
 
+    /// Reads a primitive field as `U`, checking that the DNA type of the field is one `U` can be read from (see
+    /// `BlendPrimitive::blender_names`). Unlike the `get_[]` functions, an `int` can't be read as a `u32` here.
+    ///
     /// ## Example
     ///
     /// ```rust
     /// # use blend::Blend;
     /// # fn main() {
     ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
-    /// let obj = blend.instances_with_code(*b"OB").next().unwrap();
-    /// let name = obj.get("id").get_string("name");
+    /// let camera = blend.instances_with_code(*b"CA").next().unwrap();
+    /// let lens: f32 = camera.get_value("lens");
+    /// let kind = camera.get_value::<i8>("type");
+    /// # assert_eq!(lens, camera.get_f32("lens"));
+    /// # assert!(std::panic::catch_unwind(|| camera.get_value::<u16>("lens")).is_err());
     /// # }
     /// ```
     ///
     /// ## Panics
     ///
-    /// * Panics if the field cannot be read as a string.
-    pub fn get_string<T: AsRef<str>>(&self, name: T) -> String {
-        let name = name.as_ref();
-        let field = self.expect_field(name);
-
-        match field.info {
-            FieldInfo::Value | FieldInfo::ValueArray { .. } => {
-                if !field.is_primitive || field.type_name != "char" {
-                    panic!(
-                        "field '{}' is not a primitive or has the wrong type. ({:?})",
-                        name, field
-                    )
-                }
-
-                let data = &self.data.get(field.data_start, field.data_len);
-                data.iter()
-                    .take_while(|c| **c != 0)
-                    .map(|c| *c as char)
-                    .collect()
-            }
-            _ => panic!("field '{}' is not a string. ({:?})", name, field),
-        }
+    /// * Panics if the field is not a primitive that can be read as `U`.
+    pub fn get_value<U: BlendPrimitive>(&self, name: impl AsRef<str>) -> U {
+        expect(self.try_get_value(name))
     }
 
-    /// Used to access fields which are structs.
+    /// Same as `get_value`, but returns an error instead of panicking.
     ///
     /// ## Example
     ///
     /// ```rust
-    /// # use blend::Blend;
+    /// # use blend::{Blend, runtime::AccessError};
     /// # fn main() {
     ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
-    /// let obj = blend.instances_with_code(*b"OB").next().expect("no object found");
-    /// let id = obj.get("id");
-    /// let data = obj.get("data");
+    /// let camera = blend.instances_with_code(*b"CA").next().unwrap();
+    /// assert!(camera.try_get_value::<f32>("lens").is_ok());
+    ///
+    /// match camera.try_get_value::<u16>("lens") {
+    ///     Err(AccessError::WrongType { expected, found, .. }) => println!("{} is not a {}", found, expected),
+    ///     _ => unreachable!(),
+    /// }
+    /// # assert!(matches!(camera.try_get_value::<f32>("focal_length"), Err(AccessError::NoSuchField { .. })));
     /// # }
     /// ```
-    ///
-    /// ## Panics
-    ///
-    /// * Panics if the field is not a valid struct.
-    pub fn get<T: AsRef<str>>(&self, name: T) -> Instance<'a> {
+    pub fn try_get_value<U: BlendPrimitive>(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<U, AccessError> {
         let name = name.as_ref();
-        let field = self.expect_field(name);
+        self.check_type::<U>(name, self.try_field(name)?)?;
+        self.try_read_value(name)
+    }
 
-        match field.info {
-            FieldInfo::Value => {
-                if field.is_primitive {
-                    panic!("cannot access field '{}' as a struct. ({:?})", name, field,)
-                }
+    /// Reads a primitive array, or a block of primitives behind a typed pointer, as a `Vec<U>`. Like `get_value` it
+    /// checks that the DNA type of the field is one `U` can be read from.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").next().unwrap();
+    /// let loc = obj.get_vec::<f32>("loc");
+    /// # assert_eq!(loc, obj.get_f32_vec("loc"));
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if the field is not an array or pointer of a primitive that can be read as `U`.
+    pub fn get_vec<U: BlendPrimitive>(&self, name: impl AsRef<str>) -> Vec<U> {
+        expect(self.try_get_vec(name))
+    }
+
+    /// Same as `get_vec`, but returns an error instead of panicking.
+    pub fn try_get_vec<U: BlendPrimitive>(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<U>, AccessError> {
+        let name = name.as_ref();
+        self.check_type::<U>(name, self.try_field(name)?)?;
+        self.try_read_value_vec(name)
+    }
+
+    /// Reads a primitive field as the Rust type matching its DNA type (see `PrimitiveKind::from_field`). Useful for code
+    /// that handles fields of any primitive type, like printing or serializing them.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::{Blend, parsers::primitive::PrimitiveValue};
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let camera = blend.instances_with_code(*b"CA").next().unwrap();
+    /// assert_eq!(camera.get_primitive("lens"), PrimitiveValue::F32(camera.get_f32("lens")));
+    /// assert_eq!(format!("{:?}", camera.get_primitive("type")), format!("{:?}", camera.get_i8("type")));
+    /// # assert!(camera.try_get_primitive("id").is_err());
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if the field is not a primitive.
+    pub fn get_primitive(&self, name: impl AsRef<str>) -> PrimitiveValue {
+        expect(self.try_get_primitive(name))
+    }
+
+    /// Same as `get_primitive`, but returns an error instead of panicking.
+    pub fn try_get_primitive(&self, name: impl AsRef<str>) -> Result<PrimitiveValue, AccessError> {
+        let name = name.as_ref();
+        let field = self.try_field(name)?;
+
+        match (
+            &field.info,
+            PrimitiveKind::from_field(field, self.blend.header.pointer_size),
+        ) {
+            (FieldInfo::Value, Some(kind)) if field.data_len == kind.size() => Ok(kind.parse(
+                self.try_field_data(name, field)?,
+                self.blend.header.endianness,
+            )),
+            _ => Err(self.wrong_type(name, field, "a primitive")),
+        }
+    }
+
+    /// Reads a primitive array, or a block of primitives behind a typed pointer, as the Rust type matching its DNA
+    /// type. See `get_primitive`.
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if the field is not an array or pointer of a primitive other than `void`.
+    pub fn get_primitives(&self, name: impl AsRef<str>) -> Vec<PrimitiveValue> {
+        expect(self.try_get_primitives(name))
+    }
+
+    /// Same as `get_primitives`, but returns an error instead of panicking.
+    pub fn try_get_primitives(
+        &self,
+        name: impl AsRef<str>,
+    ) -> Result<Vec<PrimitiveValue>, AccessError> {
+        let name = name.as_ref();
+        let field = self.try_field(name)?;
+        let expected = || String::from("an array of primitives");
+        let kind = PrimitiveKind::from_field(field, self.blend.header.pointer_size)
+            .ok_or_else(|| self.wrong_type(name, field, expected()))?;
+        let data = self.try_primitives_data(name, field, kind.size(), expected)?;
+
+        Ok(data
+            .chunks(kind.size())
+            .map(|s| kind.parse(s, self.blend.header.endianness))
+            .collect())
+    }
+
+    pub fn get_u8_vec<T: AsRef<str>>(&self, name: T) -> Vec<u8> {
+        expect(self.try_get_u8_vec(name))
+    }
+
+    pub fn try_get_u8_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<u8>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_i8_vec<T: AsRef<str>>(&self, name: T) -> Vec<i8> {
+        expect(self.try_get_i8_vec(name))
+    }
+
+    pub fn try_get_i8_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<i8>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_i32_vec<T: AsRef<str>>(&self, name: T) -> Vec<i32> {
+        expect(self.try_get_i32_vec(name))
+    }
+
+    pub fn try_get_i32_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<i32>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_u32_vec<T: AsRef<str>>(&self, name: T) -> Vec<u32> {
+        expect(self.try_get_u32_vec(name))
+    }
+
+    pub fn try_get_u32_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<u32>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_i16_vec<T: AsRef<str>>(&self, name: T) -> Vec<i16> {
+        expect(self.try_get_i16_vec(name))
+    }
+
+    pub fn try_get_i16_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<i16>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_u16_vec<T: AsRef<str>>(&self, name: T) -> Vec<u16> {
+        expect(self.try_get_u16_vec(name))
+    }
+
+    pub fn try_get_u16_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<u16>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_f32_vec<T: AsRef<str>>(&self, name: T) -> Vec<f32> {
+        expect(self.try_get_f32_vec(name))
+    }
+
+    pub fn try_get_f32_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<f32>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_f64_vec<T: AsRef<str>>(&self, name: T) -> Vec<f64> {
+        expect(self.try_get_f64_vec(name))
+    }
+
+    pub fn try_get_f64_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<f64>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_u64_vec<T: AsRef<str>>(&self, name: T) -> Vec<u64> {
+        expect(self.try_get_u64_vec(name))
+    }
+
+    pub fn try_get_u64_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<u64>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    pub fn get_i64_vec<T: AsRef<str>>(&self, name: T) -> Vec<i64> {
+        expect(self.try_get_i64_vec(name))
+    }
+
+    pub fn try_get_i64_vec<T: AsRef<str>>(&self, name: T) -> Result<Vec<i64>, AccessError> {
+        self.try_read_value_vec(name.as_ref())
+    }
+
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").next().unwrap();
+    /// let name = obj.get("id").get_string("name");
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if the field cannot be read as a string.
+    pub fn get_string<T: AsRef<str>>(&self, name: T) -> String {
+        expect(self.try_get_string(name))
+    }
+
+    /// Same as `get_string`, but returns an error instead of panicking.
+    pub fn try_get_string<T: AsRef<str>>(&self, name: T) -> Result<String, AccessError> {
+        let name = name.as_ref();
+        let field = self.try_field(name)?;
 
-                let r#struct = &self
-                    .dna
-                    .structs
+        match field.info {
+            FieldInfo::Value | FieldInfo::ValueArray { .. }
+                if field.is_primitive && field.type_name == "char" =>
+            {
+                Ok(self
+                    .try_field_data(name, field)?
                     .iter()
-                    .find(|s| s.type_index == field.type_index)
-                    .unwrap_or_else(|| {
-                        panic!(
-                            "could not find type information for field '{}'. ({:?})",
-                            name, field
-                        )
-                    });
-                let r#type = &self.dna.types[r#struct.type_index];
+                    .take_while(|c| **c != 0)
+                    .map(|c| *c as char)
+                    .collect())
+            }
+            _ => Err(self.wrong_type(name, field, "a string")),
+        }
+    }
 
-                let fields = generate_fields(r#struct, r#type, self.dna, &self.blend.header);
+    /// Used to access fields which are structs.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").next().expect("no object found");
+    /// let id = obj.get("id");
+    /// let data = obj.get("data");
+    /// # }
+    /// ```
+    ///
+    /// Pointers whose type is not a struct, like the `void *data` of custom data layers, are read with the struct saved
+    /// in the header of the block they point to. Blocks of raw data, like arrays of floats, are saved with the first
+    /// struct of the DNA (`Link`) in their header and are read as one, read them with the `get_[]_vec` methods instead.
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if the field is not a valid struct.
+    /// * Panics if the field points to a linked datablock which can't be found in its library. Only instances created
+    ///   through a `BlendLibrarySet` follow links.
+    pub fn get<T: AsRef<str>>(&self, name: T) -> Instance<'a> {
+        expect(self.try_get(name))
+    }
 
-                Instance {
-                    dna: self.dna,
-                    blend: self.blend,
-                    type_name: r#type.name.clone(),
-                    data: InstanceDataFormat::Raw(self.data.get(field.data_start, field.data_len)),
-                    fields,
-                }
+    /// Same as `get`, but returns an error instead of panicking.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::{Blend, runtime::AccessError};
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").next().expect("no object found");
+    ///
+    /// match obj.try_get("parent") {
+    ///     Ok(parent) => println!("parent: {}", parent.get("id").get_string("name")),
+    ///     Err(AccessError::NullPointer { .. }) => println!("no parent"),
+    ///     Err(err) => println!("{}", err),
+    /// }
+    /// # assert!(matches!(obj.try_get("loc"), Err(AccessError::WrongType { .. })));
+    /// # }
+    /// ```
+    pub fn try_get<T: AsRef<str>>(&self, name: T) -> Result<Instance<'a>, AccessError> {
+        let name = name.as_ref();
+        let field = self.try_field(name)?;
+
+        match field.info {
+            FieldInfo::Value if !field.is_primitive => {
+                let struct_index = self
+                    .blend
+                    .struct_index(field.type_index)
+                    .ok_or_else(|| self.wrong_type(name, field, "a struct"))?;
+
+                Ok(Instance::new_raw(
+                    self.blend,
+                    self.block,
+                    self.try_field_data(name, field)?,
+                    self.try_layout(name, struct_index)?,
+                )
+                .with_libraries(self.libraries)
+                .with_path(self.path.field(name, None)))
             }
-            FieldInfo::Pointer { indirection_count } if indirection_count == 1 => {
-                let pointer = self.get_ptr(field);
-                let block = match pointer {
-                    PointerInfo::Block(block) => block,
-                    PointerInfo::Null | PointerInfo::Invalid => panic!(
-                        "field '{}' is null or doesn't point to a valid block. ({:?})",
-                        name, field
-                    ),
-                };
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } => {
+                let (block, data, dna_index) = self.try_pointer(name, field)?;
 
-                let (fields, r#type) = match block {
-                    Block::Principal {
-                        data, dna_index, ..
-                    } => {
-                        assert!(
-                            data.count == 1,
-                            "field '{}' is a list of structs, use get_instances to access. ({:?})",
-                            name,
-                            field
-                        );
-
-                        let r#struct = &self.dna.structs[*dna_index];
-                        let r#type = &self.dna.types[r#struct.type_index];
-
-                        (
-                            generate_fields(r#struct, r#type, self.dna, &self.blend.header),
-                            r#type,
-                        )
-                    }
-                    Block::Subsidiary { dna_index, .. } => {
-                        if let Some(v) = generate_subsidiary_fields(
-                            self.dna,
-                            &self.blend.header,
-                            field,
-                            *dna_index,
-                        ) {
-                            v
-                        } else {
-                            panic!("field '{}' is a pointer to a list of primitives", name);
-                        }
+                let layout = match block {
+                    // Lists of structs have to be accessed with `get_iter`
+                    Block::Principal { .. } if data.count != 1 => {
+                        return Err(self.wrong_type(name, field, "a single struct"))
                     }
-                    _ => unimplemented!(),
+                    Block::Principal { .. } => self.try_layout(name, dna_index)?,
+                    _ => subsidiary_layout(self.blend, field, dna_index)
+                        .map_err(|error| AccessError::InvalidStruct {
+                            field: self.field_path(name),
+                            error,
+                        })?
+                        .ok_or_else(|| self.wrong_type(name, field, "a struct"))?,
                 };
-                Instance {
-                    dna: self.dna,
-                    blend: self.blend,
-                    type_name: r#type.name.clone(),
-                    data: InstanceDataFormat::Block(block),
-                    fields,
-                }
+
+                let instance = Instance::new(self.blend, block, layout)
+                    .with_libraries(self.libraries)
+                    .try_follow_link(|| self.field_path(name))?;
+                let path = self.path.field(name, Some(instance.type_name));
+
+                Ok(instance.with_path(path))
             }
-            _ => panic!("field '{}' is not a valid struct ({:?})", name, field),
+            _ => Err(self.wrong_type(name, field, "a struct")),
         }
     }
 
@@ -982,354 +1007,352 @@ impl<'a> Instance<'a> {
     /// ## Panics
     ///
     /// * Panics if the field is not a valid list of structs.
+    /// * Panics while iterating if an element of the list can't be read.
     pub fn get_iter<T: AsRef<str>>(&self, name: T) -> impl Iterator<Item = Instance<'a>> {
+        expect(self.try_get_iter(name)).map(expect)
+    }
+
+    /// Same as `get_iter`, but returns an error instead of panicking. Errors found while iterating, like a broken link
+    /// in a `ListBase`, are returned by the iterator, which ends after them. An empty `ListBase` is an empty iterator.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::{Blend, runtime::AccessError};
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").skip(1).next().unwrap();
+    /// let materials = obj
+    ///     .try_get_iter("mat")
+    ///     .and_then(|materials| materials.collect::<Result<Vec<_>, _>>());
+    /// # assert!(materials.is_ok());
+    /// # assert!(matches!(obj.try_get_iter("totcol"), Err(AccessError::NotAList { .. })));
+    /// # }
+    /// ```
+    pub fn try_get_iter<T: AsRef<str>>(
+        &self,
+        name: T,
+    ) -> Result<impl Iterator<Item = Result<Instance<'a>, AccessError>>, AccessError> {
         let name = name.as_ref();
-        let field = self.expect_field(name);
+        let field = self.try_field(name)?;
+        let libraries = self.libraries;
 
         enum InstanceIterator<'b> {
             ListBase {
-                last_address: NonZeroU64,
-                cur: Instance<'b>,
-                ended: bool,
+                list: Instance<'b>,
+                last_address: Option<NonZeroU64>,
+                next: Option<Result<Instance<'b>, AccessError>>,
+                /// The addresses of the elements returned so far, to stop at a loop in a corrupt list.
+                visited: HashSet<NonZeroU64>,
             },
             ValueArray {
-                dna: &'b Dna,
                 blend: &'b RawBlend,
-                fields: LinkedHashMap<String, FieldTemplate>,
+                block: &'b Block,
+                layout: Arc<StructLayout>,
                 data: &'b [u8],
                 len: usize,
-                type_name: String,
                 cur_index: usize,
             },
             Pointer1 {
-                dna: &'b Dna,
                 blend: &'b RawBlend,
-                fields: LinkedHashMap<String, FieldTemplate>,
+                block: &'b Block,
+                layout: Arc<StructLayout>,
                 data: &'b BlockData,
-                type_name: String,
                 cur_index: usize,
             },
             Pointer2 {
-                dna: &'b Dna,
                 blend: &'b RawBlend,
                 pointers: std::vec::IntoIter<NonZeroU64>,
                 field: FieldTemplate,
+                name: String,
+                /// The path of the instance the field belongs to, only formatted for errors.
+                path: InstancePath<'b>,
             },
         }
 
         impl<'b> Iterator for InstanceIterator<'b> {
-            type Item = Instance<'b>;
+            type Item = Result<Instance<'b>, AccessError>;
             fn next(&mut self) -> Option<Self::Item> {
                 match self {
                     InstanceIterator::ListBase {
+                        ref list,
                         ref last_address,
-                        ref mut cur,
-                        ref mut ended,
+                        ref mut next,
+                        ref mut visited,
                     } => {
-                        if *ended {
-                            return None;
+                        let cur = match next.take()? {
+                            Ok(cur) => cur,
+                            Err(err) => return Some(Err(err)),
+                        };
+
+                        if let Some(address) = cur.data.memory_address() {
+                            if !visited.insert(address) {
+                                return Some(Err(AccessError::CyclicList { field: list.path() }));
+                            }
                         }
 
-                        let ret = cur.clone();
-
-                        if ret.memory_address() == *last_address {
-                            *ended = true;
-                            Some(ret)
-                        } else {
-                            while !cur.is_valid("next") {
-                                *cur = cur.get(cur.fields.keys().next().expect(""));
-                            }
-                            *cur = cur.get("next");
-                            Some(ret)
+                        if cur.data.memory_address() != *last_address {
+                            *next = Some(list_next(&cur));
                         }
+
+                        Some(Ok(cur))
                     }
                     InstanceIterator::ValueArray {
-                        dna,
                         blend,
-                        ref fields,
+                        block,
+                        ref layout,
                         data,
                         ref len,
-                        ref type_name,
                         ref mut cur_index,
                     } => {
-                        let data_len = data.len() / len;
-                        let data_start = *cur_index * data_len;
-
-                        if data_start == data.len() {
+                        if *cur_index == *len {
                             return None;
                         }
 
+                        let data_len = data.len() / *len;
+                        let data_start = *cur_index * data_len;
+
                         *cur_index += 1;
 
-                        Some(Instance {
-                            dna,
+                        Some(Ok(Instance::new_raw(
                             blend,
-                            type_name: type_name.clone(),
-                            data: InstanceDataFormat::Raw(&data[data_start..data_start + data_len]),
-                            fields: fields.clone(),
-                        })
+                            block,
+                            &data[data_start..data_start + data_len],
+                            Arc::clone(layout),
+                        )))
                     }
                     InstanceIterator::Pointer1 {
-                        dna,
                         blend,
-                        ref fields,
+                        block,
+                        ref layout,
                         data,
-                        ref type_name,
                         ref mut cur_index,
                     } => {
-                        let data_len = data.data.len() / data.count;
-                        let data_start = *cur_index * data_len;
-
-                        if data_start == data.data.len() {
+                        if *cur_index == data.count {
                             return None;
                         }
 
+                        let data_len = data.data.len() / data.count;
+                        let data_start = *cur_index * data_len;
+
                         *cur_index += 1;
 
-                        Some(Instance {
-                            dna,
+                        Some(Ok(Instance::new_raw(
                             blend,
-                            type_name: type_name.clone(),
-                            data: InstanceDataFormat::Raw(
-                                &data.data[data_start..data_start + data_len],
-                            ),
-                            fields: fields.clone(),
-                        })
+                            block,
+                            &data.data[data_start..data_start + data_len],
+                            Arc::clone(layout),
+                        )))
                     }
                     InstanceIterator::Pointer2 {
                         blend,
-                        dna,
                         ref mut pointers,
                         ref field,
+                        ref name,
+                        ref path,
                     } => {
-                        for address in pointers {
-                            let block = blend.blocks.iter().find(|b| match b {
-                                Block::Principal { memory_address, .. }
-                                | Block::Subsidiary { memory_address, .. } => {
-                                    *memory_address == address
-                                }
-                                _ => false,
-                            });
-
-                            match block {
-                                Some(Block::Principal { dna_index, .. }) => {
-                                    let r#struct = &dna.structs[*dna_index];
-                                    let r#type = &dna.types[r#struct.type_index];
-
-                                    let fields =
-                                        generate_fields(r#struct, r#type, dna, &blend.header);
-
-                                    return Some(Instance {
-                                        dna,
-                                        blend,
-                                        type_name: r#type.name.clone(),
-                                        data: InstanceDataFormat::Block(
-                                            block.expect("we are sure block is some here"),
-                                        ),
-                                        fields,
-                                    });
-                                }
-                                Some(Block::Subsidiary { dna_index, .. }) => {
-                                    if let Some((fields, r#type)) = generate_subsidiary_fields(
-                                        dna,
-                                        &blend.header,
+                        let address = pointers.next()?;
+                        let field_path = || path.field(name, None).to_string();
+                        let invalid_struct = |error| AccessError::InvalidStruct {
+                            field: field_path(),
+                            error,
+                        };
+
+                        let layout = match blend.block_at(address) {
+                            Some(Block::Principal { dna_index, .. }) => {
+                                blend.try_struct_layout(*dna_index).map_err(invalid_struct)
+                            }
+                            Some(Block::Subsidiary { dna_index, .. }) => {
+                                match subsidiary_layout(blend, field, *dna_index) {
+                                    Ok(Some(layout)) => Ok(layout),
+                                    Ok(None) => Err(AccessError::wrong_type(
+                                        field_path(),
+                                        name,
                                         field,
-                                        *dna_index,
-                                    ) {
-                                        return Some(Instance {
-                                            dna,
-                                            blend,
-                                            type_name: r#type.name.clone(),
-                                            data: InstanceDataFormat::Block(
-                                                block.expect("we are sure block is some here"),
-                                            ),
-                                            fields,
-                                        });
-                                    } else {
-                                        continue;
-                                    }
+                                        "a list of structs",
+                                    )),
+                                    Err(error) => Err(invalid_struct(error)),
                                 }
-                                Some(_) => unimplemented!(),
-                                None => continue,
                             }
-                        }
-                        None
+                            _ => Err(AccessError::DanglingPointer {
+                                field: field_path(),
+                            }),
+                        };
+
+                        Some(layout.map(|layout| {
+                            let block = blend.block_at(address).expect("the block was found above");
+                            Instance::new(blend, block, layout)
+                        }))
                     }
                 }
             }
         }
 
-        match field.info {
+        let instances = match field.info {
             FieldInfo::Value => {
                 if field.type_name != "ListBase" {
-                    panic!("field '{}' cannot be read as a list. ({:?})", name, field)
+                    return Err(AccessError::NotAList {
+                        field: self.field_path(name),
+                    });
                 }
 
-                let list_instance = self.get(name);
-
-                let last_address = list_instance.get("last").memory_address();
-                let cur = list_instance.get("first");
+                let list_instance = self.try_get(name)?;
 
-                InstanceIterator::ListBase {
-                    last_address,
-                    cur,
-                    ended: false,
+                match list_instance.try_get("first") {
+                    Err(AccessError::NullPointer { .. }) => InstanceIterator::ListBase {
+                        list: list_instance,
+                        last_address: None,
+                        next: None,
+                        visited: HashSet::new(),
+                    },
+                    first => InstanceIterator::ListBase {
+                        last_address: list_instance.try_get("last")?.data.memory_address(),
+                        list: list_instance,
+                        next: Some(Ok(first?)),
+                        visited: HashSet::new(),
+                    },
                 }
             }
             FieldInfo::ValueArray { len, .. } => {
                 if field.is_primitive {
-                    panic!(
-                        "field '{}' is a primitive array, call the appropriate method. ({:?})",
-                        name, field
-                    );
+                    return Err(self.wrong_type(name, field, "a list of structs"));
                 }
 
-                if let Some(r#struct) = &self
-                    .dna
-                    .structs
-                    .iter()
-                    .find(|s| s.type_index == field.type_index)
-                {
-                    let r#type = &self.dna.types[r#struct.type_index];
-                    let fields = generate_fields(r#struct, r#type, self.dna, &self.blend.header);
-
-                    let data = self.data.data();
+                let struct_index = self
+                    .blend
+                    .struct_index(field.type_index)
+                    .ok_or_else(|| self.wrong_type(name, field, "a list of structs"))?;
 
-                    InstanceIterator::ValueArray {
-                        dna: self.dna,
-                        blend: self.blend,
-                        fields,
-                        data,
-                        len,
-                        type_name: r#type.name.clone(),
-                        cur_index: 0,
-                    }
-                } else {
-                    unreachable!("no type information found")
+                InstanceIterator::ValueArray {
+                    blend: self.blend,
+                    block: self.block,
+                    layout: self.try_layout(name, struct_index)?,
+                    data: self.try_field_data(name, field)?,
+                    len,
+                    cur_index: 0,
                 }
             }
-            FieldInfo::Pointer { indirection_count } if indirection_count == 1 => {
-                let pointer = self.get_ptr(field);
-                let block = match pointer {
-                    PointerInfo::Block(block) => block,
-                    PointerInfo::Null | PointerInfo::Invalid => panic!(
-                        "field '{}' is null or doesn't point to a valid block. ({:?})",
-                        name, field
-                    ),
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } => {
+                let (block, data, dna_index) = self.try_pointer(name, field)?;
+
+                let layout = match block {
+                    Block::Principal { .. } => self.try_layout(name, dna_index)?,
+                    _ => subsidiary_layout(self.blend, field, dna_index)
+                        .map_err(|error| AccessError::InvalidStruct {
+                            field: self.field_path(name),
+                            error,
+                        })?
+                        .ok_or_else(|| self.wrong_type(name, field, "a list of structs"))?,
                 };
 
-                match block {
-                    Block::Principal {
-                        data, dna_index, ..
-                    } => {
-                        let r#struct = &self.dna.structs[*dna_index];
-                        let r#type = &self.dna.types[r#struct.type_index];
-
-                        let fields =
-                            generate_fields(r#struct, r#type, self.dna, &self.blend.header);
-
-                        InstanceIterator::Pointer1 {
-                            dna: self.dna,
-                            blend: self.blend,
-                            fields,
-                            data,
-                            type_name: r#type.name.clone(),
-                            cur_index: 0,
-                        }
-                    }
-                    Block::Subsidiary {
-                        data, dna_index, ..
-                    } => {
-                        let (fields, r#type) = generate_subsidiary_fields(
-                            self.dna,
-                            &self.blend.header,
-                            field,
-                            *dna_index,
-                        )
-                        .expect("");
-
-                        InstanceIterator::Pointer1 {
-                            dna: self.dna,
-                            blend: self.blend,
-                            fields,
-                            data,
-                            type_name: r#type.name.clone(),
-                            cur_index: 0,
-                        }
-                    }
-                    _ => unimplemented!(),
+                InstanceIterator::Pointer1 {
+                    blend: self.blend,
+                    block,
+                    layout,
+                    data,
+                    cur_index: 0,
                 }
             }
-            FieldInfo::Pointer { indirection_count } if indirection_count == 2 => {
-                let pointer = self.get_ptr(field);
-                let block = match pointer {
-                    PointerInfo::Block(block) => block,
-                    PointerInfo::Null | PointerInfo::Invalid => panic!(
-                        "field '{}' is null or doesn't point to a valid block. ({:?})",
-                        name, field
-                    ),
-                };
+            FieldInfo::Pointer {
+                indirection_count: 2,
+            } => {
+                let (_, data, _) = self.try_pointer(name, field)?;
 
                 let pointer_size = self.blend.header.pointer_size.bytes_num();
-                let pointer_count = match block {
-                    Block::Principal { data, .. } | Block::Subsidiary { data, .. } => {
-                        data.data.len() / pointer_size
-                    }
-                    _ => unimplemented!(),
-                };
-
-                let mut pointers = Vec::new();
-                for i in 0..pointer_count {
-                    match block {
-                        Block::Principal { data, .. } | Block::Subsidiary { data, .. } => {
-                            match self.parse_ptr_address(&data.data[i * pointer_size..]) {
-                                None => {
-                                    continue;
-                                }
-                                Some(address) => pointers.push(address),
-                            }
-                        }
-                        _ => unimplemented!(),
-                    }
-                }
+                let pointers = data
+                    .data
+                    .chunks_exact(pointer_size)
+                    .filter_map(|pointer| self.parse_ptr_address(pointer))
+                    .collect::<Vec<_>>();
 
                 InstanceIterator::Pointer2 {
                     blend: self.blend,
-                    dna: self.dna,
                     field: field.clone(),
+                    name: String::from(name),
+                    path: self.path.clone(),
                     pointers: pointers.into_iter(),
                 }
             }
             FieldInfo::PointerArray {
-                indirection_count,
-                len,
+                indirection_count: 1,
                 ..
-            } if indirection_count == 1 => {
-                let data = self.data.get(field.data_start, field.data_len);
+            } => {
                 let pointer_size = self.blend.header.pointer_size.bytes_num();
-                let pointer_count = data.len() / pointer_size;
-
-                assert_eq!(len, pointer_count);
-
-                let mut pointers = Vec::new();
-                for i in 0..pointer_count {
-                    match self.parse_ptr_address(&data[i * pointer_size..]) {
-                        None => {
-                            continue;
-                        }
-                        Some(address) => pointers.push(address),
-                    }
-                }
+                let pointers = self
+                    .try_field_data(name, field)?
+                    .chunks_exact(pointer_size)
+                    .filter_map(|pointer| self.parse_ptr_address(pointer))
+                    .collect::<Vec<_>>();
 
                 InstanceIterator::Pointer2 {
                     blend: self.blend,
-                    dna: self.dna,
                     field: field.clone(),
+                    name: String::from(name),
+                    path: self.path.clone(),
                     pointers: pointers.into_iter(),
                 }
             }
-            _ => unimplemented!(),
+            _ => {
+                return Err(AccessError::NotAList {
+                    field: self.field_path(name),
+                })
+            }
+        };
+
+        // Instances created inside the iterator don't know about the libraries and the path of this one
+        let list_path = self.path.field(name, None);
+        Ok(instances.enumerate().map(move |(index, instance)| {
+            instance.and_then(|instance| {
+                instance
+                    .with_libraries(libraries)
+                    .try_follow_link(|| list_path.index(index).to_string())
+                    .map(|instance| instance.with_path(list_path.index(index)))
+            })
+        }))
+    }
+}
+
+/// The render settings of a scene, read from a `REND` block. See `Blend::render_info`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderInfo {
+    /// The name of the scene, without the `"SC"` prefix of its ID name.
+    pub scene_name: String,
+    pub start_frame: i32,
+    pub end_frame: i32,
+}
+
+/// The thumbnail of a blend file, read from the `TEST` block. See `Blend::thumbnail`.
+#[derive(Debug, Clone)]
+pub struct Thumbnail {
+    pub width: usize,
+    pub height: usize,
+    /// 8-bit RGBA pixels, row by row starting with the top row.
+    pub pixels: Vec<u8>,
+}
+
+impl Thumbnail {
+    /// Encodes the thumbnail as a PNG image. Returns `png::EncodingError::LimitsExceeded` if the width or the height
+    /// doesn't fit in a u32.
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut png_data = Vec::new();
+        {
+            let width =
+                u32::try_from(self.width).map_err(|_| png::EncodingError::LimitsExceeded)?;
+            let height =
+                u32::try_from(self.height).map_err(|_| png::EncodingError::LimitsExceeded)?;
+
+            let mut encoder = png::Encoder::new(&mut png_data, width, height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
         }
+
+        Ok(png_data)
     }
 }
 
@@ -1340,55 +1363,213 @@ pub struct Blend {
 }
 
 impl Blend {
+    /// Loads a blend file from disk. Compressed files are supported through the `gzip` and `zstd` cargo features.
     pub fn from_path<T: AsRef<Path>>(path: T) -> Result<Blend, BlendParseError> {
-        use std::{fs::File, io::Cursor};
-
-        let mut file = File::open(path).expect("could not open .blend file");
-
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)
-            .map_err(BlendParseError::IoError)?;
-
-        Blend::new(Cursor::new(buffer))
+        let blend = RawBlend::from_path(path)?;
+        Ok(Self { blend })
     }
 
+    /// Parses a blend file from `data`. Compressed files are supported through the `gzip` and `zstd` cargo features.
     pub fn new<T: Read>(data: T) -> Result<Blend, BlendParseError> {
         let blend = RawBlend::from_data(data)?;
         Ok(Self { blend })
     }
 
+    /// Parses a blend file already loaded in memory. Blocks reference their data inside `buffer` instead of copying
+    /// it, so the file is only kept in memory once.
+    pub fn from_buffer(buffer: Vec<u8>) -> Result<Blend, BlendParseError> {
+        let blend = RawBlend::from_buffer(buffer)?;
+        Ok(Self { blend })
+    }
+
+    /// Memory maps a blend file instead of reading it. See `RawBlend::from_mmap`.
+    #[cfg(feature = "memmap")]
+    pub fn from_mmap<T: AsRef<Path>>(path: T) -> Result<Blend, BlendParseError> {
+        let blend = RawBlend::from_mmap(path)?;
+        Ok(Self { blend })
+    }
+
     /// A blend file is made of blocks of binary data which represent structs. These blocks can have pointers to other
     /// blocks but only root blocks have a defined type (Object, Mesh, Materal, etc). Subsidiary blocks may or may not
     /// have the correct type information in their headers, but their type is defined by the field that accesses them.
     /// You can only query for root blocks because subsidiary blocks have to be accessed through some field for their
     /// type to be known.
-    pub fn root_instances(&self) -> impl Iterator<Item = Instance> {
+    ///
+    /// Blocks whose struct can't be read, because the struct index in their header is corrupt, are skipped. See
+    /// `RawBlend::try_struct_layout`.
+    pub fn root_instances(&self) -> impl Iterator<Item = Instance<'_>> {
         self.blend
             .blocks
             .iter()
             .filter_map(move |block| match block {
                 Block::Principal { dna_index, .. } /*| Block::Global { dna_index, .. }*/ => {
-                    let dna_struct = &self.blend.dna.structs[*dna_index];
-                    let dna_type = &self.blend.dna.types[dna_struct.type_index];
-
-                    let fields =
-                        generate_fields(dna_struct, dna_type, &self.blend.dna, &self.blend.header);
-
-                    Some(Instance {
-                        dna: &self.blend.dna,
-                        blend: &self.blend,
-                        type_name: dna_type.name.clone(),
-                        data: InstanceDataFormat::Block(block),
-                        fields,
-                    })
+                    Some(Instance::new(
+                        &self.blend,
+                        block,
+                        self.blend.try_struct_layout(*dna_index).ok()?,
+                    ).with_root_path())
                 }
                 _ => None,
             })
     }
 
+    /// Returns the instance saved at the memory address `address`, which is what a pointer with this address leads
+    /// to. Subsidiary blocks are interpreted with the type in their block header, which may not be correct as their
+    /// real type is defined by the field pointing to them. Returns `None` if no block was saved at `address` or if the
+    /// struct index in the header of the block is corrupt.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").next().unwrap();
+    /// let same_obj = blend.instance_at(obj.memory_address()).unwrap();
+    /// assert_eq!(obj.get("id").get_string("name"), same_obj.get("id").get_string("name"));
+    /// # }
+    /// ```
+    pub fn instance_at(&self, address: NonZeroU64) -> Option<Instance<'_>> {
+        let block = self.blend.block_at(address)?;
+
+        match block {
+            Block::Principal { dna_index, .. } | Block::Subsidiary { dna_index, .. } => Some(
+                Instance::new(
+                    &self.blend,
+                    block,
+                    self.blend.try_struct_layout(*dna_index).ok()?,
+                )
+                .with_root_path(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Returns the render settings Blender saves for every scene in `REND` blocks. This is a cheap way to read the frame
+    /// range of the scenes in a file without accessing the `Scene` structs.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// for info in blend.render_info() {
+    ///     println!("{}: {}..={}", info.scene_name, info.start_frame, info.end_frame);
+    /// }
+    /// # }
+    /// ```
+    pub fn render_info(&self) -> impl Iterator<Item = RenderInfo> + '_ {
+        let endianness = self.blend.header.endianness;
+
+        self.blend
+            .blocks
+            .iter()
+            .filter_map(move |block| match block {
+                // The frame range takes 8 bytes, anything shorter is not a valid REND block
+                Block::Rend { data, .. } if data.data.len() >= 8 => Some(RenderInfo {
+                    start_frame: parse_i32(&data.data[0..4], endianness),
+                    end_frame: parse_i32(&data.data[4..8], endianness),
+                    scene_name: c_string(&data.data[8..]),
+                }),
+                _ => None,
+            })
+    }
+
+    /// Returns the thumbnail Blender saves in the `TEST` block for its file browser, if the file has one.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// if let Some(thumbnail) = blend.thumbnail() {
+    ///     assert_eq!(thumbnail.pixels.len(), thumbnail.width * thumbnail.height * 4);
+    /// }
+    /// # }
+    /// ```
+    pub fn thumbnail(&self) -> Option<Thumbnail> {
+        let endianness = self.blend.header.endianness;
+
+        self.blend.blocks.iter().find_map(|block| match block {
+            Block::Test { data, .. } if data.data.len() >= 8 => {
+                let width = usize::try_from(parse_i32(&data.data[0..4], endianness)).ok()?;
+                let height = usize::try_from(parse_i32(&data.data[4..8], endianness)).ok()?;
+                let row_len = width.checked_mul(4)?;
+                let pixels_len = row_len.checked_mul(height)?;
+                let pixels = data.data.get(8..8_usize.checked_add(pixels_len)?)?;
+
+                // Blender stores images starting with the bottom row
+                Some(Thumbnail {
+                    width,
+                    height,
+                    pixels: pixels
+                        .chunks(row_len.max(1))
+                        .rev()
+                        .flatten()
+                        .copied()
+                        .collect(),
+                })
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns the `FileGlobal` struct stored in the `GLOB` block. It holds the scene and screen that were active when
+    /// the file was saved, the full version (`subversion`, `minversion` and `minsubversion`), the path the file was
+    /// saved to (`filename`) and the `build_hash` of the Blender build that saved it. Returns `None` if the file has
+    /// no `GLOB` block, which Blender always writes but which can be missing from stripped files, or if the struct index
+    /// in its header is corrupt.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let global = blend.file_global().unwrap();
+    /// let scene = global.get("curscene");
+    /// let subversion = global.get_i16("subversion");
+    /// let build_hash = global.get_string("build_hash");
+    /// # assert_eq!(global.type_name, "FileGlobal");
+    /// # assert_eq!(scene.code()[0..=1], *b"SC");
+    /// # let mut blend = blend;
+    /// # blend.blend.blocks_mut().retain(|block| !matches!(block, blend::parsers::blend::Block::Global { .. }));
+    /// # assert!(blend.file_global().is_none());
+    /// # }
+    /// ```
+    pub fn file_global(&self) -> Option<Instance<'_>> {
+        self.blend.blocks.iter().find_map(|block| match block {
+            Block::Global { dna_index, .. } => Some(
+                Instance::new(
+                    &self.blend,
+                    block,
+                    self.blend.try_struct_layout(*dna_index).ok()?,
+                )
+                .with_root_path(),
+            ),
+            _ => None,
+        })
+    }
+
     /// Root blocks have a code that tells us their type, "OB" for object, "ME" for mesh, "MA" for material, etc.
     /// You can use this method to filter for a single type of block.
-    pub fn instances_with_code(&self, search_code: [u8; 2]) -> impl Iterator<Item = Instance> {
+    ///
+    /// Like `root_instances`, blocks whose struct can't be read are skipped. So are blocks holding more than one
+    /// struct, which Blender never saves with a 2 letter code and which are only found in corrupt or edited files.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let meshes = blend.instances_with_code(*b"ME").count();
+    /// # assert_eq!(meshes, 3);
+    /// # }
+    /// ```
+    pub fn instances_with_code(&self, search_code: [u8; 2]) -> impl Iterator<Item = Instance<'_>> {
         self.blend
             .blocks
             .iter()
@@ -1398,103 +1579,341 @@ impl Blend {
                     dna_index,
                     code,
                     ..
-                } if *code == search_code => {
-                    assert!(
-                        data.count == 1,
-                        "blocks with a 2 letter code are assumed to not be lists"
-                    );
-
-                    let r#struct = &self.blend.dna.structs[*dna_index];
-                    let r#type = &self.blend.dna.types[r#struct.type_index];
-
-                    let fields =
-                        generate_fields(r#struct, r#type, &self.blend.dna, &self.blend.header);
-
-                    Some(Instance {
-                        dna: &self.blend.dna,
-                        blend: &self.blend,
-                        type_name: r#type.name.clone(),
-                        data: InstanceDataFormat::Block(block),
-                        fields,
-                    })
-                }
+                } if *code == search_code && data.count == 1 => Some(
+                    Instance::new(
+                        &self.blend,
+                        block,
+                        self.blend.try_struct_layout(*dna_index).ok()?,
+                    )
+                    .with_root_path(),
+                ),
                 _ => None,
             })
     }
 }
 
-fn generate_fields(
-    dna_struct: &DnaStruct,
-    dna_type: &DnaType,
-    dna: &Dna,
-    header: &BlendHeader,
-) -> LinkedHashMap<String, FieldTemplate> {
-    let mut fields = LinkedHashMap::new();
-    let mut data_start = 0;
-
-    for field in &dna_struct.fields {
-        let field_dna_type = &dna.types[field.type_index];
-        let field_full_name = &dna.names[field.name_index];
-
-        let is_primitive = field.type_index < 13;
-        let (_, (field_name, field_info)) =
-            parse_field(field_full_name).expect("field name could not be parsed");
-
-        let field_bytes_len = match field_info {
-            FieldInfo::Pointer { .. } | FieldInfo::FnPointer => header.pointer_size.bytes_num(),
-            FieldInfo::PointerArray { len, .. } => header.pointer_size.bytes_num() * len,
-            FieldInfo::ValueArray { len, .. } => field_dna_type.bytes_len * len,
-            FieldInfo::Value => field_dna_type.bytes_len,
-        };
+/// Reads a null terminated string. Blender saves strings as UTF-8.
+fn c_string(data: &[u8]) -> String {
+    let len = data.iter().position(|c| *c == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..len]).into_owned()
+}
 
-        fields.insert(
-            String::from(field_name),
-            FieldTemplate {
-                info: field_info,
-                type_index: field.type_index,
-                type_name: field_dna_type.name.clone(),
-                data_start,
-                data_len: field_bytes_len,
-                is_primitive,
-            },
-        );
+/// Unwraps the result of a `try_[]` method, panicking with the message of the error. Used by the panicking accessors.
+fn expect<T, E: fmt::Display>(result: Result<T, E>) -> T {
+    result.unwrap_or_else(|err| panic!("{}", err))
+}
 
-        data_start += field_bytes_len;
+/// Returns the element following `element` in a `ListBase`. Elements which don't have a `next` pointer themselves
+/// start with a struct which does, like the `ModifierData` at the start of every modifier.
+fn list_next<'a>(element: &Instance<'a>) -> Result<Instance<'a>, AccessError> {
+    let mut cur = element.clone();
+
+    while !cur.is_valid("next") {
+        match cur.layout.fields.iter().next() {
+            Some((name, field))
+                if matches!(field.info, FieldInfo::Value) && !field.is_primitive =>
+            {
+                cur = cur.try_get(name)?;
+            }
+            _ => break,
+        }
     }
-    assert_eq!(dna_type.bytes_len, data_start);
 
-    fields
+    cur.try_get("next")
 }
 
-fn generate_subsidiary_fields<'a>(
-    dna: &'a Dna,
-    header: &BlendHeader,
+/// Looks up the field `name` of the struct described by `layout`, trying its aliases if there is no field with that
+/// name.
+fn find_field<'l>(
+    blend: &RawBlend,
+    layout: &'l StructLayout,
+    name: &str,
+) -> Option<&'l FieldTemplate> {
+    layout.fields.get(name).or_else(|| {
+        let struct_name = &blend.dna.types[layout.type_index].name;
+
+        blend
+            .aliases
+            .get(struct_name, name)
+            .iter()
+            .find_map(|alias| layout.fields.get(alias))
+    })
+}
+
+/// The struct index Blender saves in the header of blocks of raw data, like arrays of floats or of pointers. Their real
+/// type is only known from the field pointing to them.
+const RAW_DATA_STRUCT_INDEX: usize = 0;
+
+/// Returns the layout used to read a subsidiary block through `field`. The struct in the block header is preferred,
+/// since it is what was saved. Blocks of raw data pointed to by a struct pointer are read with the type of the field,
+/// the ones pointed to by a primitive field (`float*` or `void*`) with the struct in their header like any other block.
+/// Returns `None` if the type of the field isn't a struct, and an error if the struct index in the block header is
+/// corrupt.
+fn subsidiary_layout(
+    blend: &RawBlend,
     field: &FieldTemplate,
     dna_index: usize,
-) -> Option<(LinkedHashMap<String, FieldTemplate>, &'a DnaType)> {
-    if field.type_index >= 13 {
-        if dna_index >= 13 {
-            //assert_eq!(field.type_index as u16, self.dna.structs[block.header.sdna_index as usize].0);
-            let r#struct = &dna.structs[dna_index];
-            let r#type = &dna.types[r#struct.type_index];
-            Some((generate_fields(r#struct, r#type, dna, header), r#type))
-        } else if let Some(r#struct) = &dna
-            .structs
-            .iter()
-            .find(|s| s.type_index == field.type_index)
-        {
-            let r#type = &dna.types[r#struct.type_index];
-            Some((generate_fields(r#struct, r#type, dna, header), r#type))
-        } else {
-            None
-        }
+) -> Result<Option<Arc<StructLayout>>, LayoutError> {
+    if dna_index == RAW_DATA_STRUCT_INDEX && !field.is_primitive {
+        blend
+            .struct_index(field.type_index)
+            .map(|struct_index| blend.try_struct_layout(struct_index))
+            .transpose()
     } else {
-        let r#struct = &dna.structs[dna_index];
-        if r#struct.type_index >= 13 {
-            let r#type = &dna.types[r#struct.type_index];
-            Some((generate_fields(r#struct, r#type, dna, header), r#type))
-        } else {
-            None
+        blend.try_struct_layout(dna_index).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "png")]
+    use super::Thumbnail;
+    use super::{AccessError, Blend, Instance};
+    use crate::parsers::{blend::Block, buffer::BlockBytes};
+
+    fn blend() -> Blend {
+        Blend::from_path("examples/blend_files/2_80.blend").unwrap()
+    }
+
+    fn find<'a>(blend: &'a Blend, code: [u8; 2], name: &str) -> Instance<'a> {
+        blend
+            .instances_with_code(code)
+            .find(|instance| instance.get("id").get_string("name") == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn cyclic_list() {
+        let mut blend = blend();
+        let sphere = find(&blend, *b"OB", "OBSphere");
+        let modifiers = sphere
+            .get_iter("modifiers")
+            .map(|m| m.memory_address())
+            .collect::<Vec<_>>();
+        assert_eq!(modifiers.len(), 3);
+
+        // Loop the list back to its first element
+        for block in blend.blend.blocks_mut().iter_mut() {
+            if block.memory_address() == Some(modifiers[1]) {
+                block.data_mut().unwrap().data.to_mut()[..8]
+                    .copy_from_slice(&modifiers[0].get().to_le_bytes());
+            }
+        }
+
+        let sphere = find(&blend, *b"OB", "OBSphere");
+        let modifiers = sphere
+            .try_get_iter("modifiers")
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(modifiers.len(), 3);
+        assert!(modifiers[..2].iter().all(Result::is_ok));
+        assert!(
+            matches!(&modifiers[2], Err(AccessError::CyclicList { field }) if field == "OB\"Sphere\".modifiers")
+        );
+    }
+
+    #[test]
+    fn dangling_pointer_in_pointer_array() {
+        let mut blend = blend();
+        let materials = find(&blend, *b"ME", "MECube")
+            .get_iter("mat")
+            .map(|m| m.memory_address().get().to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(materials.len(), 1);
+
+        for block in blend.blend.blocks_mut().iter_mut() {
+            if let Block::Subsidiary { data, .. } = block {
+                if data.data[..] == materials[0][..] {
+                    data.data
+                        .to_mut()
+                        .copy_from_slice(&0xdead0_u64.to_le_bytes());
+                }
+            }
+        }
+
+        let cube = find(&blend, *b"ME", "MECube");
+        assert!(!cube.is_valid("mat"));
+        let materials = cube.try_get_iter("mat").unwrap().collect::<Vec<_>>();
+        assert!(
+            matches!(&materials[..], [Err(AccessError::DanglingPointer { field })] if field == "ME\"Cube\".mat")
+        );
+    }
+
+    #[test]
+    fn raw_data_blocks() {
+        let blend = blend();
+        let mesh = blend.instances_with_code(*b"ME").next().unwrap();
+
+        // `void` pointers are read with the struct saved in the header of the block they point to
+        let uv_layer = mesh
+            .get("ldata")
+            .get_iter("layers")
+            .find(|layer| layer.get_i32("type") == 16)
+            .unwrap();
+        assert_eq!(uv_layer.get("data").type_name, "MLoopUV");
+
+        // The paint mask layer holds a block of floats, saved without a struct in its header
+        let mask_layer = mesh
+            .get("vdata")
+            .get_iter("layers")
+            .find(|layer| layer.get_i32("type") == 34)
+            .unwrap();
+        assert_eq!(mask_layer.get("data").type_name, "Link");
+        assert_eq!(
+            mask_layer.get_f32_vec("data").len(),
+            mesh.get_i32("totvert") as usize
+        );
+    }
+
+    #[test]
+    fn truncated_block() {
+        let mut blend = blend();
+        let address = blend
+            .instances_with_code(*b"OB")
+            .next()
+            .unwrap()
+            .memory_address();
+
+        // Cut the block short of its struct
+        for block in blend.blend.blocks_mut().iter_mut() {
+            if block.memory_address() == Some(address) {
+                let data = block.data_mut().unwrap();
+                data.data = BlockBytes::Owned(data.data[..64].to_vec());
+            }
         }
+
+        // The fields past its end are invalid and can't be read
+        let obj = blend.instance_at(address).unwrap();
+        for field in &["data", "loc", "modifiers", "id", "mat"] {
+            assert!(!obj.is_valid(field));
+        }
+        assert!(matches!(
+            obj.try_get("data"),
+            Err(AccessError::TruncatedData { .. })
+        ));
+        assert!(matches!(
+            obj.try_get_f32_vec("loc"),
+            Err(AccessError::TruncatedData { .. })
+        ));
+        assert!(matches!(
+            obj.try_get_iter("modifiers").err(),
+            Some(AccessError::TruncatedData { .. })
+        ));
+        assert!(obj.try_get("id").is_err());
+        assert!(obj.to_string().contains("truncated"));
+    }
+
+    #[test]
+    fn render_info() {
+        let mut blend = Blend::from_path("examples/blend_files/3_5.blend").unwrap();
+        assert_eq!(blend.render_info().next().unwrap().scene_name, "Scene");
+
+        // Scene names are saved as UTF-8
+        for block in blend.blend.blocks_mut().iter_mut() {
+            if let Block::Rend { data, .. } = block {
+                let mut rend = data.data[..8].to_vec();
+                rend.extend_from_slice("Scène\0".as_bytes());
+                data.data = BlockBytes::Owned(rend);
+            }
+        }
+        assert_eq!(blend.render_info().next().unwrap().scene_name, "Scène");
+    }
+
+    #[test]
+    fn thumbnail() {
+        let mut blend = Blend::from_path("examples/blend_files/3_5.blend").unwrap();
+        let thumbnail = blend.thumbnail().unwrap();
+        assert_eq!((thumbnail.width, thumbnail.height), (128, 128));
+
+        // A TEST block whose size doesn't match its data has no thumbnail
+        for block in blend.blend.blocks_mut().iter_mut() {
+            if let Block::Test { data, .. } = block {
+                let mut test = data.data.to_vec();
+                test[..8].copy_from_slice(&[0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff, 0x7f]);
+                data.data = BlockBytes::Owned(test);
+            }
+        }
+        assert!(blend.thumbnail().is_none());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn thumbnail_to_png() {
+        let blend = Blend::from_path("examples/blend_files/3_5.blend").unwrap();
+        assert!(blend
+            .thumbnail()
+            .unwrap()
+            .to_png()
+            .unwrap()
+            .starts_with(b"\x89PNG"));
+
+        let too_wide = Thumbnail {
+            width: usize::MAX,
+            height: 0,
+            pixels: Vec::new(),
+        };
+        assert!(matches!(
+            too_wide.to_png(),
+            Err(png::EncodingError::LimitsExceeded)
+        ));
+    }
+
+    #[test]
+    fn instances_with_code_skips_unreadable_blocks() {
+        let mut blend = blend();
+        let mesh_count = blend.instances_with_code(*b"ME").count();
+        let mesh_address = blend
+            .instances_with_code(*b"ME")
+            .next()
+            .unwrap()
+            .memory_address();
+        let obj_name = blend
+            .instances_with_code(*b"OB")
+            .find(|obj| obj.get("data").memory_address() == mesh_address)
+            .map(|obj| obj.get("id").get_string("name"))
+            .unwrap();
+
+        // Corrupt the struct index in the header of the mesh block
+        for block in blend.blend.blocks_mut().iter_mut() {
+            if let Block::Principal {
+                memory_address,
+                dna_index,
+                ..
+            } = block
+            {
+                if *memory_address == mesh_address {
+                    *dna_index = 60000;
+                }
+            }
+        }
+
+        assert_eq!(blend.instances_with_code(*b"ME").count(), mesh_count - 1);
+        assert!(blend.instance_at(mesh_address).is_none());
+        let obj = find(&blend, *b"OB", &obj_name);
+        assert!(!obj.is_valid("data"));
+        assert!(matches!(
+            obj.try_get("data"),
+            Err(AccessError::InvalidStruct { .. })
+        ));
+        let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| obj.get("data")));
+        assert!(panic.is_err());
+
+        // Blocks with a 2 letter code holding more than one struct are skipped too
+        for block in blend.blend.blocks_mut().iter_mut() {
+            if let Block::Principal {
+                memory_address,
+                dna_index,
+                data,
+                ..
+            } = block
+            {
+                if *memory_address == mesh_address {
+                    *dna_index = 0;
+                    data.count = 2;
+                }
+            }
+        }
+
+        assert_eq!(blend.instances_with_code(*b"ME").count(), mesh_count - 1);
     }
 }
@@ -0,0 +1,419 @@
+use super::{c_string, AccessError, Instance, RAW_DATA_STRUCT_INDEX};
+use crate::parsers::{blend::Block, dna_diff::FieldType, field::FieldInfo, layout::FieldTemplate};
+use std::{collections::HashSet, fmt, fmt::Debug, num::NonZeroU64};
+
+/// Writes an `Instance` as indented text, with every field and every element of its lists. The `Display`
+/// implementation of `Instance` uses the default `Dumper`.
+///
+/// Structs behind pointers are written in place the first time they are found and as a back-reference (`@address`)
+/// after that, so every block is written once and cycles end. Pointers to other datablocks (blocks with a code, like
+/// the mesh of an object) are only written as back-references unless `follow_datablocks` is set. The dumper doesn't
+/// recurse, so deeply nested blocks can't overflow the stack, and fields it can't read are written as `<error>`
+/// instead of panicking.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::{Blend, runtime::Dumper};
+/// # fn main() {
+///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+/// let mesh = blend.instances_with_code(*b"ME").next().unwrap();
+/// let dumper = Dumper {
+///     max_list_len: Some(4),
+///     ..Dumper::default()
+/// };
+///
+/// let mut text = String::new();
+/// dumper.dump(&mesh, &mut text).unwrap();
+/// println!("{}", text);
+/// # assert!(text.starts_with("Mesh (code:ME|@"));
+/// # assert!(text.contains("... 20 more"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Dumper {
+    /// How many elements of each list are written. `None` writes every element.
+    pub max_list_len: Option<usize>,
+    /// Whether pointers to other datablocks are followed.
+    pub follow_datablocks: bool,
+}
+
+/// What is left to write. The dumper pops these from a stack instead of recursing into structs.
+enum Work<'a> {
+    /// Text written as is.
+    Text(String),
+    /// The fields of a struct, starting at the field `next`.
+    Fields {
+        instance: Instance<'a>,
+        next: usize,
+        indent: usize,
+    },
+    /// A struct, written in place or as a back-reference. `prefix` is written before it.
+    Struct {
+        instance: Instance<'a>,
+        prefix: String,
+        indent: usize,
+        in_list: bool,
+    },
+}
+
+impl Dumper {
+    /// Writes `instance` to `out`.
+    pub fn dump(&self, instance: &Instance, out: &mut impl fmt::Write) -> fmt::Result {
+        Writer {
+            dumper: self,
+            out,
+            visited: HashSet::new(),
+        }
+        .write(instance)
+    }
+}
+
+/// The state of a `Dumper` while it writes an instance.
+struct Writer<'d, W> {
+    dumper: &'d Dumper,
+    out: &'d mut W,
+    /// The addresses of the blocks already written.
+    visited: HashSet<NonZeroU64>,
+}
+
+impl<W: fmt::Write> Writer<'_, W> {
+    fn write(&mut self, instance: &Instance) -> fmt::Result {
+        let mut stack = vec![Work::Struct {
+            instance: instance.clone(),
+            prefix: String::new(),
+            indent: 0,
+            in_list: false,
+        }];
+
+        while let Some(work) = stack.pop() {
+            match work {
+                Work::Text(text) => self.out.write_str(&text)?,
+                Work::Struct {
+                    instance,
+                    prefix,
+                    indent,
+                    in_list,
+                } => {
+                    let address = instance.data.memory_address();
+                    let is_datablock = instance.data.code().is_some() && indent > 0;
+                    let written = match address {
+                        Some(address) => {
+                            (is_datablock && !self.dumper.follow_datablocks)
+                                || !self.visited.insert(address)
+                        }
+                        None => false,
+                    };
+                    let separator = if in_list { "," } else { ";" };
+
+                    match address {
+                        Some(address) if written => {
+                            writeln!(self.out, "{}@{}{}", prefix, address, separator)?
+                        }
+                        _ => {
+                            writeln!(self.out, "{}{} {{", prefix, header(&instance))?;
+                            stack.push(Work::Text(format!(
+                                "{}}}{}\n",
+                                pad(indent),
+                                if in_list { "," } else { "" }
+                            )));
+                            stack.push(Work::Fields {
+                                instance,
+                                next: 0,
+                                indent: indent + 1,
+                            });
+                        }
+                    }
+                }
+                Work::Fields {
+                    instance,
+                    next,
+                    indent,
+                } => {
+                    let layout = std::sync::Arc::clone(&instance.layout);
+
+                    for (index, (name, field)) in layout.fields.iter().enumerate().skip(next) {
+                        if name.starts_with("_pad") {
+                            continue;
+                        }
+
+                        let nested = self.field(&instance, name, field, indent)?;
+
+                        if !nested.is_empty() {
+                            stack.push(Work::Fields {
+                                instance,
+                                next: index + 1,
+                                indent,
+                            });
+                            stack.extend(nested.into_iter().rev());
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the field `name` of `instance` if it fits in a line. Otherwise writes the first line and returns what
+    /// is left to write, in order.
+    fn field<'a>(
+        &mut self,
+        instance: &Instance<'a>,
+        name: &str,
+        field: &FieldTemplate,
+        indent: usize,
+    ) -> Result<Vec<Work<'a>>, fmt::Error> {
+        let field_type = FieldType {
+            type_name: field.type_name.clone(),
+            info: field.info.clone(),
+        };
+        let label = format!(
+            "{}{}{}",
+            field.type_name,
+            "*".repeat(field_type.indirection_count()),
+            field_type
+                .dimensions()
+                .iter()
+                .map(|d| format!("[{}]", d))
+                .collect::<String>()
+        );
+        let prefix = format!("{}{}: {} = ", pad(indent), name, label);
+
+        let value = match &field.info {
+            FieldInfo::Value if field.type_name == "void" => Ok(String::from("()")),
+            FieldInfo::Value if field.is_primitive => primitive(instance, name),
+            FieldInfo::Value if field.type_name == "ListBase" => {
+                match instance
+                    .try_get_iter(name)
+                    .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+                {
+                    Ok(elements) => {
+                        let element_type = elements.first().map_or("?", |e| e.type_name);
+                        let prefix =
+                            format!("{}{}: ListBase<{}> = ", pad(indent), name, element_type);
+                        return self.list(&prefix, elements, indent);
+                    }
+                    // Lists Blender doesn't save can't be followed, they are written as the struct they are saved as
+                    Err(_) => match instance.try_get(name) {
+                        Ok(list) => {
+                            return Ok(vec![Work::Struct {
+                                instance: list,
+                                prefix: format!("{}{}: ", pad(indent), name),
+                                indent,
+                                in_list: false,
+                            }])
+                        }
+                        Err(err) => Err(err),
+                    },
+                }
+            }
+            FieldInfo::Value => match instance.try_get(name) {
+                Ok(value) => {
+                    return Ok(vec![Work::Struct {
+                        instance: value,
+                        prefix: format!("{}{}: ", pad(indent), name),
+                        indent,
+                        in_list: false,
+                    }])
+                }
+                Err(err) => Err(err),
+            },
+            FieldInfo::ValueArray { dimensions, .. } if field.type_name == "char" => {
+                instance.try_field_data(name, field).map(|data| {
+                    let len = dimensions.last().copied().unwrap_or(1).max(1);
+                    let strings = data.chunks(len).map(c_string).collect::<Vec<_>>();
+
+                    match strings.as_slice() {
+                        [string] => format!("{:?}", string),
+                        strings => format!("{:?}", strings),
+                    }
+                })
+            }
+            FieldInfo::ValueArray { .. } if field.is_primitive => primitive_array(instance, name),
+            FieldInfo::ValueArray { .. } => match instance
+                .try_get_iter(name)
+                .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+            {
+                Ok(elements) => return self.list(&prefix, elements, indent),
+                Err(err) => Err(err),
+            },
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } => return self.pointer(instance, name, field, &prefix, indent),
+            FieldInfo::Pointer { .. } | FieldInfo::PointerArray { .. } => {
+                match instance
+                    .try_get_iter(name)
+                    .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+                {
+                    Ok(elements) => return self.list(&prefix, elements, indent),
+                    Err(AccessError::NullPointer { .. }) => Ok(String::from("null")),
+                    Err(err) => Err(err),
+                }
+            }
+            FieldInfo::FnPointer => Ok(address(instance, name, field)),
+        };
+
+        match value {
+            Ok(value) => writeln!(self.out, "{}{};", prefix, value)?,
+            Err(err) => writeln!(self.out, "{}<{}>;", prefix, err)?,
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// Writes a pointer to a single block: as null, a back-reference, primitives or the struct(s) it points to.
+    fn pointer<'a>(
+        &mut self,
+        instance: &Instance<'a>,
+        name: &str,
+        field: &FieldTemplate,
+        prefix: &str,
+        indent: usize,
+    ) -> Result<Vec<Work<'a>>, fmt::Error> {
+        let address = address(instance, name, field);
+        let block = instance.try_pointer(name, field);
+
+        // Lists are written by their `ListBase`, following their links would write their elements again
+        if ["next", "prev", "first", "last"].contains(&name) || block.is_err() {
+            let missing = match block {
+                Err(AccessError::DanglingPointer { .. }) => " (missing)",
+                _ => "",
+            };
+            writeln!(self.out, "{}{}{};", prefix, address, missing)?;
+            return Ok(Vec::new());
+        }
+
+        if field.is_primitive && field.type_name != "void" {
+            let value = match field.type_name.as_str() {
+                "char" => instance
+                    .try_get_vec::<u8>(name)
+                    .map(|data| format!("{:?}", c_string(&data))),
+                _ => primitive_array(instance, name),
+            };
+            writeln!(self.out, "{}{};", prefix, value.unwrap_or(address))?;
+            return Ok(Vec::new());
+        }
+
+        // The type of `void` pointers to raw data and of some blocks can't be known, those are only referenced
+        match block {
+            Ok((Block::Subsidiary { .. }, _, RAW_DATA_STRUCT_INDEX)) if field.is_primitive => {}
+            Ok((_, data, _)) if data.count == 1 => {
+                if let Ok(pointee) = instance.try_get(name) {
+                    return Ok(vec![Work::Struct {
+                        instance: pointee,
+                        prefix: String::from(prefix),
+                        indent,
+                        in_list: false,
+                    }]);
+                }
+            }
+            // Arrays of structs are a single block, the elements don't have an address of their own
+            Ok((Block::Subsidiary { memory_address, .. }, _, _))
+                if !self.visited.contains(memory_address) =>
+            {
+                let elements = instance
+                    .try_get_iter(name)
+                    .and_then(Iterator::collect::<Result<Vec<_>, _>>);
+
+                if let Ok(elements) = elements {
+                    self.visited.insert(*memory_address);
+                    return self.list(prefix, elements, indent);
+                }
+            }
+            _ => {}
+        }
+
+        writeln!(self.out, "{}{};", prefix, address)?;
+        Ok(Vec::new())
+    }
+
+    /// Writes the first line of a list and returns its elements.
+    fn list<'a>(
+        &mut self,
+        prefix: &str,
+        elements: Vec<Instance<'a>>,
+        indent: usize,
+    ) -> Result<Vec<Work<'a>>, fmt::Error> {
+        if elements.is_empty() {
+            writeln!(self.out, "{}[];", prefix)?;
+            return Ok(Vec::new());
+        }
+
+        writeln!(self.out, "{}[", prefix)?;
+
+        let len = elements.len();
+        let shown = self.dumper.max_list_len.map_or(len, |max| max.min(len));
+        let mut works = elements
+            .into_iter()
+            .take(shown)
+            .map(|instance| Work::Struct {
+                instance,
+                prefix: pad(indent + 1),
+                indent: indent + 1,
+                in_list: true,
+            })
+            .collect::<Vec<_>>();
+
+        if shown < len {
+            works.push(Work::Text(format!(
+                "{}... {} more\n",
+                pad(indent + 1),
+                len - shown
+            )));
+        }
+        works.push(Work::Text(format!("{}];\n", pad(indent))));
+
+        Ok(works)
+    }
+}
+
+/// The first line of a struct: its type, code and address.
+fn header(instance: &Instance) -> String {
+    match (instance.data.code(), instance.data.memory_address()) {
+        (Some(code), Some(address)) => format!(
+            "{} (code:{}|@{})",
+            instance.type_name,
+            String::from_utf8_lossy(&code[0..=1]),
+            address
+        ),
+        (Some(code), None) => format!(
+            "{} (code:{})",
+            instance.type_name,
+            String::from_utf8_lossy(&code[0..=1])
+        ),
+        (None, Some(address)) => format!("{} (@{})", instance.type_name, address),
+        (None, None) => String::from(instance.type_name),
+    }
+}
+
+fn pad(indent: usize) -> String {
+    " ".repeat(4 * indent)
+}
+
+/// Formats the address saved in a pointer field as `@address`, or `null`.
+fn address(instance: &Instance, name: &str, field: &FieldTemplate) -> String {
+    match instance
+        .try_field_data(name, field)
+        .map(|data| instance.parse_ptr_address(data))
+    {
+        Ok(Some(address)) => format!("@{}", address),
+        Ok(None) => String::from("null"),
+        Err(err) => format!("<{}>", err),
+    }
+}
+
+/// Formats a primitive field with the Rust type matching its DNA type.
+fn primitive(instance: &Instance, name: &str) -> Result<String, AccessError> {
+    instance
+        .try_get_primitive(name)
+        .map(|value| format!("{:?}", value))
+}
+
+/// Formats a primitive array, or the primitives behind a pointer.
+fn primitive_array(instance: &Instance, name: &str) -> Result<String, AccessError> {
+    instance
+        .try_get_primitives(name)
+        .map(|values| format!("{:?}", values))
+}
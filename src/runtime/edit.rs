@@ -0,0 +1,749 @@
+use super::{
+    expect, find_field, parse_ptr_address, AccessError, Blend, Instance, InstanceDataFormat,
+};
+use crate::parsers::{
+    blend::{Block, RawBlend},
+    field::FieldInfo,
+    layout::{FieldTemplate, StructLayout},
+    primitive::BlendPrimitive,
+};
+use std::{error::Error, fmt, mem::size_of_val, num::NonZeroU64, sync::Arc};
+
+/// Where the data of an `Instance` is stored inside a `RawBlend`. A location doesn't borrow the blend file, so the
+/// locations of every instance you want to change can be collected first and passed to `Blend::edit` afterwards.
+///
+/// A location can only be used with the `Blend` the instance was read from. Locations of other files, including the
+/// libraries of a `BlendLibrarySet` and the files returned by `RawBlend::convert_to`, are refused by `Blend::edit`.
+#[derive(Debug, Clone)]
+pub struct InstanceLocation {
+    /// The `RawBlend::id` of the file holding the instance.
+    blend_id: u64,
+    /// The index of the block holding the instance inside `RawBlend::blocks`.
+    block_index: usize,
+    /// The memory address of that block, to notice when the blocks were reordered since.
+    memory_address: Option<NonZeroU64>,
+    /// Where the instance starts inside the data of the block.
+    offset: usize,
+    /// The fields of the instance.
+    layout: Arc<StructLayout>,
+}
+
+impl InstanceLocation {
+    /// The index of the block holding the instance inside `RawBlend::blocks`.
+    pub fn block_index(&self) -> usize {
+        self.block_index
+    }
+
+    /// Where the instance starts inside the data of its block.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The fields of the instance.
+    pub fn layout(&self) -> &Arc<StructLayout> {
+        &self.layout
+    }
+}
+
+/// Returned by `Blend::try_edit` when an `InstanceLocation` can't be edited in a `Blend`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocationError {
+    /// The location was taken from an instance of another file.
+    OtherFile,
+    /// The file has no block at the index of the location anymore, or the block there has another memory address,
+    /// which happens when blocks are removed or reordered through `RawBlend::blocks_mut`.
+    BlockMoved { block_index: usize },
+    /// The instance would end past the end of its block, which happens when blocks are shortened through
+    /// `RawBlend::blocks_mut`.
+    OutOfBounds { block_index: usize },
+}
+
+impl fmt::Display for LocationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocationError::OtherFile => write!(f, "location was taken from another file"),
+            LocationError::BlockMoved { block_index } => {
+                write!(
+                    f,
+                    "block {} of the location was moved or removed",
+                    block_index
+                )
+            }
+            LocationError::OutOfBounds { block_index } => {
+                write!(f, "location is outside of block {}", block_index)
+            }
+        }
+    }
+}
+
+impl Error for LocationError {}
+
+impl<'a> Instance<'a> {
+    /// Returns where the data of this instance is stored, to be edited with `Blend::edit`.
+    pub fn location(&self) -> InstanceLocation {
+        // Instances are never created from the DNA1 block, the only block without data
+        let block_data = InstanceDataFormat::Block(self.block)
+            .data()
+            .expect("the block of an instance has data");
+        let data = self.data.data().expect("an instance has data");
+
+        InstanceLocation {
+            blend_id: self.blend.id,
+            block_index: self
+                .blend
+                .block_position(self.block)
+                .expect("the block of an instance is always in its blend file"),
+            memory_address: self.block.memory_address(),
+            offset: data.as_ptr() as usize - block_data.as_ptr() as usize,
+            layout: Arc::clone(&self.layout),
+        }
+    }
+}
+
+impl Blend {
+    /// Returns an `InstanceMut` to change the primitive fields of the instance at `location`. Values are written with
+    /// the endianness of the file and every later read on this `Blend` sees them. Blocks are copied out of the file
+    /// buffer the first time they are edited, the buffer itself is never modified.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let mut blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let objects = blend
+    ///     .instances_with_code(*b"OB")
+    ///     .map(|obj| obj.location())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// for location in &objects {
+    ///     let mut obj = blend.edit(location);
+    ///     obj.set_f32_vec("loc", &[0.0, 0.0, 1.0]);
+    ///     obj.get("id").set_string("name", "OBMoved");
+    /// }
+    ///
+    /// for obj in blend.instances_with_code(*b"OB") {
+    ///     assert_eq!(obj.get_f32_vec("loc"), [0.0, 0.0, 1.0]);
+    ///     assert_eq!(obj.get("id").get_string("name"), "OBMoved");
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if `location` can't be edited in this file. See `try_edit`.
+    pub fn edit(&mut self, location: &InstanceLocation) -> InstanceMut<'_> {
+        expect(self.try_edit(location))
+    }
+
+    /// Same as `edit`, but returns an error instead of panicking when `location` was taken from another file, when its
+    /// block was moved or removed since, or when the instance doesn't fit inside its block anymore.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::{Blend, runtime::LocationError};
+    /// # fn main() {
+    ///     # let mut blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let other = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let location = other.instances_with_code(*b"OB").next().unwrap().location();
+    ///
+    /// assert!(matches!(blend.try_edit(&location), Err(LocationError::OtherFile)));
+    /// # }
+    /// ```
+    pub fn try_edit(
+        &mut self,
+        location: &InstanceLocation,
+    ) -> Result<InstanceMut<'_>, LocationError> {
+        if location.blend_id != self.blend.id {
+            return Err(LocationError::OtherFile);
+        }
+
+        let block_index = location.block_index;
+        let block = self
+            .blend
+            .blocks
+            .get(block_index)
+            .filter(|block| block.memory_address() == location.memory_address)
+            .ok_or(LocationError::BlockMoved { block_index })?;
+        let block_len = block.data().map_or(0, |data| data.data.len());
+        if location
+            .offset
+            .checked_add(location.layout.size())
+            .is_none_or(|end| end > block_len)
+        {
+            return Err(LocationError::OutOfBounds { block_index });
+        }
+
+        Ok(InstanceMut {
+            blend: &mut self.blend,
+            location: location.clone(),
+        })
+    }
+}
+
+/// Gives write access to the primitive fields of an `Instance`. Created by `Blend::edit`.
+pub struct InstanceMut<'a> {
+    blend: &'a mut RawBlend,
+    location: InstanceLocation,
+}
+
+impl<'a> InstanceMut<'a> {
+    /// Returns the field `name` of the instance, or a `NoSuchField` error. Errors of an `InstanceMut` name the field
+    /// without a path, since the instance is found through an `InstanceLocation`.
+    fn try_field<'l>(
+        &self,
+        layout: &'l StructLayout,
+        name: &str,
+    ) -> Result<&'l FieldTemplate, AccessError> {
+        find_field(self.blend, layout, name).ok_or_else(|| AccessError::NoSuchField {
+            struct_name: self.blend.dna.types[layout.type_index].name.clone(),
+            name: String::from(name),
+            path: None,
+        })
+    }
+
+    /// The bytes of this instance's block from `start` to `start + len`, relative to the start of the instance.
+    fn data(&self, start: usize, len: usize) -> &[u8] {
+        let start = self.location.offset + start;
+        match self.blend.blocks[self.location.block_index].data() {
+            Some(data) => &data.data[start..start + len],
+            // The location was checked by `Blend::try_edit`
+            None => unreachable!("the DNA1 block can't be edited"),
+        }
+    }
+
+    /// Same as `data`, but copies the block out of the file buffer so it can be written.
+    fn data_mut(&mut self, start: usize, len: usize) -> &mut [u8] {
+        let start = self.location.offset + start;
+        &mut block_data_mut(&mut self.blend.blocks[self.location.block_index])[start..start + len]
+    }
+
+    /// Returns the index of the block a pointer field points to.
+    fn try_pointer_target(&self, name: &str, field: &FieldTemplate) -> Result<usize, AccessError> {
+        let address = parse_ptr_address(
+            self.data(field.data_start, field.data_len),
+            self.blend.header.pointer_size,
+            self.blend.header.endianness,
+        )
+        .ok_or_else(|| AccessError::NullPointer {
+            field: String::from(name),
+        })?;
+
+        let block = self
+            .blend
+            .block_at(address)
+            .ok_or_else(|| AccessError::DanglingPointer {
+                field: String::from(name),
+            })?;
+
+        Ok(self
+            .blend
+            .block_position(block)
+            .expect("block_at always returns a block of the file"))
+    }
+
+    /// `try_set_value` abstracts writing primitives and is used by all `set_[]` functions (`set_i8`, `set_f32`, etc).
+    fn try_set_value<U: BlendPrimitive>(
+        &mut self,
+        name: &str,
+        value: U,
+    ) -> Result<(), AccessError> {
+        let layout = Arc::clone(&self.location.layout);
+        let field = self.try_field(&layout, name)?;
+
+        match field.info {
+            FieldInfo::Value if field.is_primitive && field.data_len == U::SIZE => {
+                let endianness = self.blend.header.endianness;
+                value.write(self.data_mut(field.data_start, field.data_len), endianness);
+                Ok(())
+            }
+            _ => Err(AccessError::wrong_type(
+                String::from(name),
+                name,
+                field,
+                U::blender_name(),
+            )),
+        }
+    }
+
+    pub fn set_u8<T: AsRef<str>>(&mut self, name: T, value: u8) {
+        expect(self.try_set_u8(name, value))
+    }
+
+    pub fn try_set_u8<T: AsRef<str>>(&mut self, name: T, value: u8) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    pub fn set_i8<T: AsRef<str>>(&mut self, name: T, value: i8) {
+        expect(self.try_set_i8(name, value))
+    }
+
+    pub fn try_set_i8<T: AsRef<str>>(&mut self, name: T, value: i8) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    /// ## Panics
+    ///
+    /// * Panics if `value` is not ASCII.
+    pub fn set_char<T: AsRef<str>>(&mut self, name: T, value: char) {
+        expect(self.try_set_char(name, value))
+    }
+
+    /// Same as `set_char`, but returns an error instead of panicking.
+    pub fn try_set_char<T: AsRef<str>>(&mut self, name: T, value: char) -> Result<(), AccessError> {
+        let name = name.as_ref();
+        if !value.is_ascii() {
+            return Err(AccessError::ValueDoesNotFit {
+                field: String::from(name),
+                value: format!("{:?}", value),
+            });
+        }
+        self.try_set_u8(name, value as u8)
+    }
+
+    pub fn set_u16<T: AsRef<str>>(&mut self, name: T, value: u16) {
+        expect(self.try_set_u16(name, value))
+    }
+
+    pub fn try_set_u16<T: AsRef<str>>(&mut self, name: T, value: u16) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    pub fn set_i16<T: AsRef<str>>(&mut self, name: T, value: i16) {
+        expect(self.try_set_i16(name, value))
+    }
+
+    pub fn try_set_i16<T: AsRef<str>>(&mut self, name: T, value: i16) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    pub fn set_i32<T: AsRef<str>>(&mut self, name: T, value: i32) {
+        expect(self.try_set_i32(name, value))
+    }
+
+    pub fn try_set_i32<T: AsRef<str>>(&mut self, name: T, value: i32) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    pub fn set_u32<T: AsRef<str>>(&mut self, name: T, value: u32) {
+        expect(self.try_set_u32(name, value))
+    }
+
+    pub fn try_set_u32<T: AsRef<str>>(&mut self, name: T, value: u32) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    pub fn set_f32<T: AsRef<str>>(&mut self, name: T, value: f32) {
+        expect(self.try_set_f32(name, value))
+    }
+
+    pub fn try_set_f32<T: AsRef<str>>(&mut self, name: T, value: f32) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    pub fn set_f64<T: AsRef<str>>(&mut self, name: T, value: f64) {
+        expect(self.try_set_f64(name, value))
+    }
+
+    pub fn try_set_f64<T: AsRef<str>>(&mut self, name: T, value: f64) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    pub fn set_u64<T: AsRef<str>>(&mut self, name: T, value: u64) {
+        expect(self.try_set_u64(name, value))
+    }
+
+    pub fn try_set_u64<T: AsRef<str>>(&mut self, name: T, value: u64) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    pub fn set_i64<T: AsRef<str>>(&mut self, name: T, value: i64) {
+        expect(self.try_set_i64(name, value))
+    }
+
+    pub fn try_set_i64<T: AsRef<str>>(&mut self, name: T, value: i64) -> Result<(), AccessError> {
+        self.try_set_value(name.as_ref(), value)
+    }
+
+    /// `try_set_value_vec` abstracts writing primitive arrays and is used by all `set_[]_vec` functions (`set_i8_vec`,
+    /// `set_f32_vec`, etc). Like `Instance::get_f32_vec` and the other getters it accepts arrays and pointers to
+    /// blocks of primitives of the same size as `U`, and `values` must have as many elements as the array or block.
+    /// Pointers to structs and to datablocks are rejected, and so are pointers to primitives of another size, except
+    /// `void*`.
+    fn try_set_value_vec<U: BlendPrimitive>(
+        &mut self,
+        name: &str,
+        values: &[U],
+    ) -> Result<(), AccessError> {
+        let layout = Arc::clone(&self.location.layout);
+        let field = self.try_field(&layout, name)?;
+
+        let endianness = self.blend.header.endianness;
+        let size = U::SIZE;
+        let wrong_type = || {
+            AccessError::wrong_type(
+                String::from(name),
+                name,
+                field,
+                format!("an array of {}", U::blender_name()),
+            )
+        };
+        let does_not_fit = || AccessError::ValueDoesNotFit {
+            field: String::from(name),
+            value: format!("an array of {} values", values.len()),
+        };
+
+        let data = match field.info {
+            FieldInfo::ValueArray { len, .. }
+                if field.is_primitive && field.data_len == len * size =>
+            {
+                if values.len() != len {
+                    return Err(does_not_fit());
+                }
+
+                self.data_mut(field.data_start, field.data_len)
+            }
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } if field.is_primitive => {
+                let type_len = self.blend.dna.types[field.type_index].bytes_len;
+                if field.type_name != "void" && type_len != size {
+                    return Err(wrong_type());
+                }
+
+                // A `void*` can point to anything, but datablocks are always structs
+                let block_index = self.try_pointer_target(name, field)?;
+                if let Block::Principal { .. } = self.blend.blocks[block_index] {
+                    return Err(wrong_type());
+                }
+
+                let data = block_data_mut(&mut self.blend.blocks[block_index]);
+                if data.len() != size_of_val(values) {
+                    return Err(does_not_fit());
+                }
+
+                data
+            }
+            _ => return Err(wrong_type()),
+        };
+
+        for (chunk, value) in data.chunks_mut(size).zip(values) {
+            value.write(chunk, endianness);
+        }
+        Ok(())
+    }
+
+    pub fn set_u8_vec<T: AsRef<str>>(&mut self, name: T, values: &[u8]) {
+        expect(self.try_set_u8_vec(name, values))
+    }
+
+    pub fn try_set_u8_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[u8],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    pub fn set_i8_vec<T: AsRef<str>>(&mut self, name: T, values: &[i8]) {
+        expect(self.try_set_i8_vec(name, values))
+    }
+
+    pub fn try_set_i8_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[i8],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    pub fn set_i32_vec<T: AsRef<str>>(&mut self, name: T, values: &[i32]) {
+        expect(self.try_set_i32_vec(name, values))
+    }
+
+    pub fn try_set_i32_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[i32],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    pub fn set_u32_vec<T: AsRef<str>>(&mut self, name: T, values: &[u32]) {
+        expect(self.try_set_u32_vec(name, values))
+    }
+
+    pub fn try_set_u32_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[u32],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    pub fn set_i16_vec<T: AsRef<str>>(&mut self, name: T, values: &[i16]) {
+        expect(self.try_set_i16_vec(name, values))
+    }
+
+    pub fn try_set_i16_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[i16],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    pub fn set_u16_vec<T: AsRef<str>>(&mut self, name: T, values: &[u16]) {
+        expect(self.try_set_u16_vec(name, values))
+    }
+
+    pub fn try_set_u16_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[u16],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::{Blend, runtime::AccessError};
+    /// # fn main() {
+    ///     # let mut blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+    /// let location = blend.instances_with_code(*b"OB").next().unwrap().location();
+    /// let mut obj = blend.edit(&location);
+    ///
+    /// obj.set_f32_vec("loc", &[0.0, 1.0, 2.0]);
+    /// assert!(matches!(obj.try_set_f32_vec("loc", &[0.0]), Err(AccessError::ValueDoesNotFit { .. })));
+    /// // `data` points to the datablock of the object, which can't be overwritten with primitives
+    /// assert!(matches!(obj.try_set_f32_vec("data", &[0.0; 4]), Err(AccessError::WrongType { .. })));
+    /// # assert!(matches!(obj.try_set_f32_vec("parent", &[0.0; 4]), Err(AccessError::WrongType { .. })));
+    /// # assert!(matches!(obj.try_set_f32_vec("scale", &[0.0; 3]), Ok(())));
+    /// # assert!(matches!(obj.try_set_f32("loc", 0.0), Err(AccessError::WrongType { .. })));
+    /// # assert!(matches!(obj.try_set_f32("location", 0.0), Err(AccessError::NoSuchField { .. })));
+    /// # assert!(matches!(obj.get("id").try_set_char("name", '€'), Err(AccessError::ValueDoesNotFit { .. })));
+    /// # assert!(matches!(obj.get("id").try_set_char("name", 'é'), Err(AccessError::ValueDoesNotFit { .. })));
+    /// # assert!(matches!(obj.get("id").try_set_string("name", &"O".repeat(66)), Err(AccessError::ValueDoesNotFit { .. })));
+    /// # assert!(matches!(obj.try_get("loc").err(), Some(AccessError::WrongType { .. })));
+    /// # }
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if the field is not an array, or a pointer to a block, of primitives of the size of `f32`, or if it
+    ///   doesn't hold as many elements as `values`.
+    pub fn set_f32_vec<T: AsRef<str>>(&mut self, name: T, values: &[f32]) {
+        expect(self.try_set_f32_vec(name, values))
+    }
+
+    /// Same as `set_f32_vec`, but returns an error instead of panicking.
+    pub fn try_set_f32_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[f32],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    pub fn set_f64_vec<T: AsRef<str>>(&mut self, name: T, values: &[f64]) {
+        expect(self.try_set_f64_vec(name, values))
+    }
+
+    pub fn try_set_f64_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[f64],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    pub fn set_u64_vec<T: AsRef<str>>(&mut self, name: T, values: &[u64]) {
+        expect(self.try_set_u64_vec(name, values))
+    }
+
+    pub fn try_set_u64_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[u64],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    pub fn set_i64_vec<T: AsRef<str>>(&mut self, name: T, values: &[i64]) {
+        expect(self.try_set_i64_vec(name, values))
+    }
+
+    pub fn try_set_i64_vec<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        values: &[i64],
+    ) -> Result<(), AccessError> {
+        self.try_set_value_vec(name.as_ref(), values)
+    }
+
+    /// Writes `value` into a `char` array followed by a terminating zero. The rest of the array is zeroed.
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if the field is not a `char` array or if `value` and its terminating zero don't fit in it.
+    pub fn set_string<T: AsRef<str>>(&mut self, name: T, value: &str) {
+        expect(self.try_set_string(name, value))
+    }
+
+    /// Same as `set_string`, but returns an error instead of panicking.
+    pub fn try_set_string<T: AsRef<str>>(
+        &mut self,
+        name: T,
+        value: &str,
+    ) -> Result<(), AccessError> {
+        let name = name.as_ref();
+        let layout = Arc::clone(&self.location.layout);
+        let field = self.try_field(&layout, name)?;
+
+        match field.info {
+            FieldInfo::ValueArray { .. } if field.is_primitive && field.type_name == "char" => {
+                if value.len() >= field.data_len {
+                    return Err(AccessError::ValueDoesNotFit {
+                        field: String::from(name),
+                        value: format!("{:?}", value),
+                    });
+                }
+
+                let data = self.data_mut(field.data_start, field.data_len);
+                data[..value.len()].copy_from_slice(value.as_bytes());
+                data[value.len()..].fill(0);
+                Ok(())
+            }
+            _ => Err(AccessError::wrong_type(
+                String::from(name),
+                name,
+                field,
+                "a string",
+            )),
+        }
+    }
+
+    /// Used to edit fields which are structs, like `Instance::get`. Pointers can't be followed while editing, get the
+    /// location of the instance they point to instead.
+    ///
+    /// ## Panics
+    ///
+    /// * Panics if the field is not a struct stored inside this instance.
+    pub fn get<T: AsRef<str>>(&mut self, name: T) -> InstanceMut<'_> {
+        expect(self.try_get(name))
+    }
+
+    /// Same as `get`, but returns an error instead of panicking.
+    pub fn try_get<T: AsRef<str>>(&mut self, name: T) -> Result<InstanceMut<'_>, AccessError> {
+        let name = name.as_ref();
+        let layout = Arc::clone(&self.location.layout);
+        let field = self.try_field(&layout, name)?;
+
+        match field.info {
+            FieldInfo::Value if !field.is_primitive => {
+                let struct_index = self.blend.struct_index(field.type_index).ok_or_else(|| {
+                    AccessError::wrong_type(String::from(name), name, field, "a struct")
+                })?;
+
+                Ok(InstanceMut {
+                    location: InstanceLocation {
+                        offset: self.location.offset + field.data_start,
+                        layout: self
+                            .blend
+                            .try_struct_layout(struct_index)
+                            .map_err(|error| AccessError::InvalidStruct {
+                                field: String::from(name),
+                                error,
+                            })?,
+                        ..self.location.clone()
+                    },
+                    blend: &mut *self.blend,
+                })
+            }
+            _ => Err(AccessError::wrong_type(
+                String::from(name),
+                name,
+                field,
+                "a struct",
+            )),
+        }
+    }
+}
+
+/// Returns the data of `block`, copying it out of the file buffer if needed.
+fn block_data_mut(block: &mut Block) -> &mut [u8] {
+    match block.data_mut() {
+        Some(data) => data.data.to_mut(),
+        // Only ever called with the block of a location checked by `Blend::edit` or a block found by `block_at`,
+        // which both have data. The blocks can't change while an `InstanceMut` borrows the file.
+        None => unreachable!("the DNA1 block can't be edited"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocationError;
+    use crate::Blend;
+
+    fn blend() -> Blend {
+        Blend::from_path("examples/blend_files/3_5.blend").unwrap()
+    }
+
+    #[test]
+    fn edit_moved_block() {
+        let mut blend = blend();
+        let location = blend.instances_with_code(*b"OB").next().unwrap().location();
+
+        blend
+            .blend
+            .blocks_mut()
+            .swap(location.block_index, location.block_index + 1);
+        assert!(matches!(
+            blend.try_edit(&location),
+            Err(LocationError::BlockMoved { block_index }) if block_index == location.block_index
+        ));
+
+        blend.blend.blocks_mut().truncate(location.block_index);
+        assert!(matches!(
+            blend.try_edit(&location),
+            Err(LocationError::BlockMoved { .. })
+        ));
+    }
+
+    #[test]
+    fn edit_past_the_end_of_the_block() {
+        let mut blend = blend();
+        let mut location = blend.instances_with_code(*b"OB").next().unwrap().location();
+        assert!(blend.try_edit(&location).is_ok());
+
+        location.offset += 1;
+        assert!(matches!(
+            blend.try_edit(&location),
+            Err(LocationError::OutOfBounds { block_index }) if block_index == location.block_index
+        ));
+        let edit = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            blend.edit(&location);
+        }));
+        assert!(edit.is_err());
+    }
+
+    #[test]
+    fn edit_converted_file() {
+        let blend = blend();
+        let location = blend.instances_with_code(*b"OB").next().unwrap().location();
+        let header = blend.blend.header.clone();
+        let mut converted = Blend {
+            blend: blend
+                .blend
+                .convert_to(header.pointer_size, header.endianness)
+                .unwrap(),
+        };
+
+        assert!(matches!(
+            converted.try_edit(&location),
+            Err(LocationError::OtherFile)
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::parsers::{
+    dna_diff::FieldType,
+    layout::{FieldTemplate, LayoutError},
+};
+use std::{error::Error, fmt};
+
+/// Returned by the `try_[]` methods of `Instance` when a field can't be read the way it was asked to. The panicking
+/// methods panic with the message of this error.
+///
+/// `field` is the path to the field from the block the instance was read from, like `OB"Cube".data<Mesh>.totvert`. See
+/// `Instance::path`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessError {
+    /// The struct has no field called `name`, and no field `name` is an alias of. `path` is the path to the instance,
+    /// or `None` for an `InstanceMut`.
+    NoSuchField {
+        struct_name: String,
+        name: String,
+        path: Option<String>,
+    },
+    /// The field holds something else. `expected` is what the method reads and `found` is the declaration of the
+    /// field, like `float loc[3]`.
+    WrongType {
+        field: String,
+        expected: String,
+        found: String,
+    },
+    /// The field is a null pointer.
+    NullPointer { field: String },
+    /// The field is a pointer but the file has no block at its address.
+    DanglingPointer { field: String },
+    /// The field can't be iterated with `Instance::try_get_iter`.
+    NotAList { field: String },
+    /// The elements of the `ListBase` in the field link back to an element before reaching the last one. Only happens
+    /// with corrupt files.
+    CyclicList { field: String },
+    /// The field leads to a block whose struct can't be read, like a block whose header has a struct index missing
+    /// from the DNA. Only happens with corrupt files.
+    InvalidStruct { field: String, error: LayoutError },
+    /// The data of the instance is too short to hold the field. Only happens with corrupt files.
+    TruncatedData { field: String },
+    /// The field points to a datablock linked from a library which couldn't be opened or doesn't have it. Only
+    /// returned by instances created through a `BlendLibrarySet`.
+    MissingLinkedData { field: String, name: String },
+    /// The value given to a `try_set_[]` method of `InstanceMut` doesn't fit in the field, like an array with another
+    /// number of elements or a string longer than its `char` array. `value` describes the value.
+    ValueDoesNotFit { field: String, value: String },
+}
+
+impl AccessError {
+    /// Creates a `WrongType` error for the field `name`, described by `field`. `path` is the path to the field.
+    pub(crate) fn wrong_type(
+        path: String,
+        name: &str,
+        field: &FieldTemplate,
+        expected: impl Into<String>,
+    ) -> Self {
+        let field_type = FieldType {
+            type_name: field.type_name.clone(),
+            info: field.info.clone(),
+        };
+
+        AccessError::WrongType {
+            field: path,
+            expected: expected.into(),
+            found: field_type.declaration(name),
+        }
+    }
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessError::NoSuchField {
+                struct_name,
+                name,
+                path: Some(path),
+            } => write!(
+                f,
+                "struct {} has no field '{}' at '{}'",
+                struct_name, name, path
+            ),
+            AccessError::NoSuchField {
+                struct_name,
+                name,
+                path: None,
+            } => write!(f, "struct {} has no field '{}'", struct_name, name),
+            AccessError::WrongType {
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "field '{}' is declared as `{}` and can't be read as {}",
+                field, found, expected
+            ),
+            AccessError::NullPointer { field } => write!(f, "field '{}' is a null pointer", field),
+            AccessError::DanglingPointer { field } => {
+                write!(f, "field '{}' doesn't point to a valid block", field)
+            }
+            AccessError::NotAList { field } => write!(f, "field '{}' is not a list", field),
+            AccessError::CyclicList { field } => write!(
+                f,
+                "the list in field '{}' loops before reaching its last element",
+                field
+            ),
+            AccessError::InvalidStruct { field, error } => {
+                write!(
+                    f,
+                    "the struct of field '{}' can't be read: {}",
+                    field, error
+                )
+            }
+            AccessError::TruncatedData { field } => {
+                write!(f, "the data of field '{}' is truncated", field)
+            }
+            AccessError::MissingLinkedData { field, name } => write!(
+                f,
+                "could not find the linked datablock '{}' of field '{}' in its library",
+                name, field
+            ),
+            AccessError::ValueDoesNotFit { field, value } => {
+                write!(f, "{} doesn't fit in field '{}'", value, field)
+            }
+        }
+    }
+}
+
+impl Error for AccessError {}
@@ -0,0 +1,244 @@
+use super::{AccessError, Instance};
+use crate::parsers::primitive::BlendPrimitive;
+use std::{error::Error, fmt};
+
+/// A Rust type which can be read from an `Instance`. With the `derive` feature it can be derived for structs, reading
+/// each field with `FromField`:
+///
+/// ```text
+/// #[derive(FromInstance)]
+/// struct Object {
+///     #[blend(path = "id.name")]
+///     name: String,
+///     #[blend(field = "loc")]
+///     location: [f32; 3],
+///     #[blend(field = "data")]
+///     mesh: Option<Mesh>,
+///     #[blend(field = "mat")]
+///     materials: Vec<Material>,
+/// }
+/// ```
+///
+/// Fields are read from the field of the instance with the same name unless they are renamed with
+/// `#[blend(field = "...")]` or read through other structs with `#[blend(path = "...")]`, where the path is a list of
+/// field names separated by dots. The struct of the instance isn't checked unless the Rust struct has a
+/// `#[blend(type = "...")]` attribute naming it.
+///
+/// ## Example
+///
+/// Implementing the trait by hand:
+///
+/// ```rust
+/// # use blend::{Blend, Instance, runtime::{FromField, FromInstance, FromInstanceError}};
+/// struct Object {
+///     name: String,
+///     location: [f32; 3],
+/// }
+///
+/// impl FromInstance for Object {
+///     fn from_instance(instance: &Instance) -> Result<Self, FromInstanceError> {
+///         Ok(Object {
+///             name: String::from_path(instance, "id.name").map_err(|err| err.in_field("name"))?,
+///             location: <[f32; 3]>::from_path(instance, "loc").map_err(|err| err.in_field("location"))?,
+///         })
+///     }
+/// }
+///
+/// # fn main() {
+///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+/// for obj in blend.instances_with_code(*b"OB") {
+///     let obj = Object::from_instance(&obj).unwrap();
+///     println!("\"{}\" at {:?}", obj.name, obj.location);
+/// }
+/// # }
+/// ```
+pub trait FromInstance: Sized {
+    /// Reads the value from `instance`.
+    fn from_instance(instance: &Instance) -> Result<Self, FromInstanceError>;
+}
+
+/// A Rust type which can be read from a field of an `Instance`. Implemented for:
+///
+/// * The primitives of `BlendPrimitive`, read with `Instance::try_get_value`.
+/// * Arrays of them, like `[f32; 3]` and `[[f32; 4]; 4]`, which must have as many values as the field.
+/// * `String`, read with `Instance::try_get_string`.
+/// * Types implementing `FromInstance`, read from struct fields and pointers to structs.
+/// * `Vec<T>` where `T` implements `FromInstance`, read from anything `Instance::try_get_iter` can iterate.
+/// * `Option<T>`, which is `None` when the field, or a field in the path leading to it, is a null pointer or doesn't
+///   point to a block.
+pub trait FromField: Sized {
+    /// Reads the value from the field `name` of `instance`.
+    fn from_field(instance: &Instance, name: &str) -> Result<Self, FromInstanceError>;
+
+    /// Reads the value from the field at the end of `path`, a list of field names separated by dots like `id.name`.
+    fn from_path(instance: &Instance, path: &str) -> Result<Self, FromInstanceError> {
+        let (parents, name) = match path.rfind('.') {
+            Some(index) => (&path[..index], &path[index + 1..]),
+            None => return Self::from_field(instance, path),
+        };
+
+        let mut instance = instance.clone();
+        for parent in parents.split('.') {
+            instance = instance.try_get(parent)?;
+        }
+
+        Self::from_field(&instance, name)
+    }
+}
+
+/// Returned by `FromInstance::from_instance` when a field can't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FromInstanceError {
+    /// The path to the field of the Rust type which couldn't be read, like `mesh.materials[1].name`. Empty if the
+    /// error comes from the instance the value was read from.
+    pub field: String,
+    /// Why the field couldn't be read.
+    pub error: AccessError,
+}
+
+impl FromInstanceError {
+    /// Prepends the name of the field containing the value which couldn't be read to `field`. Used by the
+    /// implementations of `FromInstance`.
+    pub fn in_field(mut self, name: &str) -> Self {
+        self.field = match self.field.chars().next() {
+            None => String::from(name),
+            Some('[') => format!("{}{}", name, self.field),
+            Some(_) => format!("{}.{}", name, self.field),
+        };
+        self
+    }
+
+    /// Prepends the index of the element containing the value which couldn't be read to `field`.
+    fn in_element(mut self, index: usize) -> Self {
+        self.field = match self.field.chars().next() {
+            None | Some('[') => format!("[{}]{}", index, self.field),
+            Some(_) => format!("[{}].{}", index, self.field),
+        };
+        self
+    }
+
+    /// Returns true if the value couldn't be read because a pointer is null or doesn't point to a block.
+    fn is_missing(&self) -> bool {
+        self.field.is_empty()
+            && matches!(
+                self.error,
+                AccessError::NullPointer { .. } | AccessError::DanglingPointer { .. }
+            )
+    }
+}
+
+impl From<AccessError> for FromInstanceError {
+    fn from(error: AccessError) -> Self {
+        FromInstanceError {
+            field: String::new(),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for FromInstanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}", self.error)
+        } else {
+            write!(f, "could not read '{}': {}", self.field, self.error)
+        }
+    }
+}
+
+impl Error for FromInstanceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+macro_rules! impl_from_field_for_primitive {
+    ($($primitive:ty),*) => {
+        $(
+            impl FromField for $primitive {
+                fn from_field(instance: &Instance, name: &str) -> Result<Self, FromInstanceError> {
+                    Ok(instance.try_get_value(name)?)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_field_for_primitive!(char, i8, u8, bool, u16, i16, i32, u32, f32, f64, u64, i64);
+
+impl<T: BlendPrimitive, const N: usize> FromField for [T; N] {
+    fn from_field(instance: &Instance, name: &str) -> Result<Self, FromInstanceError> {
+        let values = values(instance, name, N)?;
+        Ok(std::array::from_fn(|i| values[i]))
+    }
+}
+
+impl<T: BlendPrimitive, const N: usize, const M: usize> FromField for [[T; M]; N] {
+    fn from_field(instance: &Instance, name: &str) -> Result<Self, FromInstanceError> {
+        let values = values(instance, name, N * M)?;
+        Ok(std::array::from_fn(|i| {
+            std::array::from_fn(|j| values[i * M + j])
+        }))
+    }
+}
+
+/// Reads the field `name` as `len` primitives.
+fn values<T: BlendPrimitive>(
+    instance: &Instance,
+    name: &str,
+    len: usize,
+) -> Result<Vec<T>, AccessError> {
+    let values = instance.try_get_vec::<T>(name)?;
+
+    if values.len() == len {
+        Ok(values)
+    } else {
+        let field = instance.try_field(name)?;
+        Err(instance.wrong_type(name, field, format!("{} values", len)))
+    }
+}
+
+impl FromField for String {
+    fn from_field(instance: &Instance, name: &str) -> Result<Self, FromInstanceError> {
+        Ok(instance.try_get_string(name)?)
+    }
+}
+
+impl<T: FromInstance> FromField for T {
+    fn from_field(instance: &Instance, name: &str) -> Result<Self, FromInstanceError> {
+        T::from_instance(&instance.try_get(name)?)
+    }
+}
+
+impl<T: FromInstance> FromField for Vec<T> {
+    fn from_field(instance: &Instance, name: &str) -> Result<Self, FromInstanceError> {
+        instance
+            .try_get_iter(name)?
+            .enumerate()
+            .map(|(index, element)| {
+                element
+                    .map_err(FromInstanceError::from)
+                    .and_then(|element| T::from_instance(&element))
+                    .map_err(|err| err.in_element(index))
+            })
+            .collect()
+    }
+}
+
+impl<T: FromField> FromField for Option<T> {
+    fn from_field(instance: &Instance, name: &str) -> Result<Self, FromInstanceError> {
+        match T::from_field(instance, name) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.is_missing() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn from_path(instance: &Instance, path: &str) -> Result<Self, FromInstanceError> {
+        match T::from_path(instance, path) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.is_missing() => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use super::{Blend, Instance, PointerInfo, RAW_DATA_STRUCT_INDEX};
+use crate::parsers::{
+    blend::{Block, RawBlend},
+    field::FieldInfo,
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    num::NonZeroU64,
+};
+
+/// Writes the blocks of a file and the pointers between them as a graph in the DOT language, which Graphviz can draw.
+/// Nodes are labelled with the type, the ID name and the address of their block and edges with the pointer field they
+/// come from, like `data` or `mat[0]`.
+///
+/// Only datablocks (blocks with a code, like objects and meshes) are written by default, and pointers to subsidiary
+/// blocks are ignored. Arrays of pointers are followed to the blocks they point to, so `Mesh.mat` still leads to the
+/// materials of a mesh.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::{Blend, runtime::DotExporter};
+/// # fn main() {
+///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+/// let exporter = DotExporter {
+///     codes: vec![*b"OB", *b"ME", *b"MA"],
+///     ..DotExporter::default()
+/// };
+///
+/// let mut dot = String::new();
+/// exporter.export(&blend, &mut dot).unwrap();
+/// println!("{}", dot);
+/// # assert!(dot.starts_with("digraph blend {"));
+/// # assert!(dot.contains("\\nOBCube\\n"));
+/// # assert!(dot.contains("[label=\"mat[0]\"]"));
+/// # assert!(!dot.contains("\\nSCScene\\n"));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DotExporter {
+    /// The codes of the datablocks to write, like `*b"OB"`. Every datablock is written when empty.
+    pub codes: Vec<[u8; 2]>,
+    /// Whether the subsidiary blocks the written blocks point to are written too, along with the blocks they point
+    /// to in turn. Blocks of raw data, like arrays of floats, are never written.
+    pub subsidiary_blocks: bool,
+}
+
+impl DotExporter {
+    /// Writes the graph of `blend` to `out`.
+    pub fn export(&self, blend: &Blend, out: &mut impl fmt::Write) -> fmt::Result {
+        let raw = &blend.blend;
+        let mut queue = raw
+            .blocks
+            .iter()
+            .filter(|block| match block {
+                Block::Principal { code, .. } => self.keeps(*code),
+                _ => false,
+            })
+            .collect::<VecDeque<_>>();
+        let mut written = queue
+            .iter()
+            .filter_map(|block| address(block))
+            .collect::<HashSet<_>>();
+
+        writeln!(out, "digraph blend {{")?;
+        writeln!(out, "    node [shape=box];")?;
+
+        while let Some(block) = queue.pop_front() {
+            let source = match address(block) {
+                Some(address) => address,
+                None => continue,
+            };
+            let elements = elements(raw, block);
+
+            let name = match block {
+                Block::Principal { .. } => elements
+                    .first()
+                    .and_then(|element| element.try_get("id").ok())
+                    .and_then(|id| id.try_get_string("name").ok()),
+                _ => None,
+            };
+            let type_name = elements.first().map_or("?", |element| element.type_name);
+            let shape = match block {
+                Block::Principal { .. } => "",
+                _ => ", shape=ellipse",
+            };
+
+            match name {
+                Some(name) => writeln!(
+                    out,
+                    "    \"{}\" [label=\"{}\\n{}\\n@{}\"{}];",
+                    source,
+                    type_name,
+                    escape(&name),
+                    source,
+                    shape
+                )?,
+                None => writeln!(
+                    out,
+                    "    \"{}\" [label=\"{}\\n@{}\"{}];",
+                    source, type_name, source, shape
+                )?,
+            }
+
+            let mut pointers = Vec::new();
+            for element in &elements {
+                collect_pointers(raw, element, "", &mut pointers);
+            }
+
+            let mut edges = HashSet::new();
+            for (label, target) in pointers {
+                let keep = match target {
+                    Block::Principal { code, .. } => self.keeps(*code),
+                    Block::Subsidiary { dna_index, .. } => {
+                        self.subsidiary_blocks && *dna_index != RAW_DATA_STRUCT_INDEX
+                    }
+                    _ => false,
+                };
+                let target_address = match address(target) {
+                    Some(address) if keep => address,
+                    _ => continue,
+                };
+
+                if written.insert(target_address) {
+                    queue.push_back(target);
+                }
+
+                if edges.insert((target_address, label.clone())) {
+                    writeln!(
+                        out,
+                        "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                        source,
+                        target_address,
+                        escape(&label)
+                    )?;
+                }
+            }
+        }
+
+        writeln!(out, "}}")
+    }
+
+    /// Returns true if the datablocks with `code` are written.
+    fn keeps(&self, code: [u8; 2]) -> bool {
+        self.codes.is_empty() || self.codes.contains(&code)
+    }
+}
+
+fn address(block: &Block) -> Option<NonZeroU64> {
+    match block {
+        Block::Principal { memory_address, .. } | Block::Subsidiary { memory_address, .. } => {
+            Some(*memory_address)
+        }
+        _ => None,
+    }
+}
+
+/// Returns an `Instance` for each struct saved in `block`. Blocks of raw data have none.
+fn elements<'a>(raw: &'a RawBlend, block: &'a Block) -> Vec<Instance<'a>> {
+    let (data, dna_index) = match block {
+        Block::Principal {
+            data, dna_index, ..
+        }
+        | Block::Subsidiary {
+            data, dna_index, ..
+        } => (data, *dna_index),
+        _ => return Vec::new(),
+    };
+
+    if dna_index == RAW_DATA_STRUCT_INDEX || data.count == 0 {
+        return Vec::new();
+    }
+
+    let layout = match raw.try_struct_layout(dna_index) {
+        Ok(layout) => layout,
+        // The struct index in the header of the block is corrupt
+        Err(_) => return Vec::new(),
+    };
+    let len = data.data.len() / data.count;
+
+    data.data
+        .chunks_exact(len.max(1))
+        .map(|element| Instance::new_raw(raw, block, element, layout.clone()))
+        .collect()
+}
+
+/// Finds the blocks the pointers of `instance` lead to, including the pointers of the structs it contains. `prefix` is
+/// the path to `instance` from the struct of the block.
+fn collect_pointers<'a>(
+    raw: &'a RawBlend,
+    instance: &Instance<'a>,
+    prefix: &str,
+    pointers: &mut Vec<(String, &'a Block)>,
+) {
+    let pointer_size = raw.header.pointer_size.bytes_num();
+
+    for (name, field) in instance.layout.fields.iter() {
+        let label = format!("{}{}", prefix, name);
+
+        match field.info {
+            FieldInfo::Value if !field.is_primitive => {
+                if let Ok(value) = instance.try_get(name) {
+                    collect_pointers(raw, &value, &format!("{}.", label), pointers);
+                }
+            }
+            FieldInfo::ValueArray { .. } if !field.is_primitive => {
+                if let Ok(values) = instance.try_get_iter(name) {
+                    for (index, value) in values.enumerate() {
+                        if let Ok(value) = value {
+                            let prefix = format!("{}[{}].", label, index);
+                            collect_pointers(raw, &value, &prefix, pointers);
+                        }
+                    }
+                }
+            }
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } => {
+                if let PointerInfo::Block(block) = instance.get_ptr(field) {
+                    pointers.push((label, block));
+                }
+            }
+            // Arrays of pointers lead to the blocks they point to, not to the array itself
+            FieldInfo::Pointer { .. } | FieldInfo::PointerArray { .. } => {
+                let data = match field.info {
+                    FieldInfo::PointerArray { .. } => instance.try_field_data(name, field).ok(),
+                    _ => match instance.get_ptr(field) {
+                        PointerInfo::Block(Block::Principal { data, .. })
+                        | PointerInfo::Block(Block::Subsidiary { data, .. }) => {
+                            Some(&data.data[..])
+                        }
+                        _ => None,
+                    },
+                };
+                let data = match data {
+                    Some(data) => data,
+                    None => continue,
+                };
+
+                for (index, pointer) in data.chunks_exact(pointer_size).enumerate() {
+                    let target = instance
+                        .parse_ptr_address(pointer)
+                        .and_then(|address| raw.block_at(address));
+
+                    if let Some(target) = target {
+                        pointers.push((format!("{}[{}]", label, index), target));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Escapes the quotes and backslashes of a DOT string.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,502 @@
+use super::{Blend, Instance, InstanceDataFormat};
+use crate::parsers::{blend::RawBlend, BlendParseError};
+use std::{
+    collections::HashMap,
+    fs,
+    num::NonZeroU64,
+    path::{Path, PathBuf},
+    ptr,
+    sync::OnceLock,
+};
+
+/// A .blend file linked by the root file of a `BlendLibrarySet`. The file is only opened the first time a link into it
+/// is followed.
+pub struct LinkedLibrary {
+    path: PathBuf,
+    blend: OnceLock<Blend>,
+    /// The address of the block of every ID in the library, by block code and ID name. Built the first time a link
+    /// into the library is resolved.
+    ids: OnceLock<HashMap<([u8; 2], String), NonZeroU64>>,
+}
+
+impl LinkedLibrary {
+    /// The path of the library, resolved from the path saved in its `Library` struct.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if the library was already opened.
+    pub fn is_loaded(&self) -> bool {
+        self.blend.get().is_some()
+    }
+
+    /// Opens the library if it wasn't opened yet. A library that failed to load is opened again the next time.
+    pub fn blend(&self) -> Result<&Blend, BlendParseError> {
+        if let Some(blend) = self.blend.get() {
+            return Ok(blend);
+        }
+
+        let blend = Blend::from_path(&self.path)?;
+        Ok(self.blend.get_or_init(|| blend))
+    }
+
+    /// Returns the ID called `name` with the code `code`, opening the library if needed. The ID is a placeholder if
+    /// the library links it from another library. The first one wins if the library has more than one.
+    fn find_id(&self, code: [u8; 2], name: String) -> Option<Instance<'_>> {
+        let blend = self.blend().ok()?;
+        let ids = self.ids.get_or_init(|| {
+            let mut ids = HashMap::new();
+
+            for instance in blend.root_instances() {
+                // Placeholders of IDs the library links in turn are an `ID` in a `"ID"` block, the code of every ID is
+                // taken from its name instead
+                let name = if is_placeholder(&instance) {
+                    instance.try_get_string("name")
+                } else {
+                    instance
+                        .try_get("id")
+                        .and_then(|id| id.try_get_string("name"))
+                };
+
+                if let Ok(name) = name {
+                    if let [a, b, ..] = *name.as_bytes() {
+                        ids.entry(([a, b], name))
+                            .or_insert_with(|| instance.memory_address());
+                    }
+                }
+            }
+
+            ids
+        });
+
+        blend.instance_at(*ids.get(&(code, name))?)
+    }
+}
+
+/// A .blend file together with the libraries it links data from.
+///
+/// When a file links an ID (an object, a mesh, a material...) from another file, Blender doesn't save the data of the
+/// ID, only a placeholder: an `ID` struct in a block with the code `"ID"` whose `lib` field points to the `Library`
+/// (`"LI"`) the ID comes from. Instances created through a `BlendLibrarySet` replace these placeholders with the real
+/// datablock found in the library, so `Instance::get` and `Instance::get_iter` can follow a pointer into another file.
+///
+/// Library paths starting with `//` are relative to the file linking them. Blender saves every library used by a file,
+/// including the ones linked indirectly through other libraries, so the root file is enough to know all of them.
+/// Instances found in a library follow its links too, and a placeholder whose library links the datablock from
+/// another library is followed through every library of the chain. Libraries that are not saved in the root file
+/// are never opened.
+///
+/// ## Example
+///
+/// ```no_run
+/// # use blend::BlendLibrarySet;
+/// # fn main() {
+/// let set = BlendLibrarySet::from_path("scene_with_links.blend").expect("error loading blend file");
+///
+/// for obj in set.instances_with_code(*b"OB") {
+///     let data = obj.get("data");
+///     println!("{} uses {}", obj.get("id").get_string("name"), data.get("id").get_string("name"));
+/// }
+/// # }
+/// ```
+pub struct BlendLibrarySet {
+    root: Blend,
+    root_path: PathBuf,
+    libraries: Vec<LinkedLibrary>,
+}
+
+impl BlendLibrarySet {
+    /// Loads the blend file at `path` and finds the libraries it links. The libraries themselves are not opened yet.
+    pub fn from_path<T: AsRef<Path>>(path: T) -> Result<BlendLibrarySet, BlendParseError> {
+        let root_path = normalize_path(path.as_ref().to_path_buf());
+        let root = Blend::from_path(&root_path)?;
+
+        let libraries = root
+            .instances_with_code(*b"LI")
+            .filter_map(|library| library_path(&root_path, &library))
+            .map(|path| LinkedLibrary {
+                path,
+                blend: OnceLock::new(),
+                ids: OnceLock::new(),
+            })
+            .collect();
+
+        Ok(BlendLibrarySet {
+            root,
+            root_path,
+            libraries,
+        })
+    }
+
+    /// The file the set was loaded from.
+    pub fn root(&self) -> &Blend {
+        &self.root
+    }
+
+    /// Every library linked by the root file, directly or indirectly.
+    pub fn libraries(&self) -> &[LinkedLibrary] {
+        &self.libraries
+    }
+
+    /// Same as `Blend::root_instances` on the root file, but the instances follow links into libraries.
+    pub fn root_instances(&self) -> impl Iterator<Item = Instance<'_>> {
+        self.root
+            .root_instances()
+            .map(move |instance| instance.with_libraries(Some(self)))
+    }
+
+    /// Same as `Blend::instances_with_code` on the root file, but the instances follow links into libraries.
+    pub fn instances_with_code(&self, search_code: [u8; 2]) -> impl Iterator<Item = Instance<'_>> {
+        self.root
+            .instances_with_code(search_code)
+            .map(move |instance| instance.with_libraries(Some(self)))
+    }
+
+    /// Returns the datablock a linked placeholder stands for, opening its library if needed. When the library links
+    /// the datablock from another library in turn, its placeholder is resolved too. Returns `None` if `placeholder`
+    /// is not a placeholder, if a library can't be loaded, if a library has no ID with the same name or if the
+    /// libraries link the datablock from each other in a cycle.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use blend::BlendLibrarySet;
+    /// # fn main() {
+    /// let set = BlendLibrarySet::from_path("scene_with_links.blend").expect("error loading blend file");
+    /// let placeholder = set.root().instances_with_code(*b"ID").next().unwrap();
+    ///
+    /// let camera = set.resolve(&placeholder).unwrap();
+    /// assert_eq!(camera.get("id").get_string("name"), placeholder.get_string("name"));
+    /// # }
+    /// ```
+    pub fn resolve<'a>(&'a self, placeholder: &Instance<'a>) -> Option<Instance<'a>> {
+        let mut resolved = self.resolve_link(placeholder)?;
+
+        // Each link leads into a library, a chain longer than the number of libraries can only be a cycle
+        for _ in 0..self.libraries.len() {
+            if !is_placeholder(&resolved) {
+                return Some(resolved);
+            }
+            resolved = self.resolve_link(&resolved)?;
+        }
+
+        None
+    }
+
+    /// Returns the ID a placeholder stands for in its library, which may be a placeholder itself.
+    fn resolve_link<'a>(&'a self, placeholder: &Instance<'a>) -> Option<Instance<'a>> {
+        if !is_placeholder(placeholder) {
+            return None;
+        }
+
+        let linking_path = self.path_of(placeholder.blend)?;
+        let library = placeholder.try_get("lib").ok()?;
+        let library_path = library_path(linking_path, &library)?;
+
+        let library = self.libraries.iter().find(|l| l.path == library_path)?;
+
+        let name = placeholder.try_get_string("name").ok()?;
+        let code = match name.as_bytes() {
+            [a, b, ..] => [*a, *b],
+            _ => return None,
+        };
+
+        library
+            .find_id(code, name)
+            .map(|instance| instance.with_libraries(Some(self)))
+    }
+
+    /// Returns the path of the file `blend` was loaded from, if it belongs to this set.
+    fn path_of(&self, blend: &RawBlend) -> Option<&Path> {
+        if ptr::eq(blend, &self.root.blend) {
+            return Some(&self.root_path);
+        }
+
+        self.libraries
+            .iter()
+            .find(|library| match library.blend.get() {
+                Some(loaded) => ptr::eq(blend, &loaded.blend),
+                None => false,
+            })
+            .map(|library| library.path())
+    }
+}
+
+/// Returns true if `instance` is the `ID` Blender saves in place of a linked datablock.
+pub(crate) fn is_placeholder(instance: &Instance) -> bool {
+    matches!(instance.data, InstanceDataFormat::Block(_))
+        && instance.data.code() == Some(*b"ID\0\0")
+        && instance.type_name == "ID"
+}
+
+/// Resolves the path saved in the `Library` struct `library`, which was loaded from the file at `linking_path`.
+/// Returns `None` if the struct has no path.
+fn library_path(linking_path: &Path, library: &Instance) -> Option<PathBuf> {
+    // Blender uses `\` as the separator on Windows, even in relative paths
+    let saved_path = library.try_get_string("filepath").ok()?.replace('\\', "/");
+
+    let path = match saved_path.strip_prefix("//") {
+        Some(relative) => linking_path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(relative),
+        None => PathBuf::from(saved_path),
+    };
+
+    Some(normalize_path(path))
+}
+
+/// Makes the paths of the same file compare equal when it can be found on disk.
+fn normalize_path(path: PathBuf) -> PathBuf {
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::BlendLibrarySet;
+    use crate::{
+        parsers::blend::{Block, BlockData, RawBlend},
+        Blend,
+    };
+    use std::{
+        fs,
+        num::NonZeroU64,
+        path::{Path, PathBuf},
+    };
+
+    /// A directory in the temporary directory of the system, removed with its files when dropped.
+    pub(crate) struct TempDir(PathBuf);
+
+    impl TempDir {
+        pub(crate) fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("blend_{}_{}", name, std::process::id()));
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+
+        pub(crate) fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Writes `linked_3_5.blend` to `dir` with a copy of `3_5.blend` next to it, and returns its path.
+    ///
+    /// The camera datablock of the 3.5 file is replaced by the placeholder Blender saves for linked IDs: a `"ID"` block
+    /// holding the `ID` of the camera, whose `lib` field points to a new `Library` block with the path `//3_5.blend`.
+    /// Everything else is left as is, so following the link from the camera object finds the original camera.
+    pub(crate) fn write_linked_file(dir: &Path) -> PathBuf {
+        let source_path = "examples/blend_files/3_5.blend";
+        fs::copy(source_path, dir.join("3_5.blend")).unwrap();
+
+        let blend = Blend::from_path(source_path).unwrap();
+        let raw = &blend.blend;
+        let camera = blend
+            .instances_with_code(*b"OB")
+            .find(|obj| obj.get("id").get_string("name") == "OBCamera")
+            .unwrap()
+            .get("data");
+        let camera_address = camera.memory_address();
+
+        let struct_index = |name: &str| {
+            let type_index = raw.dna.types.iter().position(|t| t.name == name).unwrap();
+            raw.struct_index(type_index).unwrap()
+        };
+        let library_index = struct_index("Library");
+        let id_index = struct_index("ID");
+        let library_layout = raw.struct_layout(library_index);
+        let id_layout = raw.struct_layout(id_index);
+
+        // The new block goes past the addresses of every data block
+        let last_address = raw
+            .blocks()
+            .iter()
+            .filter_map(|block| block.memory_address())
+            .max()
+            .unwrap()
+            .get();
+        let library_address = (last_address + 0x10000) & !0xf;
+
+        let mut library = vec![0u8; library_layout.size()];
+        write_bytes(
+            &mut library,
+            id_layout.fields["name"].data_start,
+            b"LI3_5.blend",
+        );
+        // `Library::filepath` is saved under its old name in the DNA
+        write_bytes(
+            &mut library,
+            library_layout.fields["name"].data_start,
+            b"//3_5.blend",
+        );
+
+        // The placeholder keeps the name of the camera and drops the pointers that only make sense in the library
+        let mut placeholder = camera.data.get(0, id_layout.size()).unwrap().to_vec();
+        write_bytes(
+            &mut placeholder,
+            id_layout.fields["lib"].data_start,
+            &library_address.to_le_bytes(),
+        );
+        for name in &[
+            "next",
+            "prev",
+            "newid",
+            "properties",
+            "override_library",
+            "orig_id",
+            "py_instance",
+            "library_weak_reference",
+        ] {
+            if let Some(field) = id_layout.fields.get(*name) {
+                write_bytes(&mut placeholder, field.data_start, &0u64.to_le_bytes());
+            }
+        }
+
+        let mut linked = RawBlend::from_path(source_path).unwrap();
+        let mut blocks = linked.blocks_mut();
+        blocks.retain(|block| {
+            !matches!(block, Block::Principal { memory_address, .. } if *memory_address == camera_address)
+        });
+        blocks.push(Block::Principal {
+            code: *b"LI",
+            memory_address: NonZeroU64::new(library_address).unwrap(),
+            dna_index: library_index,
+            data: BlockData {
+                data: library.into(),
+                count: 1,
+            },
+        });
+        blocks.push(Block::Principal {
+            code: *b"ID",
+            memory_address: camera_address,
+            dna_index: id_index,
+            data: BlockData {
+                data: placeholder.into(),
+                count: 1,
+            },
+        });
+        drop(blocks);
+
+        let path = dir.join("linked_3_5.blend");
+        linked.write_to(fs::File::create(&path).unwrap()).unwrap();
+        path
+    }
+
+    fn write_bytes(data: &mut [u8], start: usize, bytes: &[u8]) {
+        data[start..start + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Writes `root.blend` to `dir`, which links the camera from `middle.blend`, a copy of `linked_3_5.blend` linking
+    /// it from `3_5.blend` in turn. The root file also has the `Library` of `3_5.blend`, like Blender saves it.
+    fn write_library_chain(dir: &Path) {
+        let middle = dir.join("middle.blend");
+        fs::rename(write_linked_file(dir), &middle).unwrap();
+
+        let mut root = RawBlend::from_path(&middle).unwrap();
+        let (library_address, library_index, mut library) = root
+            .blocks()
+            .iter()
+            .find_map(|block| match block {
+                Block::Principal {
+                    code: [b'L', b'I'],
+                    memory_address,
+                    dna_index,
+                    data,
+                } => Some((memory_address.get(), *dna_index, data.data.to_vec())),
+                _ => None,
+            })
+            .unwrap();
+        let id_index = root
+            .blocks()
+            .iter()
+            .find_map(|block| match block {
+                Block::Principal {
+                    code: [b'I', b'D'],
+                    dna_index,
+                    ..
+                } => Some(*dna_index),
+                _ => None,
+            })
+            .unwrap();
+
+        let filepath = root.struct_layout(library_index).fields["name"].data_start;
+        write_bytes(&mut library, filepath, b"//middle.blend\0");
+        let middle_address = library_address + 0x1000;
+
+        let lib = root.struct_layout(id_index).fields["lib"].data_start;
+        let mut blocks = root.blocks_mut();
+        for block in blocks.iter_mut() {
+            if let Block::Principal {
+                code: [b'I', b'D'],
+                data,
+                ..
+            } = block
+            {
+                write_bytes(data.data.to_mut(), lib, &middle_address.to_le_bytes());
+            }
+        }
+        blocks.push(Block::Principal {
+            code: *b"LI",
+            memory_address: NonZeroU64::new(middle_address).unwrap(),
+            dna_index: library_index,
+            data: BlockData {
+                data: library.into(),
+                count: 1,
+            },
+        });
+        drop(blocks);
+
+        root.write_to(fs::File::create(dir.join("root.blend")).unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn follow_links() {
+        let dir = TempDir::new("linked");
+        let set = BlendLibrarySet::from_path(write_linked_file(dir.path())).unwrap();
+
+        assert!(set.root().instances_with_code(*b"CA").next().is_none());
+        assert!(!set.libraries()[0].is_loaded());
+
+        let camera = set
+            .instances_with_code(*b"OB")
+            .find(|obj| obj.get("id").get_string("name") == "OBCamera")
+            .unwrap();
+        assert_eq!(camera.get("data").type_name, "Camera");
+        assert_eq!(camera.get("data").get_f32("lens"), 50.0);
+        assert!(set.libraries()[0].is_loaded());
+    }
+
+    #[test]
+    fn resolve() {
+        let dir = TempDir::new("linked_resolve");
+        let set = BlendLibrarySet::from_path(write_linked_file(dir.path())).unwrap();
+        let placeholder = set.root().instances_with_code(*b"ID").next().unwrap();
+
+        let camera = set.resolve(&placeholder).unwrap();
+        assert_eq!(
+            camera.get("id").get_string("name"),
+            placeholder.get_string("name")
+        );
+        assert!(set.resolve(&camera).is_none());
+    }
+
+    #[test]
+    fn resolve_library_chain() {
+        let dir = TempDir::new("linked_chain");
+        write_library_chain(dir.path());
+
+        let chain = BlendLibrarySet::from_path(dir.path().join("root.blend")).unwrap();
+        let placeholder = chain.root().instances_with_code(*b"ID").next().unwrap();
+        let camera = chain.resolve(&placeholder).unwrap();
+
+        assert_eq!(camera.type_name, "Camera");
+        assert_eq!(camera.get_f32("lens"), 50.0);
+        assert!(chain.libraries().iter().all(|library| library.is_loaded()));
+    }
+}
@@ -0,0 +1,191 @@
+use super::Instance;
+use crate::parsers::blend::{Block, RawBlend};
+use std::{fmt, num::NonZeroU64, sync::Arc};
+
+/// The fields and list indices followed from a root block to reach an `Instance`, like
+/// `OB"Cube".data<Mesh>.mloop[12]`. Used in error messages. Instances accessed through an `Instance` share the path of
+/// their parent, so extending it doesn't copy it. Nothing is formatted until the path is displayed: the name of the
+/// root block is only read then, and the elements of a list keep their index next to the shared path of the list
+/// instead of allocating a node each.
+#[derive(Clone, Default)]
+pub(crate) struct InstancePath<'a> {
+    root: Option<PathRoot<'a>>,
+    last: Option<Arc<PathNode<'a>>>,
+    /// The index of the element of the list `last` ends with, if the path ends with one.
+    index: Option<usize>,
+}
+
+struct PathNode<'a> {
+    parent: Option<Arc<PathNode<'a>>>,
+    segment: PathSegment<'a>,
+}
+
+#[derive(Clone, Copy)]
+enum PathRoot<'a> {
+    /// A principal block, identified by its code and the name of its ID if it has one.
+    Block {
+        code: [u8; 2],
+        type_name: &'a str,
+        blend: &'a RawBlend,
+        block: &'a Block,
+    },
+    /// A block without a code, or one reached through `Blend::instance_at`.
+    Address {
+        type_name: &'a str,
+        address: NonZeroU64,
+    },
+    /// A block identified by a 4 letter code, like `GLOB`.
+    Code { code: [u8; 4], type_name: &'a str },
+}
+
+enum PathSegment<'a> {
+    /// A field. `pointee` is the type of the struct when the field is a pointer.
+    Field {
+        name: String,
+        pointee: Option<&'a str>,
+    },
+    /// An element of a list.
+    Index(usize),
+}
+
+impl<'a> InstancePath<'a> {
+    fn root(root: PathRoot<'a>) -> Self {
+        InstancePath {
+            root: Some(root),
+            last: None,
+            index: None,
+        }
+    }
+
+    fn push(&self, segment: PathSegment<'a>) -> Self {
+        let parent = match self.index {
+            Some(index) => Some(Arc::new(PathNode {
+                parent: self.last.clone(),
+                segment: PathSegment::Index(index),
+            })),
+            None => self.last.clone(),
+        };
+
+        InstancePath {
+            root: self.root,
+            last: Some(Arc::new(PathNode { parent, segment })),
+            index: None,
+        }
+    }
+
+    /// Starts a path at the principal block `block`, whose ID name is read when the path is displayed.
+    pub(crate) fn block(
+        code: [u8; 2],
+        type_name: &'a str,
+        blend: &'a RawBlend,
+        block: &'a Block,
+    ) -> Self {
+        InstancePath::root(PathRoot::Block {
+            code,
+            type_name,
+            blend,
+            block,
+        })
+    }
+
+    /// Starts a path at the block saved at `address`.
+    pub(crate) fn address(type_name: &'a str, address: NonZeroU64) -> Self {
+        InstancePath::root(PathRoot::Address { type_name, address })
+    }
+
+    /// Starts a path at a block with a 4 letter code.
+    pub(crate) fn code(code: [u8; 4], type_name: &'a str) -> Self {
+        InstancePath::root(PathRoot::Code { code, type_name })
+    }
+
+    /// Extends the path with the field `name`. `pointee` is the type of the struct the field points to, if it is a
+    /// pointer.
+    pub(crate) fn field(&self, name: &str, pointee: Option<&'a str>) -> Self {
+        self.push(PathSegment::Field {
+            name: String::from(name),
+            pointee,
+        })
+    }
+
+    /// Extends the path of a list with its element `index`.
+    pub(crate) fn index(&self, index: usize) -> Self {
+        match self.index {
+            None => InstancePath {
+                root: self.root,
+                last: self.last.clone(),
+                index: Some(index),
+            },
+            Some(_) => self.push(PathSegment::Index(index)),
+        }
+    }
+}
+
+/// Returns the name of the ID of a principal block without its code, like `Cube` for `OBCube`.
+fn id_name(blend: &RawBlend, block: &Block) -> Option<String> {
+    let dna_index = match block {
+        Block::Principal { dna_index, .. } => *dna_index,
+        _ => return None,
+    };
+
+    let name = Instance::new(blend, block, blend.try_struct_layout(dna_index).ok()?)
+        .try_get("id")
+        .and_then(|id| id.try_get_string("name"))
+        .ok()?;
+
+    Some(name.get(2..).map(String::from).unwrap_or(name))
+}
+
+impl fmt::Display for InstancePath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.root {
+            Some(PathRoot::Block {
+                code,
+                type_name,
+                blend,
+                block,
+            }) => match id_name(blend, block) {
+                Some(name) => write!(f, "{}{:?}", String::from_utf8_lossy(&code), name)?,
+                None => write!(f, "{}<{}>", String::from_utf8_lossy(&code), type_name)?,
+            },
+            Some(PathRoot::Address { type_name, address }) => {
+                write!(f, "<{}>@{}", type_name, address)?
+            }
+            Some(PathRoot::Code { code, type_name }) => {
+                write!(f, "{}<{}>", String::from_utf8_lossy(&code), type_name)?
+            }
+            None => {}
+        }
+
+        let mut segments = Vec::new();
+        let mut node = self.last.as_deref();
+
+        while let Some(n) = node {
+            segments.push(&n.segment);
+            node = n.parent.as_deref();
+        }
+
+        let mut is_first = self.root.is_none();
+
+        for segment in segments.into_iter().rev() {
+            match segment {
+                PathSegment::Field { name, pointee } => {
+                    if !is_first {
+                        write!(f, ".")?;
+                    }
+                    write!(f, "{}", name)?;
+                    if let Some(pointee) = pointee {
+                        write!(f, "<{}>", pointee)?;
+                    }
+                }
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+            is_first = false;
+        }
+
+        if let Some(index) = self.index {
+            write!(f, "[{}]", index)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,348 @@
+use super::{AccessError, Blend, Instance};
+use crate::parsers::{
+    field::FieldInfo,
+    layout::FieldTemplate,
+    primitive::{PrimitiveKind, PrimitiveValue},
+    query::{parse_query, Query, QueryParseError, QuerySegment, Selector},
+};
+use std::{iter, sync::Arc};
+
+/// A value returned by a query. Primitive fields are converted to the widest type of their kind, arrays of `char` are
+/// read as strings and arrays of other primitives are returned whole unless a selector is used.
+#[derive(Debug, Clone)]
+pub enum QueryValue<'a> {
+    Instance(Instance<'a>),
+    String(String),
+    /// Any integer field, `char` and `bool` included. An `i128` holds the values of every integer type, `uint64_t`
+    /// included.
+    Int(i128),
+    /// A `float` or `double` field.
+    Float(f64),
+    IntArray(Vec<i128>),
+    FloatArray(Vec<f64>),
+}
+
+impl<'a> QueryValue<'a> {
+    pub fn as_instance(&self) -> Option<&Instance<'a>> {
+        match self {
+            QueryValue::Instance(instance) => Some(instance),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            QueryValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_i128(&self) -> Option<i128> {
+        match self {
+            QueryValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            QueryValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i128_slice(&self) -> Option<&[i128]> {
+        match self {
+            QueryValue::IntArray(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64_slice(&self) -> Option<&[f64]> {
+        match self {
+            QueryValue::FloatArray(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the value is a string, an integer or a float equal to the literal `value` of a filter. Floats
+    /// read from a field of `kind` `F32` are compared to the literal parsed as an `f32`, since most literals (like
+    /// `0.1`) can't be represented exactly by one.
+    fn matches(&self, value: &str, kind: Option<PrimitiveKind>) -> bool {
+        match self {
+            QueryValue::String(string) => string == value,
+            QueryValue::Int(int) => value.parse::<i128>().is_ok_and(|v| v == *int),
+            QueryValue::Float(float) if kind == Some(PrimitiveKind::F32) => {
+                value.parse::<f32>().is_ok_and(|v| v == *float as f32)
+            }
+            QueryValue::Float(float) => value.parse::<f64>().is_ok_and(|v| v == *float),
+            _ => false,
+        }
+    }
+}
+
+type QueryResults<'a> = Box<dyn Iterator<Item = Result<QueryValue<'a>, AccessError>> + 'a>;
+
+impl Blend {
+    /// Runs a query over the root blocks of the file. Queries are a path of fields separated by dots, starting with the
+    /// code of the blocks to look at, each optionally followed by selectors between brackets:
+    ///
+    /// * `field` reads a field like `Instance::get` does: structs and pointers to structs are returned as instances,
+    ///   primitives as numbers and arrays of `char` as strings.
+    /// * `field[*]` reads every element of a list like `Instance::get_iter` does, or every value of a primitive array.
+    /// * `field[3]` reads a single element of a list.
+    /// * `field[path=value]` keeps the elements of a list for which the query `path` returns `value`. Write the value
+    ///   between quotes if it contains a `]`.
+    ///
+    /// Every block with the code is used when the first segment has no selector. Null pointers are skipped, other
+    /// errors are returned by the iterator.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let positions = blend
+    ///     .query("OB[id.name=OBCube].data.mvert[*].co")
+    ///     .expect("invalid query")
+    ///     .filter_map(|value| value.ok()?.as_f64_slice().map(<[f64]>::to_vec))
+    ///     .collect::<Vec<_>>();
+    /// # assert_eq!(positions.len(), 8);
+    /// # assert_eq!(blend.query("OB[0].id.name").unwrap().count(), 1);
+    /// # // Float fields are compared at their precision
+    /// # assert_eq!(blend.query("CA[lens=50]").unwrap().count(), 1);
+    /// # assert_eq!(blend.query("CA[clipsta=0.1]").unwrap().count(), 1);
+    /// # assert_eq!(blend.query("CA[ortho_scale=7.3142858]").unwrap().count(), 1);
+    /// # assert_eq!(blend.query("CA[clipsta=0.2]").unwrap().count(), 0);
+    /// # assert!(blend.query("OB[").is_err());
+    /// # }
+    /// ```
+    pub fn query(
+        &self,
+        query: &str,
+    ) -> Result<impl Iterator<Item = Result<QueryValue<'_>, AccessError>>, QueryParseError> {
+        let parsed = parse_query(query)?;
+        let root = &parsed.segments[0];
+
+        // The first segment is the code of the blocks
+        let code = match root.name.as_bytes() {
+            [a, b] => [*a, *b],
+            _ => {
+                return Err(QueryParseError {
+                    query: String::from(query),
+                    position: 0,
+                })
+            }
+        };
+
+        let segments: Arc<[QuerySegment]> = parsed.segments[1..].into();
+        let selectors = if root.selectors.is_empty() {
+            vec![Selector::All]
+        } else {
+            root.selectors.clone()
+        };
+        let roots = self
+            .instances_with_code(code)
+            .map(|instance| Ok(QueryValue::Instance(instance)));
+
+        Ok(select(Box::new(roots), &selectors)
+            .flat_map(move |value| evaluate_from(value, Arc::clone(&segments), 0)))
+    }
+}
+
+impl<'a> Instance<'a> {
+    /// Runs a query starting from the fields of this instance, like `data.mvert[*].co`. See `Blend::query` for the
+    /// syntax.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::Blend;
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").skip(1).next().unwrap();
+    /// for material in obj.query("mat[*].id.name").unwrap() {
+    ///     println!("{}", material.unwrap().as_str().unwrap());
+    /// }
+    /// # // Integers of every type are returned without wrapping
+    /// # let mut blend = blend;
+    /// # let location = blend.file_global().unwrap().location();
+    /// # blend.edit(&location).set_u64("build_commit_timestamp", u64::MAX);
+    /// # let global = blend.file_global().unwrap();
+    /// # let timestamp = global.query("build_commit_timestamp").unwrap().next().unwrap().unwrap();
+    /// # assert_eq!(timestamp.as_i128(), Some(i128::from(u64::MAX)));
+    /// # }
+    /// ```
+    pub fn query(
+        &self,
+        query: &str,
+    ) -> Result<impl Iterator<Item = Result<QueryValue<'a>, AccessError>>, QueryParseError> {
+        let query = parse_query(query)?;
+        Ok(evaluate(self.clone(), &query))
+    }
+}
+
+/// Evaluates `query` starting from `instance`.
+fn evaluate<'a>(instance: Instance<'a>, query: &Query) -> QueryResults<'a> {
+    evaluate_from(
+        Ok(QueryValue::Instance(instance)),
+        query.segments.clone().into(),
+        0,
+    )
+}
+
+/// Evaluates the segments of a query starting at `index` on `value`, the result of the previous segments.
+fn evaluate_from<'a>(
+    value: Result<QueryValue<'a>, AccessError>,
+    segments: Arc<[QuerySegment]>,
+    index: usize,
+) -> QueryResults<'a> {
+    let instance = match value {
+        Ok(QueryValue::Instance(instance)) if index < segments.len() => instance,
+        // Only instances have fields, anything else is a result
+        value => return Box::new(iter::once(value)),
+    };
+
+    let segment = &segments[index];
+
+    let values: QueryResults<'a> = if segment.selectors.is_empty() {
+        match field_value(&instance, &segment.name) {
+            Err(AccessError::NullPointer { .. }) => Box::new(iter::empty()),
+            value => Box::new(iter::once(value)),
+        }
+    } else {
+        match field_elements(&instance, &segment.name) {
+            Ok(elements) => select(elements, &segment.selectors),
+            Err(AccessError::NullPointer { .. }) => Box::new(iter::empty()),
+            Err(err) => Box::new(iter::once(Err(err))),
+        }
+    };
+
+    Box::new(values.flat_map(move |value| evaluate_from(value, Arc::clone(&segments), index + 1)))
+}
+
+/// Applies the selectors of a segment to the elements of a list.
+fn select<'a>(elements: QueryResults<'a>, selectors: &[Selector]) -> QueryResults<'a> {
+    selectors
+        .iter()
+        .fold(elements, |elements, selector| match selector {
+            Selector::All => elements,
+            Selector::Index(index) => Box::new(elements.skip(*index).take(1)),
+            Selector::Filter { path, value } => {
+                let path = path.clone();
+                let value = value.clone();
+
+                Box::new(elements.filter(move |element| match element {
+                    Ok(QueryValue::Instance(instance)) => filter_matches(instance, &path, &value),
+                    _ => false,
+                }))
+            }
+        })
+}
+
+/// Returns true if the query `path` returns the literal `value` from `instance`. The last segment of the path is
+/// evaluated on its own to know the type of the field it reads, see `QueryValue::matches`.
+fn filter_matches(instance: &Instance, path: &Query, value: &str) -> bool {
+    let (last, parents) = match path.segments.split_last() {
+        Some(segments) => segments,
+        None => return false,
+    };
+    let last: Arc<[QuerySegment]> = Arc::from(vec![last.clone()]);
+
+    evaluate_from(
+        Ok(QueryValue::Instance(instance.clone())),
+        parents.into(),
+        0,
+    )
+    .any(|parent| match parent {
+        Ok(QueryValue::Instance(parent)) => {
+            let kind = parent.try_field(&last[0].name).ok().and_then(|field| {
+                PrimitiveKind::from_field(field, parent.blend.header.pointer_size)
+            });
+
+            evaluate_from(Ok(QueryValue::Instance(parent)), Arc::clone(&last), 0)
+                .any(|result| result.is_ok_and(|v| v.matches(value, kind)))
+        }
+        _ => false,
+    })
+}
+
+/// Reads the field `name` of `instance` as a single value.
+fn field_value<'a>(instance: &Instance<'a>, name: &str) -> Result<QueryValue<'a>, AccessError> {
+    let field = instance.try_field(name)?;
+
+    match field.info {
+        FieldInfo::Value if field.is_primitive => primitive_value(instance, name),
+        FieldInfo::ValueArray { .. } if field.is_primitive && field.type_name == "char" => {
+            instance.try_get_string(name).map(QueryValue::String)
+        }
+        FieldInfo::ValueArray { .. }
+        | FieldInfo::Pointer {
+            indirection_count: 1,
+        } if field.is_primitive && field.type_name != "void" => {
+            primitive_array(instance, name, field)
+        }
+        _ => instance.try_get(name).map(QueryValue::Instance),
+    }
+}
+
+/// Reads every element of the field `name` of `instance`, which can be a list of structs or a primitive array.
+fn field_elements<'a>(
+    instance: &Instance<'a>,
+    name: &str,
+) -> Result<QueryResults<'a>, AccessError> {
+    let field = instance.try_field(name)?;
+
+    match field.info {
+        FieldInfo::ValueArray { .. }
+        | FieldInfo::Pointer {
+            indirection_count: 1,
+        } if field.is_primitive && field.type_name != "void" => {
+            let values: Vec<QueryValue<'a>> = match primitive_array(instance, name, field)? {
+                QueryValue::IntArray(values) => values.into_iter().map(QueryValue::Int).collect(),
+                QueryValue::FloatArray(values) => {
+                    values.into_iter().map(QueryValue::Float).collect()
+                }
+                _ => unreachable!("primitive_array only returns arrays"),
+            };
+
+            Ok(Box::new(values.into_iter().map(Ok)))
+        }
+        _ => Ok(Box::new(
+            instance
+                .try_get_iter(String::from(name))?
+                .map(|element| element.map(QueryValue::Instance)),
+        )),
+    }
+}
+
+fn primitive_value<'a>(instance: &Instance<'a>, name: &str) -> Result<QueryValue<'a>, AccessError> {
+    let value = instance.try_get_primitive(name)?;
+
+    Ok(match value.as_i128() {
+        Some(int) => QueryValue::Int(int),
+        None => QueryValue::Float(value.as_f64()),
+    })
+}
+
+fn primitive_array<'a>(
+    instance: &Instance<'a>,
+    name: &str,
+    field: &FieldTemplate,
+) -> Result<QueryValue<'a>, AccessError> {
+    let values = instance.try_get_primitives(name)?;
+    let is_float = PrimitiveKind::from_field(field, instance.blend.header.pointer_size)
+        .is_some_and(PrimitiveKind::is_float);
+
+    Ok(if is_float {
+        QueryValue::FloatArray(values.into_iter().map(PrimitiveValue::as_f64).collect())
+    } else {
+        QueryValue::IntArray(
+            values
+                .into_iter()
+                .filter_map(PrimitiveValue::as_i128)
+                .collect(),
+        )
+    })
+}
@@ -0,0 +1,634 @@
+use super::{c_string, AccessError, Instance, RAW_DATA_STRUCT_INDEX};
+use crate::parsers::{
+    blend::Block,
+    field::FieldInfo,
+    layout::FieldTemplate,
+    primitive::{PrimitiveKind, PrimitiveValue},
+};
+use serde::{
+    ser::{Error, SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use std::{cell::RefCell, collections::HashSet, num::NonZeroU64};
+
+/// Decides which pointers are written as objects when serializing an `Instance`, see `Instance::serializable`. Every
+/// other pointer is written as a reference to the address of its block, `{"@": address}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SerializeOptions {
+    /// How many pointers can be followed from the serialized instance. `Some(0)` writes every pointer as a reference
+    /// and `None` follows pointers until every reachable block was written once.
+    pub max_depth: Option<usize>,
+    /// Whether pointers to other datablocks (blocks with a code, like the mesh of an object) are followed. They are
+    /// written as references by default, like the `Display` implementation of `Instance` does.
+    pub inline_datablocks: bool,
+}
+
+/// An `Instance` and the options used to serialize it. Returned by `Instance::serializable`.
+#[derive(Debug, Clone)]
+pub struct SerializableInstance<'i, 'a> {
+    instance: &'i Instance<'a>,
+    options: SerializeOptions,
+}
+
+impl<'a> Instance<'a> {
+    /// Serializes this `Instance` with `options` instead of the default ones. Structs are written as maps of their
+    /// fields (padding excluded), primitive arrays as arrays nested like their dimensions, `char` arrays as strings
+    /// and lists (`ListBase`, arrays of pointers, etc) as arrays of their elements. Blocks written as objects start
+    /// with their address in the `"@"` entry (arrays of structs are written as arrays, without it), so the references
+    /// written for pointers can be resolved. A block is only written once, the pointers found after that are
+    /// references, which also breaks the cycles of the file.
+    ///
+    /// Only available with the `serde` feature.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// # use blend::{Blend, runtime::SerializeOptions};
+    /// # fn main() {
+    ///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+    /// let obj = blend.instances_with_code(*b"OB").next().unwrap();
+    /// let options = SerializeOptions {
+    ///     max_depth: Some(1),
+    ///     inline_datablocks: true,
+    /// };
+    /// let json = serde_json::to_value(obj.serializable(options)).unwrap();
+    ///
+    /// assert_eq!(json["id"]["name"], "OBCamera");
+    /// assert_eq!(json["data"]["id"]["name"], "CACamera");
+    /// # assert!(json["data"]["@"].is_u64());
+    /// # let json = serde_json::to_value(&obj).unwrap();
+    /// # assert_eq!(json["data"].as_object().unwrap().len(), 1);
+    /// # assert_eq!(json["loc"].as_array().unwrap().len(), 3);
+    /// # }
+    /// ```
+    pub fn serializable(&self, options: SerializeOptions) -> SerializableInstance<'_, 'a> {
+        SerializableInstance {
+            instance: self,
+            options,
+        }
+    }
+}
+
+/// Serializes the `Instance` with the default `SerializeOptions`, see `Instance::serializable`.
+impl Serialize for Instance<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.serializable(SerializeOptions::default())
+            .serialize(serializer)
+    }
+}
+
+impl Serialize for SerializableInstance<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let context = Context {
+            options: self.options,
+            visited: RefCell::new(HashSet::new()),
+        };
+        let address = self.instance.data.memory_address();
+
+        if let Some(address) = address {
+            context.visited.borrow_mut().insert(address);
+        }
+
+        Node {
+            context: &context,
+            instance: self.instance,
+            address,
+            depth: 0,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// The state shared by the whole serialization: the blocks already written.
+struct Context {
+    options: SerializeOptions,
+    visited: RefCell<HashSet<NonZeroU64>>,
+}
+
+impl Context {
+    /// Returns true if the block at `address` should be written as an object by a pointer followed at `depth`, and
+    /// marks it as written.
+    fn inline(&self, address: NonZeroU64, is_datablock: bool, depth: usize) -> bool {
+        let allowed = self
+            .options
+            .max_depth
+            .is_none_or(|max_depth| depth <= max_depth)
+            && (self.options.inline_datablocks || !is_datablock);
+
+        allowed && self.visited.borrow_mut().insert(address)
+    }
+}
+
+/// A struct, written as a map of its fields.
+struct Node<'c, 'i, 'a> {
+    context: &'c Context,
+    instance: &'i Instance<'a>,
+    /// The address of the block, if the struct is a whole block.
+    address: Option<NonZeroU64>,
+    /// How many pointers were followed to reach the struct.
+    depth: usize,
+}
+
+impl Serialize for Node<'_, '_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+
+        if let Some(address) = self.address {
+            map.serialize_entry("@", &address.get())?;
+        }
+
+        for (name, field) in self.instance.layout.fields.iter() {
+            if name.starts_with("_pad") {
+                continue;
+            }
+
+            map.serialize_entry(
+                name,
+                &Field {
+                    node: self,
+                    name,
+                    field,
+                },
+            )?;
+        }
+
+        map.end()
+    }
+}
+
+/// A field of a struct.
+struct Field<'n, 'c, 'i, 'a> {
+    node: &'n Node<'c, 'i, 'a>,
+    name: &'n str,
+    field: &'n FieldTemplate,
+}
+
+impl Serialize for Field<'_, '_, '_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Field { node, name, field } = *self;
+        let instance = node.instance;
+
+        match &field.info {
+            FieldInfo::Value if field.type_name == "void" => serializer.serialize_none(),
+            FieldInfo::Value if field.is_primitive => primitive(instance, name, serializer),
+            FieldInfo::Value if field.type_name == "ListBase" => self.serialize_list(serializer),
+            FieldInfo::Value => {
+                let value = instance.try_get(name).map_err(S::Error::custom)?;
+
+                Node {
+                    context: node.context,
+                    instance: &value,
+                    address: None,
+                    depth: node.depth,
+                }
+                .serialize(serializer)
+            }
+            FieldInfo::ValueArray { dimensions, .. } if field.type_name == "char" => {
+                let data = instance
+                    .try_field_data(name, field)
+                    .map_err(S::Error::custom)?;
+                let (last, dimensions) = dimensions.split_last().unwrap_or((&1, &[]));
+                let strings = data
+                    .chunks((*last).max(1))
+                    .map(c_string)
+                    .collect::<Vec<_>>();
+
+                Nested::new(&strings, dimensions).serialize(serializer)
+            }
+            FieldInfo::ValueArray { dimensions, .. } if field.is_primitive => {
+                primitive_array(instance, name, dimensions, serializer)
+            }
+            FieldInfo::ValueArray { dimensions, .. } => {
+                let elements = instance
+                    .try_get_iter(name)
+                    .and_then(Iterator::collect::<Result<Vec<_>, _>>)
+                    .map_err(S::Error::custom)?;
+                let nodes = elements
+                    .iter()
+                    .map(|element| Node {
+                        context: node.context,
+                        instance: element,
+                        address: None,
+                        depth: node.depth,
+                    })
+                    .collect::<Vec<_>>();
+
+                Nested::new(&nodes, dimensions).serialize(serializer)
+            }
+            FieldInfo::Pointer {
+                indirection_count: 1,
+            } => self.serialize_pointer(serializer),
+            FieldInfo::Pointer { .. } => self.serialize_list(serializer),
+            FieldInfo::PointerArray {
+                indirection_count,
+                dimensions,
+                ..
+            } => self.serialize_pointer_array(*indirection_count, dimensions, serializer),
+            FieldInfo::FnPointer => serializer.serialize_none(),
+        }
+    }
+}
+
+impl Field<'_, '_, '_, '_> {
+    /// Reads the address saved in a pointer field.
+    fn address(&self) -> Result<Option<NonZeroU64>, AccessError> {
+        let instance = self.node.instance;
+        let data = instance.try_field_data(self.name, self.field)?;
+
+        Ok(instance.parse_ptr_address(data))
+    }
+
+    /// Writes a pointer as null, a reference, a primitive array or the struct(s) it points to.
+    fn serialize_pointer<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Field { node, name, field } = *self;
+        let instance = node.instance;
+
+        let address = match self.address().map_err(S::Error::custom)? {
+            Some(address) => address,
+            None => return serializer.serialize_none(),
+        };
+
+        // Lists are written by the `ListBase` fields, following their links would write them again. Blocks Blender
+        // didn't save can only be referenced.
+        if ["next", "prev", "first", "last"].contains(&name)
+            || instance.try_pointer(name, field).is_err()
+        {
+            return Reference(address).serialize(serializer);
+        }
+
+        if field.is_primitive && field.type_name != "void" {
+            return match field.type_name.as_str() {
+                "char" => match instance.try_get_vec::<u8>(name) {
+                    Ok(data) => serializer.serialize_str(&c_string(&data)),
+                    Err(_) => Reference(address).serialize(serializer),
+                },
+                _ => primitive_array(instance, name, &[], serializer),
+            };
+        }
+
+        let is_datablock = matches!(
+            instance.blend.block_at(address),
+            Some(Block::Principal { .. })
+        );
+
+        // The type of `void` pointers to raw data and of some blocks can't be known, those are only referenced
+        let elements = match instance.try_get_iter(name) {
+            Ok(_) if field.is_primitive && is_raw_data(instance, address) => None,
+            Ok(elements) => elements.collect::<Result<Vec<_>, _>>().ok(),
+            Err(_) => None,
+        };
+
+        match elements {
+            Some(elements) if node.context.inline(address, is_datablock, node.depth + 1) => {
+                let node = |element, address| Node {
+                    context: node.context,
+                    instance: element,
+                    address,
+                    depth: node.depth + 1,
+                };
+
+                // Only the blocks holding a single struct are written as objects with an address
+                match elements.as_slice() {
+                    [element] => node(element, Some(address)).serialize(serializer),
+                    elements => {
+                        serializer.collect_seq(elements.iter().map(|element| node(element, None)))
+                    }
+                }
+            }
+            _ => Reference(address).serialize(serializer),
+        }
+    }
+
+    /// Writes an array of pointers like `serialize_pointer` writes a single pointer: null pointers as null, the
+    /// primitives behind pointers to primitives, and the structs behind the other pointers the first time their block
+    /// is found. Pointers which can't be followed, like the ones to blocks Blender didn't save or to raw data, are
+    /// written as references.
+    fn serialize_pointer_array<S: Serializer>(
+        &self,
+        indirection_count: usize,
+        dimensions: &[usize],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let Field { node, name, field } = *self;
+        let instance = node.instance;
+        let blend = instance.blend;
+
+        let data = instance
+            .try_field_data(name, field)
+            .map_err(S::Error::custom)?;
+        let pointer_size = blend.header.pointer_size.bytes_num();
+        let kind = PrimitiveKind::from_field(field, blend.header.pointer_size)
+            .filter(|_| indirection_count == 1);
+
+        // The iterator returns one element per pointer which isn't null, in the order of the array
+        let mut structs = match instance.try_get_iter(name) {
+            Ok(structs) if kind.is_none() && indirection_count == 1 => Some(structs),
+            _ => None,
+        };
+
+        let elements = data
+            .chunks_exact(pointer_size)
+            .map(|pointer| {
+                let address = match instance.parse_ptr_address(pointer) {
+                    Some(address) => address,
+                    None => return ArrayElement::Null,
+                };
+
+                if let Some(kind) = kind {
+                    return match blend.block_at(address).and_then(Block::data) {
+                        Some(data) if field.type_name == "char" => {
+                            ArrayElement::String(c_string(&data.data))
+                        }
+                        Some(data) if data.data.len() % kind.size() == 0 => {
+                            ArrayElement::Primitives(
+                                data.data
+                                    .chunks(kind.size())
+                                    .map(|s| kind.parse(s, blend.header.endianness))
+                                    .collect(),
+                            )
+                        }
+                        _ => ArrayElement::Reference(address),
+                    };
+                }
+
+                match structs.as_mut().and_then(Iterator::next) {
+                    Some(Ok(_)) if field.is_primitive && is_raw_data(instance, address) => {
+                        ArrayElement::Reference(address)
+                    }
+                    Some(Ok(element)) => ArrayElement::Struct(element),
+                    _ => ArrayElement::Reference(address),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let elements = elements
+            .iter()
+            .map(|element| Element {
+                context: node.context,
+                element,
+                depth: node.depth + 1,
+            })
+            .collect::<Vec<_>>();
+
+        Nested::new(&elements, dimensions).serialize(serializer)
+    }
+
+    /// Writes the elements of a `ListBase` or of a pointer to pointers. Lists which can't be followed, like the ones
+    /// Blender doesn't save, are written as saved: a `ListBase` as a struct and a pointer as a reference.
+    fn serialize_list<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Field { node, name, field } = *self;
+        let instance = node.instance;
+
+        let elements = instance
+            .try_get_iter(name)
+            .and_then(Iterator::collect::<Result<Vec<_>, _>>);
+
+        match elements {
+            Ok(elements) => serializer.collect_seq(elements.iter().map(|element| Pointee {
+                context: node.context,
+                element,
+                depth: node.depth + 1,
+            })),
+            Err(AccessError::NullPointer { .. }) => serializer.serialize_none(),
+            Err(err) => match field.info {
+                FieldInfo::Value => {
+                    let list = instance.try_get(name).map_err(S::Error::custom)?;
+
+                    Node {
+                        context: node.context,
+                        instance: &list,
+                        address: None,
+                        depth: node.depth,
+                    }
+                    .serialize(serializer)
+                }
+                FieldInfo::Pointer { .. } => match self.address().map_err(S::Error::custom)? {
+                    Some(address) => Reference(address).serialize(serializer),
+                    None => serializer.serialize_none(),
+                },
+                _ => Err(S::Error::custom(err)),
+            },
+        }
+    }
+}
+
+/// An element of a list, written as an object the first time its block is found or as a reference.
+struct Pointee<'c, 'i, 'a> {
+    context: &'c Context,
+    element: &'i Instance<'a>,
+    depth: usize,
+}
+
+impl Serialize for Pointee<'_, '_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let element = self.element;
+        let address = element.memory_address();
+
+        if self
+            .context
+            .inline(address, element.data.code().is_some(), self.depth)
+        {
+            Node {
+                context: self.context,
+                instance: element,
+                address: Some(address),
+                depth: self.depth,
+            }
+            .serialize(serializer)
+        } else {
+            Reference(address).serialize(serializer)
+        }
+    }
+}
+
+/// What an element of an array of pointers is written as, see `Field::serialize_pointer_array`.
+enum ArrayElement<'a> {
+    Null,
+    Reference(NonZeroU64),
+    String(String),
+    Primitives(Vec<PrimitiveValue>),
+    Struct(Instance<'a>),
+}
+
+/// An element of an array of pointers.
+struct Element<'c, 'e, 'a> {
+    context: &'c Context,
+    element: &'e ArrayElement<'a>,
+    depth: usize,
+}
+
+impl Serialize for Element<'_, '_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.element {
+            ArrayElement::Null => serializer.serialize_none(),
+            ArrayElement::Reference(address) => Reference(*address).serialize(serializer),
+            ArrayElement::String(string) => serializer.serialize_str(string),
+            ArrayElement::Primitives(values) => values.serialize(serializer),
+            ArrayElement::Struct(element) => Pointee {
+                context: self.context,
+                element,
+                depth: self.depth,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+/// A pointer which isn't followed, written as `{"@": address}`.
+struct Reference(NonZeroU64);
+
+impl Serialize for Reference {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("@", &self.0.get())?;
+        map.end()
+    }
+}
+
+/// Values written as arrays nested like the dimensions of the field they were read from. No dimensions means a single
+/// value.
+struct Nested<'v, T> {
+    values: &'v [T],
+    dimensions: &'v [usize],
+}
+
+impl<'v, T> Nested<'v, T> {
+    fn new(values: &'v [T], dimensions: &'v [usize]) -> Self {
+        Nested { values, dimensions }
+    }
+}
+
+impl<T: Serialize> Serialize for Nested<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.dimensions.split_first() {
+            None => match self.values.first() {
+                Some(value) => value.serialize(serializer),
+                None => serializer.serialize_none(),
+            },
+            Some((_, [])) => self.values.serialize(serializer),
+            Some((&len, dimensions)) => {
+                let chunk_len = (self.values.len() / len.max(1)).max(1);
+                let mut seq = serializer.serialize_seq(Some(len))?;
+
+                for chunk in self.values.chunks(chunk_len) {
+                    seq.serialize_element(&Nested::new(chunk, dimensions))?;
+                }
+
+                seq.end()
+            }
+        }
+    }
+}
+
+/// Returns true if the block at `address` holds raw data. `Instance::get` reads it as the struct in its header, the
+/// first struct of the DNA, which is not what it holds.
+fn is_raw_data(instance: &Instance, address: NonZeroU64) -> bool {
+    matches!(
+        instance.blend.block_at(address),
+        Some(Block::Subsidiary {
+            dna_index: RAW_DATA_STRUCT_INDEX,
+            ..
+        })
+    )
+}
+
+/// Writes a primitive field with the Rust type matching its DNA type.
+fn primitive<S: Serializer>(
+    instance: &Instance,
+    name: &str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    instance
+        .try_get_primitive(name)
+        .map_err(S::Error::custom)?
+        .serialize(serializer)
+}
+
+/// Writes a primitive array, or the primitives behind a pointer when `dimensions` is empty.
+fn primitive_array<S: Serializer>(
+    instance: &Instance,
+    name: &str,
+    dimensions: &[usize],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let values = instance
+        .try_get_primitives(name)
+        .map_err(S::Error::custom)?;
+    let len = [values.len()];
+    let dimensions = if dimensions.is_empty() {
+        &len[..]
+    } else {
+        dimensions
+    };
+
+    Nested::new(&values, dimensions).serialize(serializer)
+}
+
+impl Serialize for PrimitiveValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            PrimitiveValue::I8(v) => v.serialize(serializer),
+            PrimitiveValue::U8(v) => v.serialize(serializer),
+            PrimitiveValue::Bool(v) => v.serialize(serializer),
+            PrimitiveValue::I16(v) => v.serialize(serializer),
+            PrimitiveValue::U16(v) => v.serialize(serializer),
+            PrimitiveValue::I32(v) => v.serialize(serializer),
+            PrimitiveValue::U32(v) => v.serialize(serializer),
+            PrimitiveValue::I64(v) => v.serialize(serializer),
+            PrimitiveValue::U64(v) => v.serialize(serializer),
+            PrimitiveValue::F32(v) => v.serialize(serializer),
+            PrimitiveValue::F64(v) => v.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        runtime::library::tests::{write_linked_file, TempDir},
+        Blend,
+    };
+    use std::path::PathBuf;
+
+    #[test]
+    fn serialize_every_root_instance() {
+        let dir = TempDir::new("serialize_linked");
+        let linked = write_linked_file(dir.path());
+        let files = ["2_80", "2_90", "3_0", "3_5"]
+            .iter()
+            .map(|file| PathBuf::from(format!("examples/blend_files/{}.blend", file)))
+            .chain(Some(linked));
+
+        for file in files {
+            let blend = Blend::from_path(&file).expect("error loading blend file");
+
+            for instance in blend.root_instances() {
+                if let Err(err) = serde_json::to_string(&instance) {
+                    panic!("{}: {}: {}", file.display(), instance.path(), err);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_pointer_arrays() {
+        let blend =
+            Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+
+        // `gputexture` points to textures Blender doesn't save
+        let image = blend.instances_with_code(*b"IM").next().unwrap();
+        let json = serde_json::to_value(&image).unwrap();
+        assert!(json["gputexture"][0]["@"].is_u64());
+        assert!(json["gputexture"][1].is_null());
+
+        // `rect` points to the raw pixels of the preview
+        let material = blend.instances_with_code(*b"MA").next().unwrap();
+        let json = serde_json::to_value(&material).unwrap();
+        let preview = &json["preview"];
+        let width = preview["w"][0].as_u64().unwrap();
+        let height = preview["h"][0].as_u64().unwrap();
+        let rect = preview["rect"][0].as_array().unwrap();
+        assert_eq!(rect.len() as u64, width * height);
+        assert!(preview["rect"][1].is_null());
+    }
+}
@@ -0,0 +1,714 @@
+use crate::runtime::{AccessError, FromField, Instance};
+use std::convert::TryFrom;
+
+/// Reads the field `name` of `instance`.
+fn read<T: FromField>(instance: &Instance, name: &str) -> Result<T, AccessError> {
+    T::from_field(instance, name).map_err(|err| err.error)
+}
+
+/// Returns the name of the datablock `instance`, without the two letter code its ID name starts with.
+fn id_name(instance: &Instance) -> Result<String, AccessError> {
+    Ok(read::<String>(&instance.try_get("id")?, "name")?
+        .chars()
+        .skip(2)
+        .collect())
+}
+
+/// Returns the instance the pointer `name` points to, or `None` if it is null or doesn't point to a block.
+fn read_pointer<'a>(
+    instance: &Instance<'a>,
+    name: &str,
+) -> Result<Option<Instance<'a>>, AccessError> {
+    match instance.try_get(name) {
+        Ok(instance) => Ok(Some(instance)),
+        Err(AccessError::NullPointer { .. }) | Err(AccessError::DanglingPointer { .. }) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Reads the field `name` of `instance` as an index or a count.
+fn read_index(instance: &Instance, name: &str) -> Result<usize, AccessError> {
+    usize::try_from(read::<i32>(instance, name)?).map_err(|_| out_of_range(instance, name))
+}
+
+/// The error for the index or count in the field `name` of `instance` when it is negative or goes past the data it
+/// indexes, which only happens with corrupt files.
+fn out_of_range(instance: &Instance, name: &str) -> AccessError {
+    AccessError::TruncatedData {
+        field: format!("{}.{}", instance.path(), name),
+    }
+}
+
+fn expect<T>(result: Result<T, AccessError>) -> T {
+    result.unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// An object, which places a mesh, a camera, a light or other data in a scene.
+///
+/// Like the other types of this module it reads its fields from the `Instance` it wraps, panicking with the message
+/// of the error if they can't be read. Every method has a `try_[]` version which returns the `AccessError` instead.
+/// `instance` gives access to the fields it doesn't have a method for.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::{Blend, types::{Object, ObjectData}};
+/// # fn main() {
+///     # let blend = Blend::from_path("examples/blend_files/3_5.blend").expect("error loading blend file");
+/// for obj in blend.instances_with_code(*b"OB").filter_map(Object::from_instance) {
+///     match obj.data() {
+///         ObjectData::Mesh(mesh) => println!("{} has {} vertices", obj.name(), mesh.positions().len()),
+///         ObjectData::Camera(camera) => println!("{} has a {}mm lens", obj.name(), camera.focal_length()),
+///         ObjectData::Light(light) => println!("{} emits {}W", obj.name(), light.energy()),
+///         _ => println!("{} at {:?}", obj.name(), obj.location()),
+///     }
+/// }
+/// # let camera = blend.instances_with_code(*b"OB").find_map(Object::from_instance).unwrap();
+/// # assert_eq!(camera.name(), "Camera");
+/// # assert_eq!(camera.parent().map(|parent| parent.name()), Some(String::from("Cube")));
+/// # assert!(matches!(camera.data(), ObjectData::Camera(_)));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Object<'a> {
+    instance: Instance<'a>,
+}
+
+/// What an object places in the scene. See `Object::data`.
+#[derive(Debug, Clone)]
+pub enum ObjectData<'a> {
+    /// The object has no data, like empties.
+    Empty,
+    Mesh(Mesh<'a>),
+    Camera(Camera<'a>),
+    Light(Light<'a>),
+    /// Data of a type this module doesn't have a wrapper for, like curves or armatures.
+    Other(Instance<'a>),
+}
+
+impl<'a> Object<'a> {
+    /// Wraps `instance`, returning `None` if it is not an object.
+    pub fn from_instance(instance: Instance<'a>) -> Option<Self> {
+        match instance.type_name {
+            "Object" => Some(Object { instance }),
+            _ => None,
+        }
+    }
+
+    /// The instance this object reads its fields from.
+    pub fn instance(&self) -> &Instance<'a> {
+        &self.instance
+    }
+
+    /// The name of the object, like `Cube`.
+    pub fn name(&self) -> String {
+        expect(self.try_name())
+    }
+
+    /// Same as `name`, but returns an error instead of panicking.
+    pub fn try_name(&self) -> Result<String, AccessError> {
+        id_name(&self.instance)
+    }
+
+    /// The location of the object, relative to its parent.
+    pub fn location(&self) -> [f32; 3] {
+        expect(self.try_location())
+    }
+
+    /// Same as `location`, but returns an error instead of panicking.
+    pub fn try_location(&self) -> Result<[f32; 3], AccessError> {
+        read(&self.instance, "loc")
+    }
+
+    /// The rotation of the object, in radians. Objects rotated with quaternions or around an axis use `quat`,
+    /// `rotAxis` and `rotAngle` instead, depending on their `rotmode`.
+    pub fn rotation_euler(&self) -> [f32; 3] {
+        expect(self.try_rotation_euler())
+    }
+
+    /// Same as `rotation_euler`, but returns an error instead of panicking.
+    pub fn try_rotation_euler(&self) -> Result<[f32; 3], AccessError> {
+        read(&self.instance, "rot")
+    }
+
+    /// The scale of the object.
+    pub fn scale(&self) -> [f32; 3] {
+        expect(self.try_scale())
+    }
+
+    /// Same as `scale`, but returns an error instead of panicking.
+    pub fn try_scale(&self) -> Result<[f32; 3], AccessError> {
+        read(&self.instance, "size")
+    }
+
+    /// The transform of the object in world space, as a column-major matrix, as it was when the file was saved.
+    pub fn world_matrix(&self) -> [[f32; 4]; 4] {
+        expect(self.try_world_matrix())
+    }
+
+    /// Same as `world_matrix`, but returns an error instead of panicking.
+    pub fn try_world_matrix(&self) -> Result<[[f32; 4]; 4], AccessError> {
+        read(&self.instance, "obmat")
+    }
+
+    /// The parent of the object, if it has one.
+    pub fn parent(&self) -> Option<Object<'a>> {
+        expect(self.try_parent())
+    }
+
+    /// Same as `parent`, but returns an error instead of panicking.
+    pub fn try_parent(&self) -> Result<Option<Object<'a>>, AccessError> {
+        Ok(read_pointer(&self.instance, "parent")?.and_then(Object::from_instance))
+    }
+
+    /// The mesh, camera, light or other data placed by the object.
+    pub fn data(&self) -> ObjectData<'a> {
+        expect(self.try_data())
+    }
+
+    /// Same as `data`, but returns an error instead of panicking.
+    pub fn try_data(&self) -> Result<ObjectData<'a>, AccessError> {
+        let data = match read_pointer(&self.instance, "data")? {
+            Some(data) => data,
+            None => return Ok(ObjectData::Empty),
+        };
+
+        Ok(match data.type_name {
+            "Mesh" => ObjectData::Mesh(Mesh { instance: data }),
+            "Camera" => ObjectData::Camera(Camera { instance: data }),
+            "Lamp" | "Light" => ObjectData::Light(Light { instance: data }),
+            _ => ObjectData::Other(data),
+        })
+    }
+}
+
+/// A mesh, made of vertices and of faces joining them.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::{Blend, types::Mesh};
+/// # fn main() {
+///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+/// let mesh = blend.instances_with_code(*b"ME").find_map(Mesh::from_instance).unwrap();
+/// let positions = mesh.positions();
+///
+/// for face in mesh.faces() {
+///     let corners = face.vertices.iter().map(|vertex| positions[*vertex]).collect::<Vec<_>>();
+///     println!("{:?} {:?}", corners, face.uvs);
+/// }
+/// # assert_eq!(mesh.name(), "Cube");
+/// # assert_eq!(positions.len(), 8);
+/// # assert_eq!(mesh.faces().len(), 6);
+/// # assert!(mesh.faces().iter().all(|face| face.vertices.len() == 4 && face.uvs.len() == 4));
+/// # assert_eq!(mesh.materials()[0].name(), "Material");
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mesh<'a> {
+    instance: Instance<'a>,
+}
+
+/// A face of a mesh. See `Mesh::faces`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Face {
+    /// The indices of the vertices of the face in `Mesh::positions`, in order.
+    pub vertices: Vec<usize>,
+    /// The texture coordinates of each vertex of the face, from the first UV map of the mesh. Empty if the mesh has
+    /// no UV map.
+    pub uvs: Vec<[f32; 2]>,
+}
+
+impl<'a> Mesh<'a> {
+    /// Wraps `instance`, returning `None` if it is not a mesh.
+    pub fn from_instance(instance: Instance<'a>) -> Option<Self> {
+        match instance.type_name {
+            "Mesh" => Some(Mesh { instance }),
+            _ => None,
+        }
+    }
+
+    /// The instance this mesh reads its fields from.
+    pub fn instance(&self) -> &Instance<'a> {
+        &self.instance
+    }
+
+    /// The name of the mesh, like `Cube`.
+    pub fn name(&self) -> String {
+        expect(self.try_name())
+    }
+
+    /// Same as `name`, but returns an error instead of panicking.
+    pub fn try_name(&self) -> Result<String, AccessError> {
+        id_name(&self.instance)
+    }
+
+    /// The position of each vertex.
+    pub fn positions(&self) -> Vec<[f32; 3]> {
+        expect(self.try_positions())
+    }
+
+    /// Same as `positions`, but returns an error instead of panicking.
+    pub fn try_positions(&self) -> Result<Vec<[f32; 3]>, AccessError> {
+        self.vertices()?
+            .iter()
+            .map(|vertex| read(vertex, "co"))
+            .collect()
+    }
+
+    /// The normal of each vertex. Files saved by Blender 3.1 and later don't store normals, as they are computed
+    /// when the mesh is loaded, so they are `None`.
+    pub fn normals(&self) -> Option<Vec<[f32; 3]>> {
+        expect(self.try_normals())
+    }
+
+    /// Same as `normals`, but returns an error instead of panicking.
+    pub fn try_normals(&self) -> Result<Option<Vec<[f32; 3]>>, AccessError> {
+        let vertices = self.vertices()?;
+
+        if vertices
+            .first()
+            .is_some_and(|vertex| !vertex.is_valid("no"))
+        {
+            return Ok(None);
+        }
+
+        // Normals are compressed into 16 bit integers
+        let normals = vertices
+            .iter()
+            .map(|vertex| {
+                read::<[i16; 3]>(vertex, "no").map(|normal| normal.map(|n| f32::from(n) / 32767.0))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Some(normals))
+    }
+
+    /// The faces of the mesh.
+    pub fn faces(&self) -> Vec<Face> {
+        expect(self.try_faces())
+    }
+
+    /// Same as `faces`, but returns an error instead of panicking. Vertex indices and corners which are negative or
+    /// past the end of the mesh, which only happen in corrupt files, return `AccessError::TruncatedData`.
+    pub fn try_faces(&self) -> Result<Vec<Face>, AccessError> {
+        let polygons = self.elements("mpoly")?;
+        let loops = self
+            .elements("mloop")?
+            .iter()
+            .map(|corner| read_index(corner, "v"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let uvs = self.uvs()?;
+
+        polygons
+            .iter()
+            .map(|polygon| {
+                let start = read_index(polygon, "loopstart")?;
+                let corners = start..start.saturating_add(read_index(polygon, "totloop")?);
+                let out_of_range = || out_of_range(polygon, "loopstart");
+
+                Ok(Face {
+                    vertices: loops
+                        .get(corners.clone())
+                        .ok_or_else(out_of_range)?
+                        .to_vec(),
+                    uvs: match &uvs {
+                        Some(uvs) => uvs.get(corners).ok_or_else(out_of_range)?.to_vec(),
+                        None => Vec::new(),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// The materials of the mesh. Empty material slots are skipped.
+    pub fn materials(&self) -> Vec<Material<'a>> {
+        expect(self.try_materials())
+    }
+
+    /// Same as `materials`, but returns an error instead of panicking.
+    pub fn try_materials(&self) -> Result<Vec<Material<'a>>, AccessError> {
+        Ok(self
+            .elements("mat")?
+            .into_iter()
+            .filter_map(Material::from_instance)
+            .collect())
+    }
+
+    fn vertices(&self) -> Result<Vec<Instance<'a>>, AccessError> {
+        self.elements("mvert")
+    }
+
+    /// Returns the elements of the array the pointer `name` points to, which is empty if it is null.
+    fn elements(&self, name: &str) -> Result<Vec<Instance<'a>>, AccessError> {
+        match self.instance.try_get_iter(name) {
+            Ok(elements) => elements.collect(),
+            Err(AccessError::NullPointer { .. }) => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the texture coordinates of each face corner from the first UV map, if the mesh has one.
+    fn uvs(&self) -> Result<Option<Vec<[f32; 2]>>, AccessError> {
+        /// `CD_MLOOPUV`, the type of the custom data layers holding UV maps
+        const UV_LAYER_TYPE: i32 = 16;
+
+        // Blender 3.5 stopped setting `mloopuv`, but still saves UV maps as custom data layers
+        let uvs = if self.instance.is_valid("mloopuv") {
+            self.elements("mloopuv")?
+        } else {
+            let corner_data = self.instance.try_get("ldata")?;
+            let layers = match corner_data.try_get_iter("layers") {
+                Ok(layers) => layers,
+                Err(AccessError::NullPointer { .. }) => return Ok(None),
+                Err(err) => return Err(err),
+            };
+
+            let mut uv_layer = None;
+            for layer in layers {
+                let layer = layer?;
+                if read::<i32>(&layer, "type")? == UV_LAYER_TYPE {
+                    uv_layer = Some(layer);
+                    break;
+                }
+            }
+
+            match uv_layer.map(|layer| layer.try_get_iter("data")) {
+                Some(Ok(uvs)) => uvs.collect::<Result<_, _>>()?,
+                _ => return Ok(None),
+            }
+        };
+
+        uvs.iter()
+            .map(|uv| read(uv, "uv"))
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+}
+
+/// A camera.
+#[derive(Debug, Clone)]
+pub struct Camera<'a> {
+    instance: Instance<'a>,
+}
+
+/// The projection of a camera. See `Camera::kind`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CameraKind {
+    Perspective,
+    Orthographic,
+    Panoramic,
+}
+
+impl<'a> Camera<'a> {
+    /// Wraps `instance`, returning `None` if it is not a camera.
+    pub fn from_instance(instance: Instance<'a>) -> Option<Self> {
+        match instance.type_name {
+            "Camera" => Some(Camera { instance }),
+            _ => None,
+        }
+    }
+
+    /// The instance this camera reads its fields from.
+    pub fn instance(&self) -> &Instance<'a> {
+        &self.instance
+    }
+
+    /// The name of the camera, like `Camera`.
+    pub fn name(&self) -> String {
+        expect(self.try_name())
+    }
+
+    /// Same as `name`, but returns an error instead of panicking.
+    pub fn try_name(&self) -> Result<String, AccessError> {
+        id_name(&self.instance)
+    }
+
+    /// The projection of the camera, or `None` if the file uses one this crate doesn't know.
+    pub fn kind(&self) -> Option<CameraKind> {
+        expect(self.try_kind())
+    }
+
+    /// Same as `kind`, but returns an error instead of panicking.
+    pub fn try_kind(&self) -> Result<Option<CameraKind>, AccessError> {
+        Ok(match read::<i8>(&self.instance, "type")? {
+            0 => Some(CameraKind::Perspective),
+            1 => Some(CameraKind::Orthographic),
+            2 => Some(CameraKind::Panoramic),
+            _ => None,
+        })
+    }
+
+    /// The focal length of perspective cameras, in millimeters.
+    pub fn focal_length(&self) -> f32 {
+        expect(self.try_focal_length())
+    }
+
+    /// Same as `focal_length`, but returns an error instead of panicking.
+    pub fn try_focal_length(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "lens")
+    }
+
+    /// The width of the sensor, in millimeters.
+    pub fn sensor_width(&self) -> f32 {
+        expect(self.try_sensor_width())
+    }
+
+    /// Same as `sensor_width`, but returns an error instead of panicking.
+    pub fn try_sensor_width(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "sensor_x")
+    }
+
+    /// The size of the view of orthographic cameras.
+    pub fn ortho_scale(&self) -> f32 {
+        expect(self.try_ortho_scale())
+    }
+
+    /// Same as `ortho_scale`, but returns an error instead of panicking.
+    pub fn try_ortho_scale(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "ortho_scale")
+    }
+
+    /// The distance of the near clipping plane.
+    pub fn clip_start(&self) -> f32 {
+        expect(self.try_clip_start())
+    }
+
+    /// Same as `clip_start`, but returns an error instead of panicking.
+    pub fn try_clip_start(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "clipsta")
+    }
+
+    /// The distance of the far clipping plane.
+    pub fn clip_end(&self) -> f32 {
+        expect(self.try_clip_end())
+    }
+
+    /// Same as `clip_end`, but returns an error instead of panicking.
+    pub fn try_clip_end(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "clipend")
+    }
+}
+
+/// A light. Blender's DNA still calls them lamps.
+///
+/// ## Example
+///
+/// ```rust
+/// # use blend::{Blend, types::{Light, LightKind}};
+/// # fn main() {
+///     # let blend = Blend::from_path("examples/blend_files/2_80.blend").expect("error loading blend file");
+/// let light = blend.instances_with_code(*b"LA").find_map(Light::from_instance).unwrap();
+///
+/// if light.kind() == Some(LightKind::Point) {
+///     println!("{}W point light with a radius of {}", light.energy(), light.radius());
+/// }
+/// # assert_eq!(light.radius(), 0.1);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Light<'a> {
+    instance: Instance<'a>,
+}
+
+/// The kind of a light. See `Light::kind`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LightKind {
+    Point,
+    Sun,
+    Spot,
+    Area,
+}
+
+impl<'a> Light<'a> {
+    /// Wraps `instance`, returning `None` if it is not a light.
+    pub fn from_instance(instance: Instance<'a>) -> Option<Self> {
+        match instance.type_name {
+            "Lamp" | "Light" => Some(Light { instance }),
+            _ => None,
+        }
+    }
+
+    /// The instance this light reads its fields from.
+    pub fn instance(&self) -> &Instance<'a> {
+        &self.instance
+    }
+
+    /// The name of the light, like `Light`.
+    pub fn name(&self) -> String {
+        expect(self.try_name())
+    }
+
+    /// Same as `name`, but returns an error instead of panicking.
+    pub fn try_name(&self) -> Result<String, AccessError> {
+        id_name(&self.instance)
+    }
+
+    /// The kind of the light, or `None` if the file uses one this crate doesn't know.
+    pub fn kind(&self) -> Option<LightKind> {
+        expect(self.try_kind())
+    }
+
+    /// Same as `kind`, but returns an error instead of panicking.
+    pub fn try_kind(&self) -> Result<Option<LightKind>, AccessError> {
+        Ok(match read::<i16>(&self.instance, "type")? {
+            0 => Some(LightKind::Point),
+            1 => Some(LightKind::Sun),
+            2 => Some(LightKind::Spot),
+            4 => Some(LightKind::Area),
+            _ => None,
+        })
+    }
+
+    /// The color of the light.
+    pub fn color(&self) -> [f32; 3] {
+        expect(self.try_color())
+    }
+
+    /// Same as `color`, but returns an error instead of panicking.
+    pub fn try_color(&self) -> Result<[f32; 3], AccessError> {
+        Ok([
+            read(&self.instance, "r")?,
+            read(&self.instance, "g")?,
+            read(&self.instance, "b")?,
+        ])
+    }
+
+    /// The power of the light, in watts, or its strength for sun lights.
+    pub fn energy(&self) -> f32 {
+        expect(self.try_energy())
+    }
+
+    /// Same as `energy`, but returns an error instead of panicking.
+    pub fn try_energy(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "energy")
+    }
+
+    /// The radius of point and spot lights, which makes their shadows softer.
+    pub fn radius(&self) -> f32 {
+        expect(self.try_radius())
+    }
+
+    /// Same as `radius`, but returns an error instead of panicking.
+    pub fn try_radius(&self) -> Result<f32, AccessError> {
+        // Blender 3.5 moved it from `area_size` to `radius`, which replaced the unused `soft`
+        if self.instance.is_valid("radius") {
+            read(&self.instance, "radius")
+        } else {
+            read(&self.instance, "area_size")
+        }
+    }
+
+    /// The angle of the cone of spot lights, in radians.
+    pub fn spot_size(&self) -> f32 {
+        expect(self.try_spot_size())
+    }
+
+    /// Same as `spot_size`, but returns an error instead of panicking.
+    pub fn try_spot_size(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "spotsize")
+    }
+}
+
+/// A material. Only the settings shown in the viewport are read, the node tree of the material isn't.
+#[derive(Debug, Clone)]
+pub struct Material<'a> {
+    instance: Instance<'a>,
+}
+
+impl<'a> Material<'a> {
+    /// Wraps `instance`, returning `None` if it is not a material.
+    pub fn from_instance(instance: Instance<'a>) -> Option<Self> {
+        match instance.type_name {
+            "Material" => Some(Material { instance }),
+            _ => None,
+        }
+    }
+
+    /// The instance this material reads its fields from.
+    pub fn instance(&self) -> &Instance<'a> {
+        &self.instance
+    }
+
+    /// The name of the material, like `Material`.
+    pub fn name(&self) -> String {
+        expect(self.try_name())
+    }
+
+    /// Same as `name`, but returns an error instead of panicking.
+    pub fn try_name(&self) -> Result<String, AccessError> {
+        id_name(&self.instance)
+    }
+
+    /// The color of the material, with its alpha.
+    pub fn base_color(&self) -> [f32; 4] {
+        expect(self.try_base_color())
+    }
+
+    /// Same as `base_color`, but returns an error instead of panicking.
+    pub fn try_base_color(&self) -> Result<[f32; 4], AccessError> {
+        Ok([
+            read(&self.instance, "r")?,
+            read(&self.instance, "g")?,
+            read(&self.instance, "b")?,
+            read(&self.instance, "a")?,
+        ])
+    }
+
+    /// How metallic the material is, from 0 to 1.
+    pub fn metallic(&self) -> f32 {
+        expect(self.try_metallic())
+    }
+
+    /// Same as `metallic`, but returns an error instead of panicking.
+    pub fn try_metallic(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "metallic")
+    }
+
+    /// How rough the surface of the material is, from 0 to 1.
+    pub fn roughness(&self) -> f32 {
+        expect(self.try_roughness())
+    }
+
+    /// Same as `roughness`, but returns an error instead of panicking.
+    pub fn try_roughness(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "roughness")
+    }
+
+    /// The intensity of the specular reflection.
+    pub fn specular(&self) -> f32 {
+        expect(self.try_specular())
+    }
+
+    /// Same as `specular`, but returns an error instead of panicking.
+    pub fn try_specular(&self) -> Result<f32, AccessError> {
+        read(&self.instance, "spec")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mesh;
+    use crate::{runtime::AccessError, Blend};
+
+    #[test]
+    fn corners_past_the_end_of_the_mesh() {
+        let mut blend = Blend::from_path("examples/blend_files/2_80.blend").unwrap();
+        let mesh = blend
+            .instances_with_code(*b"ME")
+            .find_map(Mesh::from_instance)
+            .unwrap();
+        let polygon = mesh.instance().get_iter("mpoly").nth(2).unwrap().location();
+        blend.edit(&polygon).set_i32("loopstart", 1000);
+
+        let mesh = blend
+            .instances_with_code(*b"ME")
+            .find_map(Mesh::from_instance)
+            .unwrap();
+        assert!(matches!(
+            mesh.try_faces(),
+            Err(AccessError::TruncatedData { field }) if field == "ME\"Cube\".mpoly[2].loopstart"
+        ));
+        assert_eq!(mesh.try_positions().map(|positions| positions.len()), Ok(8));
+    }
+}